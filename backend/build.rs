@@ -0,0 +1,45 @@
+// File Path: backend/build.rs
+
+//! Build script: injects build metadata served by `GET /api/info`.
+//!
+//! `GIT_SHA` and `BUILD_TIME` may be provided by the environment (e.g. Docker build
+//! args, where `.git` isn't available); otherwise they are read from `git` and `date`.
+
+use std::process::Command;
+
+fn main() {
+    let git_sha = env_or_command("GIT_SHA", "git", &["rev-parse", "--short", "HEAD"]);
+    let build_time = env_or_command("BUILD_TIME", "date", &["-u", "+%Y-%m-%dT%H:%M:%SZ"]);
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rust_version = command_output(&rustc, &["--version"]).unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=GIT_SHA={}", git_sha);
+    println!("cargo:rustc-env=BUILD_TIME={}", build_time);
+    println!("cargo:rustc-env=RUST_VERSION={}", rust_version);
+
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    println!("cargo:rerun-if-env-changed=BUILD_TIME");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs/heads");
+}
+
+/// Returns the environment variable `name` if set, else the output of `program args`,
+/// else `"unknown"`.
+fn env_or_command(name: &str, program: &str, args: &[&str]) -> String {
+    std::env::var(name)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| command_output(program, args))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Runs a command and returns its trimmed stdout, or `None` if it fails.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let trimmed = stdout.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
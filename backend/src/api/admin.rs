@@ -0,0 +1,177 @@
+// File Path: backend/src/api/admin.rs
+
+// ====================================================================
+// SECTION 1: Imports
+// Description: Imports necessary libraries.
+// ====================================================================
+
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
+use axum::{
+    extract::{ConnectInfo, Path, Query, State},
+    http::{header, HeaderMap},
+    Json,
+};
+use serde_json::Value;
+use tracing::info;
+use tracing_subscriber::EnvFilter;
+
+use crate::{
+    api::state::AppState,
+    models::{ApiError, ApiResult, BroadcastRequest, ConnectionDetails, DrainRequest, LogLevelRequest},
+    services::redis_service,
+};
+
+
+// ====================================================================
+// SECTION 2: Logging Handlers
+// Description: Runtime control of tracing verbosity.
+// ====================================================================
+
+/// Replaces the global tracing filter without a restart.
+///
+/// Accepts any `EnvFilter` directive string (same syntax as `RUST_LOG`), e.g.
+/// `{"filter":"rust_websocket_backend=debug"}`. Invalid directives return 400.
+pub async fn set_log_level(
+    State(state): State<AppState>,
+    Json(request): Json<LogLevelRequest>,
+) -> ApiResult<Json<Value>> {
+    let filter = EnvFilter::try_new(&request.filter)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid log filter '{}': {}", request.filter, e)))?;
+
+    state.log_reload_handle
+        .reload(filter)
+        .map_err(|e| ApiError::InternalError(format!("Failed to reload log filter: {}", e)))?;
+
+    info!("Log filter changed to: {}", request.filter);
+    Ok(Json(serde_json::json!({ "filter": request.filter })))
+}
+
+
+// ====================================================================
+// SECTION 3: Subscription Handlers
+// Description: Visibility into which channels clients are watching.
+// ====================================================================
+
+/// Returns a map of subscribed channel name to subscriber count.
+///
+/// Wildcard subscriptions appear under their pattern (e.g. `ws_channel:job:*`).
+pub async fn get_channels(State(state): State<AppState>) -> Json<HashMap<String, usize>> {
+    Json(state.connection_manager.subscriber_counts().await)
+}
+
+/// Reports whether any client would receive a message published on `channel` (a full
+/// Redis channel name such as `ws_channel:job:<id>`), counting wildcard subscriptions.
+pub async fn get_channel(Path(channel): Path<String>, State(state): State<AppState>) -> Json<Value> {
+    let has_subscribers = state.connection_manager.has_subscribers(&channel).await;
+    Json(serde_json::json!({ "channel": channel, "has_subscribers": has_subscribers }))
+}
+
+/// Lists every live WebSocket connection with its delivery rate and slow-client flag.
+///
+/// `?slow=true` returns only connections currently flagged as slow.
+pub async fn get_connections(
+    Query(params): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Json<Vec<ConnectionDetails>> {
+    let only_slow = params.get("slow").is_some_and(|v| v.eq_ignore_ascii_case("true"));
+    let mut connections = state.connection_manager.all_connection_details().await;
+    if only_slow {
+        connections.retain(|connection| connection.slow);
+    }
+    Json(connections)
+}
+
+/// Returns everything known about one WebSocket connection: subscriptions, remote
+/// address, user agent, connect time, pause state, queue depth and delivery counters.
+///
+/// The id is the `connection_id` from the client's WELCOME frame; unknown ids are 404.
+pub async fn get_connection(
+    Path(connection_id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<Json<ConnectionDetails>> {
+    state.connection_manager
+        .connection_details(&connection_id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Connection '{}' is not connected", connection_id)))
+}
+
+/// How long drained clients get between the DRAINING notice and the close frame.
+const DRAIN_CLOSE_DELAY: Duration = Duration::from_secs(2);
+
+/// Drains the WebSocket clients watching channels under `channel_prefix` (admin token
+/// required), e.g. before redeploying the orchestrator for one job type.
+///
+/// Each matching connection gets `{"type":"DRAINING"}` and is closed with 1001 (going
+/// away) shortly after, so it reconnects elsewhere. Connections whose outbound queue
+/// is full are counted under `skipped` instead of holding up the request. The prefix
+/// must name a channel (`ws_channel:...`); draining everyone is what shutdown is for.
+pub async fn drain_connections(
+    State(state): State<AppState>,
+    Json(request): Json<DrainRequest>,
+) -> ApiResult<Json<Value>> {
+    let prefix = request.channel_prefix.trim();
+    if prefix.strip_prefix("ws_channel:").is_none_or(str::is_empty) {
+        return Err(ApiError::BadRequest(format!(
+            "channel_prefix must name channels under 'ws_channel:', got {:?}",
+            request.channel_prefix
+        )));
+    }
+
+    let (drained, skipped) = state.connection_manager.drain(prefix, DRAIN_CLOSE_DELAY).await;
+    Ok(Json(serde_json::json!({ "channel_prefix": prefix, "connections": drained, "skipped": skipped })))
+}
+
+
+// ====================================================================
+// SECTION 4: Reload Handlers
+// Description: Refresh cached state after config files change on disk.
+// ====================================================================
+
+/// Reloads everything derived from the config volume in one call (admin token required).
+///
+/// Schemas are re-read and swapped in atomically. YAML data and validation results are
+/// not cached (every request reads and validates the file), so edited data files are
+/// served as soon as they are saved.
+pub async fn reload_all(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+) -> ApiResult<Json<Value>> {
+
+    let caller = connect_info
+        .map(|ConnectInfo(addr)| addr.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let user_agent = headers.get(header::USER_AGENT).and_then(|v| v.to_str().ok()).unwrap_or("unknown");
+    info!("Full reload triggered by {} ({})", caller, user_agent);
+
+    let health = state.yaml_service.reload_schemas().await?;
+    info!("Full reload done: {} schema(s) loaded, {} failed", health.loaded.len(), health.failed.len());
+
+    Ok(Json(serde_json::json!({
+        "schemas_loaded": health.loaded.len(),
+        "schemas_failed": health.failed.len(),
+    })))
+}
+
+
+// ====================================================================
+// SECTION 5: Broadcast Handlers
+// Description: Announcements to every connected client.
+// ====================================================================
+
+/// Sends `message` to every WebSocket client of every backend instance (admin token
+/// required). Other instances pick it up from `ws_channel:broadcast`.
+pub async fn broadcast_message(
+    State(state): State<AppState>,
+    Json(request): Json<BroadcastRequest>,
+) -> ApiResult<Json<Value>> {
+    if request.message.trim().is_empty() {
+        return Err(ApiError::BadRequest("Broadcast message must not be empty".to_string()));
+    }
+
+    state.connection_manager.broadcast(&request.message).await;
+    info!("Broadcast sent from instance {}", redis_service::instance_id());
+
+    Ok(Json(serde_json::json!({ "origin": redis_service::instance_id() })))
+}
@@ -0,0 +1,125 @@
+// File Path: backend/src/api/auth.rs
+
+// ====================================================================
+// SECTION 1: Imports
+// Description: Imports necessary libraries.
+// ====================================================================
+
+use std::{collections::HashSet, env, sync::Arc};
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::warn;
+
+use crate::{
+    models::{ApiError, ApiResult},
+    services::ticket_store,
+};
+
+
+// ====================================================================
+// SECTION 2: Bearer Token Checks
+// Description: Shared authentication for HTTP endpoints that need it.
+// ====================================================================
+
+/// Requires `Authorization: Bearer <token>` matching `WS_AUTH_TOKEN` or `WS_ADMIN_TOKEN`.
+///
+/// Returns whether the caller used the admin token; missing or unknown tokens are 401.
+pub fn require_bearer(headers: &HeaderMap) -> ApiResult<bool> {
+    let token = bearer_token(headers)
+        .ok_or_else(|| ApiError::Unauthorized("Missing bearer token".to_string()))?;
+
+    let is_admin = ticket_store::env_token_matches("WS_ADMIN_TOKEN", token);
+    if !is_admin && !ticket_store::env_token_matches("WS_AUTH_TOKEN", token) {
+        warn!("Rejected request with an invalid bearer token");
+        return Err(ApiError::Unauthorized("Invalid bearer token".to_string()));
+    }
+    Ok(is_admin)
+}
+
+/// Extracts the token from an `Authorization: Bearer <token>` header.
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+
+// ====================================================================
+// SECTION 3: API Key Middleware
+// Description: Optional `X-API-Key` enforcement for the `/api` routes.
+// ====================================================================
+
+/// Header carrying the API key.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Keys accepted in `X-API-Key` (`API_KEYS`, comma-separated).
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeys {
+    keys: HashSet<String>,
+}
+
+impl ApiKeys {
+    /// Loads the accepted keys from `API_KEYS`; blank entries are ignored.
+    pub fn from_env() -> Self {
+        let raw = env::var("API_KEYS").unwrap_or_default();
+        Self::new(raw.split(',').map(str::trim).filter(|k| !k.is_empty()).map(str::to_string))
+    }
+
+    pub fn new(keys: impl IntoIterator<Item = String>) -> Self {
+        Self { keys: keys.into_iter().collect() }
+    }
+
+    pub fn accepts(&self, key: &str) -> bool {
+        self.keys.contains(key)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// Whether `/api` routes require an API key (`REQUIRE_API_KEY`, default false for local dev).
+pub fn api_key_required() -> bool {
+    env::var("REQUIRE_API_KEY")
+        .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+        .unwrap_or(false)
+}
+
+/// Middleware rejecting requests without the admin bearer token (`WS_ADMIN_TOKEN`) with
+/// a 401 JSON error.
+pub async fn require_admin_bearer(request: Request, next: Next) -> Response {
+    match require_bearer(request.headers()) {
+        Ok(true) => next.run(request).await,
+        Ok(false) => ApiError::Unauthorized("This endpoint requires the admin token".to_string()).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Middleware rejecting requests without a valid `X-API-Key` with a 401 JSON error.
+pub async fn require_api_key(
+    State(keys): State<Arc<ApiKeys>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let key = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    match key {
+        Some(key) if keys.accepts(key) => next.run(request).await,
+        Some(_) => {
+            warn!("Rejected {} {}: invalid API key", request.method(), request.uri().path());
+            ApiError::Unauthorized("Invalid API key".to_string()).into_response()
+        }
+        None => ApiError::Unauthorized("Missing X-API-Key header".to_string()).into_response(),
+    }
+}
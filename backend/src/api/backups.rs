@@ -0,0 +1,34 @@
+// File Path: backend/src/api/backups.rs
+
+// ====================================================================
+// SECTION 1: Imports
+// Description: Imports necessary libraries.
+// ====================================================================
+
+use axum::Json;
+
+use crate::{
+    models::{ApiError, ApiResult, BackupResponse},
+    services::backup_listing,
+};
+
+
+// ====================================================================
+// SECTION 2: Backup Listing Handlers
+// Description: Lists the backup files produced by backup jobs.
+// ====================================================================
+
+/// Lists every backup file under `BACKUP_DIR`, grouped by device directory.
+///
+/// Listings over `BACKUP_LISTING_COMPRESS_THRESHOLD` entries are returned gzipped in
+/// `files_gzip_base64`. A missing backup directory returns 404.
+pub async fn list_backups() -> ApiResult<Json<BackupResponse>> {
+    let root = backup_listing::backup_dir();
+    if !root.is_dir() {
+        return Err(ApiError::NotFound(format!("Backup directory '{}' not found", root.display())));
+    }
+
+    let files = backup_listing::scan_backup_dir(&root).await?;
+    let message = format!("{} backup files", files.len());
+    BackupResponse::new("success", message, None, files).map(Json)
+}
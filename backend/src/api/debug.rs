@@ -0,0 +1,241 @@
+// File Path: backend/src/api/debug.rs
+
+// ====================================================================
+// SECTION 1: Imports and Constants
+// Description: Imports necessary libraries and defines global constants.
+// ====================================================================
+
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    path::Path,
+    time::{Duration, Instant},
+};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{info, warn};
+
+use crate::{
+    api::state::AppState,
+    models::{ApiError, ApiResult, JobEvent, ReplayRequest},
+    services::redis_service::{self, RedisMode},
+    util::input::validate_relative_path,
+};
+
+/// Sub-directory of the data directory holding recorded event fixtures.
+const FIXTURE_DIR: &str = "fixtures";
+
+/// Delay between replayed events when the request doesn't specify one.
+const DEFAULT_REPLAY_DELAY_MS: u64 = 500;
+
+/// Upper bound on `delay_ms` between replayed events.
+const MAX_REPLAY_DELAY_MS: u64 = 10_000;
+
+/// Most events a single replay publishes; longer lists are truncated.
+const MAX_REPLAY_EVENTS: usize = 1000;
+
+/// How long a round-trip probe waits for its message when `?timeout_ms` isn't given.
+const DEFAULT_ROUNDTRIP_TIMEOUT_MS: u64 = 5000;
+
+/// Upper bound on `?timeout_ms` for round-trip probes.
+const MAX_ROUNDTRIP_TIMEOUT_MS: u64 = 30_000;
+
+
+// ====================================================================
+// SECTION 2: Replay Handler
+// Description: Re-publishes recorded JobEvents to Redis to simulate a job.
+// ====================================================================
+
+/// Replays a list of recorded `JobEvent`s (inline or from a fixture file) to Redis.
+///
+/// Each event is published to `ws_channel:job:<job_id>` so it flows through the
+/// normal listener/broadcast path. Publishing runs in a background task; the
+/// response only confirms how many events were scheduled. `delay_ms` is capped at
+/// `MAX_REPLAY_DELAY_MS` and at most `MAX_REPLAY_EVENTS` events are replayed, so one
+/// request can't keep the task busy indefinitely.
+pub async fn replay_events(
+    State(state): State<AppState>,
+    Json(request): Json<ReplayRequest>,
+) -> ApiResult<Json<Value>> {
+    let mut events = match (request.events, request.fixture) {
+        (Some(events), _) => events,
+        (None, Some(fixture)) => load_fixture(&state, &fixture).await?,
+        (None, None) => {
+            return Err(ApiError::BadRequest(
+                "Either 'events' or 'fixture' must be provided".to_string(),
+            ))
+        }
+    };
+
+    if events.len() > MAX_REPLAY_EVENTS {
+        warn!("Replay truncated from {} to {} events", events.len(), MAX_REPLAY_EVENTS);
+        events.truncate(MAX_REPLAY_EVENTS);
+    }
+    let delay = Duration::from_millis(
+        request.delay_ms.unwrap_or(DEFAULT_REPLAY_DELAY_MS).min(MAX_REPLAY_DELAY_MS),
+    );
+    let event_count = events.len();
+    info!("Replaying {} recorded events with {:?} delay", event_count, delay);
+
+    tokio::spawn(async move {
+        for (index, event) in events.iter().enumerate() {
+            if index > 0 {
+                tokio::time::sleep(delay).await;
+            }
+
+            let channel = redis_service::job_channel(&event.job_id);
+            let payload = match serde_json::to_string(event) {
+                Ok(p) => p,
+                Err(e) => {
+                    warn!("Failed to serialize replay event for job {}: {}", event.job_id, e);
+                    continue;
+                }
+            };
+
+            if let Err(e) = redis_service::publish_message(&channel, &payload).await {
+                warn!("Replay aborted, failed to publish to {}: {}", channel, e);
+                break;
+            }
+        }
+        info!("Replay finished ({} events)", event_count);
+    });
+
+    Ok(Json(serde_json::json!({
+        "status": "replaying",
+        "events": event_count,
+        "delay_ms": delay.as_millis() as u64
+    })))
+}
+
+
+// ====================================================================
+// SECTION 3: Round-Trip Probe
+// Description: One-call check that Redis Pub/Sub and the broadcast path work end to end.
+// ====================================================================
+
+/// Publishes a uniquely tagged message on `ws_channel:roundtrip:<uuid>` and waits for the
+/// Redis listener to relay it onto the broadcast channel, returning the latency.
+///
+/// Open under `DEV_MODE`, otherwise the route requires the admin bearer token (see
+/// `routes::debug::probe_routes`). `?timeout_ms`
+/// (default 5000, max 30000) bounds the wait; running out is a 504. Only meaningful in
+/// `REDIS_MODE=pubsub`, since the streams listener doesn't read Pub/Sub channels.
+pub async fn ping_roundtrip(
+    Query(params): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> ApiResult<Json<Value>> {
+    if RedisMode::from_env() != RedisMode::PubSub {
+        return Err(ApiError::BadRequest("The round-trip probe requires REDIS_MODE=pubsub".to_string()));
+    }
+
+    let timeout = Duration::from_millis(
+        params
+            .get("timeout_ms")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_ROUNDTRIP_TIMEOUT_MS)
+            .min(MAX_ROUNDTRIP_TIMEOUT_MS),
+    );
+    let tag = uuid::Uuid::new_v4().to_string();
+    let channel = redis_service::roundtrip_channel(&tag);
+
+    // Subscribe before publishing so a fast relay can't be missed.
+    let mut rx = state.connection_manager.broadcast_sender.subscribe();
+    let started = Instant::now();
+    redis_service::publish_roundtrip_probe(&channel, &tag)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to publish round-trip probe: {}", e)))?;
+
+    let relayed = tokio::time::timeout(timeout, async {
+        loop {
+            match rx.recv().await {
+                Ok(msg) if msg.channel == channel => return true,
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return false,
+            }
+        }
+    })
+    .await;
+
+    match relayed {
+        Ok(true) => {
+            let latency = started.elapsed();
+            info!("Round-trip probe on {} returned in {:?}", channel, latency);
+            Ok(Json(serde_json::json!({
+                "status": "ok",
+                "channel": channel,
+                "latency_ms": latency.as_secs_f64() * 1000.0
+            })))
+        }
+        Ok(false) => Err(ApiError::InternalError("Broadcast channel closed during round-trip probe".to_string())),
+        Err(_) => {
+            warn!("Round-trip probe on {} not relayed within {:?}", channel, timeout);
+            Err(ApiError::Timeout(format!(
+                "Probe on {} was not relayed back within {} ms",
+                channel,
+                timeout.as_millis()
+            )))
+        }
+    }
+}
+
+
+// ====================================================================
+// SECTION 4: Fixture Loading
+// Description: Reads recorded events from the shared data directory.
+// ====================================================================
+
+/// Loads a fixture file (JSON or YAML list of `JobEvent`s, or a `.jsonl` Redis mirror
+/// capture) from `<data_dir>/fixtures`.
+async fn load_fixture(state: &AppState, fixture: &str) -> ApiResult<Vec<JobEvent>> {
+    let fixture = validate_relative_path("fixture name", fixture)?;
+    let path = Path::new(FIXTURE_DIR).join(fixture);
+    let content = state.yaml_service.read_data_text(&path).await.map_err(|e| match e {
+        ApiError::FileNotFound(_) => ApiError::FileNotFound(format!("Fixture not found: {}", path.display())),
+        other => other,
+    })?;
+
+    // Captures written by REDIS_MIRROR_FILE hold one relayed message per line.
+    if path.extension().is_some_and(|ext| ext == "jsonl") {
+        return Ok(events_from_capture(&content));
+    }
+
+    // YAML is a superset of JSON, so one parser handles both fixture formats.
+    serde_yaml::from_str(&content)
+        .map_err(|e| ApiError::DeserializationError(format!("Invalid fixture {}: {}", fixture, e)))
+}
+
+/// Extracts the job events from a Redis mirror capture, skipping non-job messages.
+fn events_from_capture(content: &str) -> Vec<JobEvent> {
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|message| {
+            let data = message.get("data")?.as_str()?;
+            serde_json::from_str::<JobEvent>(data).ok()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file_store::InMemoryFileStore;
+
+    #[tokio::test]
+    async fn fixtures_are_read_through_the_data_store() {
+        let store = InMemoryFileStore::new();
+        let event = JobEvent::new("j1", "r1", "backup", "progress", "running", serde_json::json!({}));
+        store.insert("/data/fixtures/backup.json", serde_json::to_string(&vec![event]).unwrap());
+        let state = AppState::for_tests_with_store(store).await;
+
+        let events = load_fixture(&state, "backup.json").await.unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].job_id, "j1");
+
+        let missing = load_fixture(&state, "absent.json").await;
+        assert!(matches!(missing, Err(ApiError::FileNotFound(_))), "got {:?}", missing);
+    }
+}
@@ -0,0 +1,21 @@
+// File Path: backend/src/api/features.rs
+
+// ====================================================================
+// SECTION 1: Imports
+// Description: Imports necessary libraries.
+// ====================================================================
+
+use axum::Json;
+
+use crate::models::Features;
+
+
+// ====================================================================
+// SECTION 2: Feature Flag Handlers
+// Description: Exposes environment-driven feature flags to clients.
+// ====================================================================
+
+/// Returns the feature flags derived from `FEATURE_*` environment variables.
+pub async fn get_features() -> Json<Features> {
+    Json(Features::from_env())
+}
@@ -0,0 +1,287 @@
+// File Path: backend/src/api/jobs.rs
+
+// ====================================================================
+// SECTION 1: Imports
+// Description: Imports necessary libraries.
+// ====================================================================
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    Json
+};
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+use crate::{
+    api::{auth, state::AppState},
+    models::{ApiError, ApiResult, BackupRequest, DeviceSummary, InventoryDevice, JobEvent, JobStatus},
+    services::{inventory, redis_service::{self, RedisMessage}},
+    util::input::validate_relative_path,
+};
+
+
+// ====================================================================
+// SECTION 2: Job Status Handlers
+// Description: Polling-friendly endpoints backed by the job event store.
+// ====================================================================
+
+/// Returns the current aggregated status of a single job.
+///
+/// Computed from the latest recorded event (plus any progress data), so clients
+/// that can't hold a WebSocket open can poll instead. Unknown jobs return 404.
+pub async fn get_job_status(
+    Path(job_id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<Json<JobStatus>> {
+    state.job_store
+        .job_status(&job_id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Job '{}' not found", job_id)))
+}
+
+/// Returns a job's recorded events, optionally only those newer than `?since=<RFC3339>`.
+///
+/// Events are ordered ascending by timestamp so a client can resume polling after a
+/// WebSocket drop by passing the timestamp of the last event it saw.
+pub async fn get_job_events(
+    Path(job_id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<Value>>> {
+    let since = parse_since(&params)?;
+
+    state.job_store
+        .events_since(&job_id, since)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Job '{}' not found", job_id)))
+}
+
+/// Returns the most recent event seen for a job, from the bounded latest-event cache.
+///
+/// Cheaper than `/events` for "what is this job doing now"; unknown jobs, and finished
+/// jobs past the cache TTL, return 404.
+pub async fn get_latest_job_event(
+    Path(job_id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<Json<JobEvent>> {
+    state.connection_manager
+        .latest_events
+        .get(&job_id)
+        .await
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("No recent event for job '{}'", job_id)))
+}
+
+
+// ====================================================================
+// SECTION 3: Device Summary Handlers
+// Description: Fleet-level aggregation of recorded jobs.
+// ====================================================================
+
+/// Returns per-device job counts (`running`, `completed`, `failed`) and last activity.
+///
+/// `?since=<RFC3339>` limits the window to jobs with an event after that time.
+pub async fn get_device_summary(
+    Query(params): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<DeviceSummary>>> {
+    let since = parse_since(&params)?;
+    Ok(Json(state.job_store.device_summaries(since).await))
+}
+
+
+// ====================================================================
+// SECTION 4: Job Publish Handlers
+// Description: Lets HTTP-only producers push events and control requests onto job channels.
+// ====================================================================
+
+/// Publishes a `JobEvent` body to `ws_channel:job:<job_id>` (requires a bearer token).
+///
+/// The body is validated against its job type's event schema (see
+/// `YamlService::job_event_schema_name`) and its `job_id` must match the path. If Redis
+/// is unreachable the event is delivered through the local broadcast channel instead.
+/// Returns how many clients are subscribed.
+pub async fn publish_job_event(
+    Path(job_id): Path<String>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> ApiResult<Json<Value>> {
+    auth::require_bearer(&headers)?;
+    state.yaml_service.validate_job_event(&body)?;
+
+    let event: JobEvent = serde_json::from_value(body)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid job event: {}", e)))?;
+    if event.job_id != job_id {
+        return Err(ApiError::BadRequest(format!(
+            "Event job_id '{}' does not match path job_id '{}'",
+            event.job_id, job_id
+        )));
+    }
+
+    let channel = redis_service::job_channel(&job_id);
+    let delivery = publish_event(&state, &channel, &event).await?;
+
+    let subscribers = state.connection_manager.subscriber_count(&channel).await;
+    info!("Published {} event for job {} via {} ({} subscriber(s))", event.event_type, job_id, delivery, subscribers);
+
+    Ok(Json(serde_json::json!({
+        "channel": channel,
+        "delivery": delivery,
+        "subscribers": subscribers
+    })))
+}
+
+
+/// Asks the orchestrator to cancel a job and records the request (requires a bearer token).
+///
+/// Publishes `{"type":"cancel",...}` on `ws_channel:control:<job_id>` (the orchestrator
+/// performs the actual cancellation) and a `cancel_requested` event on the job channel,
+/// so the intent shows up in the event history and to subscribed clients. Unknown jobs
+/// are 404. Returns the job's current status.
+pub async fn cancel_job(
+    Path(job_id): Path<String>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<JobStatus>> {
+    auth::require_bearer(&headers)?;
+
+    let status = state.job_store
+        .job_status(&job_id)
+        .await
+        .ok_or_else(|| ApiError::NotFound(format!("Job '{}' not found", job_id)))?;
+
+    // The orchestrator only hears Redis, so there is no local fallback for the control message.
+    let control_channel = redis_service::control_channel(&job_id);
+    let control = serde_json::json!({ "type": "cancel", "job_id": job_id, "requested_at": Utc::now() });
+    redis_service::publish_message(&control_channel, &control.to_string()).await?;
+
+    let event = JobEvent::new(
+        &job_id,
+        status.device.as_deref().unwrap_or_default(),
+        status.job_type.as_deref().unwrap_or_default(),
+        "cancel_requested",
+        &status.status,
+        serde_json::json!({}),
+    );
+    publish_event(&state, &redis_service::job_channel(&job_id), &event).await?;
+
+    info!("Cancel requested for job {} (status: {})", job_id, status.status);
+    Ok(Json(status))
+}
+
+
+// ====================================================================
+// SECTION 5: Backup Handlers
+// Description: Starts backup jobs, expanding inventory files into their devices.
+// ====================================================================
+
+/// Queues a backup job (requires a bearer token).
+///
+/// With `inventory_file`, the file is resolved under the inventory directory, validated
+/// against the `inventory` schema and expanded into its devices; otherwise `hostname` is
+/// the only target. A `device_queued` event per device is published on the new job's
+/// channel for the orchestrator and subscribed clients. Credentials are never published.
+pub async fn start_backup(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<BackupRequest>,
+) -> ApiResult<Json<Value>> {
+    auth::require_bearer(&headers)?;
+
+    let devices = match request.inventory_file.as_deref().filter(|file| !file.trim().is_empty()) {
+        Some(file) => {
+            let file = validate_relative_path("inventory_file", file)?;
+            inventory::load_inventory(&state.yaml_service, file).await?
+        }
+        None if request.hostname.trim().is_empty() => {
+            return Err(ApiError::BadRequest("Either 'hostname' or 'inventory_file' is required".to_string()));
+        }
+        None => vec![InventoryDevice {
+            host_name: request.hostname.clone(),
+            ip_address: request.hostname.clone(),
+            vendor: None,
+            platform: None,
+            location: None,
+            role: None,
+        }],
+    };
+
+    let job_id = uuid::Uuid::new_v4().to_string();
+    let channel = redis_service::job_channel(&job_id);
+    for device in &devices {
+        let data = serde_json::to_value(device).map_err(|e| ApiError::SerializationError(e.to_string()))?;
+        let event = JobEvent::new(&job_id, &device.host_name, "backup", "device_queued", "queued", data);
+        publish_event(&state, &channel, &event).await?;
+    }
+
+    info!(
+        "Queued backup job {} for {} device(s){}",
+        job_id,
+        devices.len(),
+        request.inventory_file.as_deref().map(|f| format!(" from inventory {}", f)).unwrap_or_default()
+    );
+
+    Ok(Json(serde_json::json!({
+        "job_id": job_id,
+        "channel": channel,
+        "devices": devices
+    })))
+}
+
+
+// ====================================================================
+// SECTION 6: Helpers
+// ====================================================================
+
+/// Publishes a job event to Redis, falling back to the local broadcast channel when
+/// Redis is unreachable. Returns how it was delivered (`"redis"` or `"local"`).
+async fn publish_event(state: &AppState, channel: &str, event: &JobEvent) -> ApiResult<&'static str> {
+    let payload = serde_json::to_string(event)
+        .map_err(|e| ApiError::SerializationError(e.to_string()))?;
+
+    match redis_service::publish_message(channel, &payload).await {
+        Ok(()) => Ok("redis"),
+        Err(e) => {
+            warn!("Redis publish to {} failed ({}); delivering locally", channel, e);
+            redis_service::broadcast_to_clients(
+                &state.connection_manager.broadcast_sender,
+                &state.connection_manager.clients,
+                RedisMessage::new(channel, payload),
+            );
+            Ok("local")
+        }
+    }
+}
+
+/// Parses the optional `?since=<RFC3339>` query parameter; malformed values are 400.
+fn parse_since(params: &HashMap<String, String>) -> ApiResult<Option<DateTime<Utc>>> {
+    params
+        .get("since")
+        .map(|raw| {
+            DateTime::parse_from_rfc3339(raw)
+                .map(|ts| ts.with_timezone(&Utc))
+                .map_err(|e| ApiError::BadRequest(format!("Invalid 'since' timestamp '{}': {}", raw, e)))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, response::IntoResponse};
+
+    #[tokio::test]
+    async fn cancel_without_bearer_token_is_unauthorized() {
+        let state = AppState::for_tests().await;
+        let result = cancel_job(Path("j1".to_string()), State(state), HeaderMap::new()).await;
+
+        let response = result.expect_err("cancel must require a bearer token").into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -7,3 +7,12 @@
 pub mod state;
 // pub mod error; // Placeholder for a dedicated error handling module
 pub mod navigation;
+pub mod debug;
+pub mod jobs;
+pub mod admin;
+pub mod features;
+pub mod validation;
+pub mod tickets;
+pub mod backups;
+pub mod auth;
+pub mod sse;
@@ -7,22 +7,26 @@
 
 use axum::{
     extract::{Query, State}, 
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
     Json
 };
 use std::collections::HashMap;
 use serde_json::Value;
 
 use crate::{
-    api::state::AppState, 
-    models::{
-        ApiResult, 
-        // Note: NavigationConfig is no longer directly used in get_navigation, 
-        // but kept here as a reference model.
-        // ApiError, NavigationConfig 
-    }
+    api::state::AppState,
+    services::yaml_service::DataFormat,
+    models::{ApiError, ApiResult, NavigationConfig, NavigationItem, NavigationQuery},
+    util::input::{validate_identifier, validate_relative_path},
 };
 
 const DEFAULT_NAVIGATION_SCHEMA: &str = "navigation";
+const SETTINGS_NAVIGATION_SCHEMA: &str = "settings_navigation";
+const YAML_CONTENT_TYPE: &str = "text/yaml";
+
+/// Header naming an alternate schema to validate against (A/B schema testing).
+const SCHEMA_OVERRIDE_HEADER: &str = "x-schema-override";
 
 
 // ====================================================================
@@ -33,24 +37,111 @@ const DEFAULT_NAVIGATION_SCHEMA: &str = "navigation";
 /// Fetches and returns the primary navigation configuration.
 /// 
 /// This handler loads the default 'navigation.yaml', validates it against 
-/// the schema, and returns the resulting JSON data.
+/// the schema, and returns it type-checked as a `NavigationConfig`. When a `role` query
+/// parameter is given, the role's overlay file is merged over the base first.
 pub async fn get_navigation(
-    Query(params): Query<HashMap<String, String>>, 
+    Query(params): Query<NavigationQuery>,
     State(state): State<AppState>,
-) -> ApiResult<Json<Value>> {
-    let schema_name = params.get("schema").map(|s| s.as_str()).unwrap_or(DEFAULT_NAVIGATION_SCHEMA);
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let schema_name = schema_or(&params, DEFAULT_NAVIGATION_SCHEMA)?;
     
     // 1. Fetch data: Loads the file, converts to Value, and validates against the schema.
-    let yaml_data = state.yaml_service
-        .get_yaml_data(schema_name, None)
-        .await?;
+    // With `?role=`, the role overlay (e.g. navigation.admin.yaml) is deep-merged first.
+    let yaml_data = match params.role.as_deref() {
+        Some(role) => {
+            let overlay_file = role_overlay_file(schema_name, role)?;
+            state.yaml_service
+                .get_merged_yaml_data(
+                    schema_name,
+                    &overlay_file,
+                    schema_override(&headers)?,
+                    data_format(&params)?,
+                    allow_additional(&params),
+                    !validation_disabled(&params),
+                )
+                .await?
+        }
+        None if validation_disabled(&params) => state.yaml_service
+            .get_unvalidated_yaml_data(schema_name, None)
+            .await?,
+        None => state.yaml_service
+            .get_yaml_data_with_override(
+                schema_name,
+                None,
+                schema_override(&headers)?,
+                data_format(&params)?,
+                allow_additional(&params),
+            )
+            .await?,
+    };
 
-    // 2. FIX APPLIED: The previous attempt to deserialize into NavigationConfig was 
-    // removed here because the YAML file structure (an array of items) did not match
-    // the struct's expected root structure (an object with an 'items' key).
-    
-    // 3. Return the raw, validated JSON Value directly.
-    Ok(Json(yaml_data)) 
+    // 2. Type-check and semantically validate, then return it in the format the client asked for.
+    let normalized = typed_navigation(yaml_data)?;
+    negotiate(&headers, normalized)
+}
+
+/// Round-trips navigation data through the typed `NavigationConfig`.
+///
+/// The YAML root may be either an array of items or an object with an `items` key;
+/// the response keeps the root layout of the file, so a bare array (what the frontend's
+/// NavigationContext reads) stays a bare array. Also runs the semantic checks JSON
+/// Schema can't express (unique ids/paths across the tree).
+fn typed_navigation(yaml_data: Value) -> ApiResult<Value> {
+    let bare_list = yaml_data.is_array();
+    let config = NavigationConfig::from_value(yaml_data)?;
+    validate_navigation(&config)?;
+
+    let normalized = if bare_list { serde_json::to_value(&config.items) } else { serde_json::to_value(&config) };
+    normalized.map_err(|e| ApiError::SerializationError(e.to_string()))
+}
+
+/// Parses the optional `?format=yaml|json|toml` override for ambiguous file extensions.
+fn data_format(params: &NavigationQuery) -> ApiResult<Option<DataFormat>> {
+    params.format.as_deref().map(DataFormat::from_name).transpose()
+}
+
+/// True when the caller explicitly opted out of schema validation with `?validate=false`.
+/// Otherwise an unknown schema name is a 404, not a silently unvalidated response.
+fn validation_disabled(params: &NavigationQuery) -> bool {
+    params.validate.as_deref().is_some_and(|v| v.eq_ignore_ascii_case("false"))
+}
+
+/// True when `?allow_additional=true` asks validation to tolerate fields the schema
+/// doesn't declare (rolling upgrades where the data is ahead of the schema).
+fn allow_additional(params: &NavigationQuery) -> bool {
+    params.allow_additional.as_deref().is_some_and(|v| v.eq_ignore_ascii_case("true"))
+}
+
+/// True when `?apply_defaults=true` asks for a copy of the data with schema defaults filled in.
+fn apply_defaults(params: &NavigationQuery) -> bool {
+    params.apply_defaults.as_deref().is_some_and(|v| v.eq_ignore_ascii_case("true"))
+}
+
+/// The `?schema=` parameter, or the handler's default schema when it is absent.
+/// A malformed schema name is a 400 (see `validate_identifier`).
+fn schema_or<'a>(params: &'a NavigationQuery, default: &'a str) -> ApiResult<&'a str> {
+    match params.schema.as_deref() {
+        Some(schema) => validate_identifier("schema", schema),
+        None => Ok(default),
+    }
+}
+
+/// The `?file=` parameter, if given. A malformed path is a 400 (see `validate_relative_path`).
+fn file_param(params: &NavigationQuery) -> ApiResult<Option<&str>> {
+    params.file.as_deref().map(|file| validate_relative_path("file", file)).transpose()
+}
+
+/// Builds the overlay file name for a role (e.g. `navigation.admin.yaml`).
+///
+/// Roles are restricted to simple identifiers so they can't be used to build arbitrary paths.
+fn role_overlay_file(schema_name: &str, role: &str) -> ApiResult<String> {
+    let valid = !role.is_empty()
+        && role.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(ApiError::BadRequest(format!("Invalid role: {}", role)));
+    }
+    Ok(format!("{}.{}.yaml", schema_name, role))
 }
 
 /// Fetches settings-specific navigation.
@@ -58,16 +149,74 @@ pub async fn get_navigation(
 /// This route uses a separate schema/data file (e.g., 'settings_navigation.yaml')
 /// to serve specialized navigation items.
 pub async fn get_settings_navigation(
-    Query(params): Query<HashMap<String, String>>, 
+    Query(params): Query<NavigationQuery>,
     State(state): State<AppState>,
-) -> ApiResult<Json<Value>> {
-    let schema_name = params.get("schema").map(|s| s.as_str()).unwrap_or("settings_navigation");
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let schema_name = schema_or(&params, SETTINGS_NAVIGATION_SCHEMA)?;
     
-    let yaml_data = state.yaml_service
-        .get_yaml_data(schema_name, None)
-        .await?;
+    let yaml_data = if validation_disabled(&params) {
+        state.yaml_service
+            .get_unvalidated_yaml_data(schema_name, None)
+            .await?
+    } else {
+        state.yaml_service
+            .get_yaml_data_with_override(
+                schema_name,
+                None,
+                schema_override(&headers)?,
+                data_format(&params)?,
+                allow_additional(&params),
+            )
+            .await?
+    };
+
+    negotiate(&headers, yaml_data)
+}
+
+
+/// Fetches every navigation variant in one call.
+///
+/// Loads the main and settings navigation (plus any other loaded schema whose name
+/// ends in "navigation") concurrently and returns them keyed by schema name. Each
+/// key holds either `{"data": ...}` or `{"error": "..."}`, so one bad file doesn't
+/// fail the whole response.
+pub async fn get_all_navigation(State(state): State<AppState>) -> ApiResult<Json<Value>> {
+    let service = &state.yaml_service;
+
+    let extra_names: Vec<String> = service
+        .list_available_schemas()
+        .await?
+        .into_iter()
+        .filter(|name| {
+            name.ends_with("navigation")
+                && name != DEFAULT_NAVIGATION_SCHEMA
+                && name != SETTINGS_NAVIGATION_SCHEMA
+        })
+        .collect();
 
-    Ok(Json(yaml_data))
+    let (main, settings, extras) = tokio::join!(
+        service.get_yaml_data(DEFAULT_NAVIGATION_SCHEMA, None),
+        service.get_yaml_data(SETTINGS_NAVIGATION_SCHEMA, None),
+        futures::future::join_all(extra_names.iter().map(|name| service.get_yaml_data(name, None))),
+    );
+
+    let mut all = serde_json::Map::new();
+    all.insert(DEFAULT_NAVIGATION_SCHEMA.to_string(), per_key_result(main));
+    all.insert(SETTINGS_NAVIGATION_SCHEMA.to_string(), per_key_result(settings));
+    for (name, result) in extra_names.into_iter().zip(extras) {
+        all.insert(name, per_key_result(result));
+    }
+
+    Ok(Json(Value::Object(all)))
+}
+
+/// Wraps a single load result as `{"data": ...}` or `{"error": "..."}`.
+fn per_key_result(result: ApiResult<Value>) -> Value {
+    match result {
+        Ok(data) => serde_json::json!({ "data": data }),
+        Err(e) => serde_json::json!({ "error": e.to_string() }),
+    }
 }
 
 
@@ -80,18 +229,251 @@ pub async fn get_settings_navigation(
 /// 
 /// This is typically used for debugging, returning a JSON object that explicitly 
 /// states if the data is 'valid' along with the data itself or validation errors.
+/// `?apply_defaults=true` adds a `repaired` copy with the schema's `default`s filled in.
+/// The response carries the file's `ETag`, to be sent back as `If-Match` when saving.
 pub async fn get_navigation_from_yaml(
-    Query(params): Query<HashMap<String, String>>, 
+    Query(params): Query<NavigationQuery>,
     State(state): State<AppState>,
-) -> ApiResult<Json<Value>> {
-    let file_path = params.get("file").map(|s| s.as_str());
-    let schema_name = params.get("schema").map(|s| s.as_str()).unwrap_or(DEFAULT_NAVIGATION_SCHEMA);
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let file_path = file_param(&params)?;
+    let schema_name = schema_or(&params, DEFAULT_NAVIGATION_SCHEMA)?;
 
-    // This service call returns a Value structured as: {"valid": bool, "data": Value}
-    let validated_result = state.yaml_service
-        .validate_yaml_data(schema_name, file_path)
+    // This service call returns a Value structured as {"valid": bool, "data": Value},
+    // plus the ETag of exactly the content that was validated.
+    let (mut validated_result, etag) = state.yaml_service
+        .validate_yaml_data_with_override(
+            schema_name,
+            file_path,
+            schema_override(&headers)?,
+            data_format(&params)?,
+            allow_additional(&params),
+        )
         .await?;
 
+    // With ?apply_defaults=true, add {"repaired": Value}: the data with schema defaults filled in.
+    if apply_defaults(&params) {
+        let validation_name = schema_override(&headers)?.unwrap_or(schema_name);
+        let repaired = state.yaml_service.apply_defaults(validation_name, &validated_result["data"])?;
+        validated_result["repaired"] = repaired;
+    }
+
     // The result from validate_yaml_data is a JSON Value confirming validation status
-    Ok(Json(validated_result))
+    let mut response = negotiate(&headers, validated_result)?;
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    Ok(response)
+}
+
+/// Replaces a data file with the JSON body (admin bearer token required).
+///
+/// The body is validated against `?schema=` (default `navigation`) and written to
+/// `?file=` in that file's format. `If-Match` must carry the ETag from the last GET:
+/// a missing header is 428 and a stale one 409, so concurrent editors can't silently
+/// overwrite each other. Returns the new ETag.
+pub async fn save_navigation_yaml(
+    Query(params): Query<NavigationQuery>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> ApiResult<Response> {
+    let schema_name = schema_or(&params, DEFAULT_NAVIGATION_SCHEMA)?;
+    let if_match = headers.get(header::IF_MATCH).and_then(|value| value.to_str().ok());
+    let etag = state.yaml_service
+        .save_yaml_data(schema_name, file_param(&params)?, &body, if_match)
+        .await?;
+
+    let mut response = Json(serde_json::json!({ "saved": true, "etag": etag })).into_response();
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    Ok(response)
+}
+
+
+// ====================================================================
+// SECTION 4: Semantic Navigation Validation
+// Description: Tree-wide checks performed after JSON-schema validation.
+// ====================================================================
+
+/// Verifies that every item `id` and every `path` is unique across the whole
+/// navigation tree (including nested children).
+///
+/// Items without an id (e.g. leaf children) are not checked for id uniqueness.
+/// Returns `ApiError::ValidationError` listing each duplicate and its locations.
+pub fn validate_navigation(config: &NavigationConfig) -> ApiResult<()> {
+    let mut ids: HashMap<&str, Vec<String>> = HashMap::new();
+    let mut paths: HashMap<&str, Vec<String>> = HashMap::new();
+    collect_navigation_keys(&config.items, "items", &mut ids, &mut paths);
+
+    let mut problems: Vec<String> = Vec::new();
+    for (kind, seen) in [("id", &ids), ("path", &paths)] {
+        let mut duplicates: Vec<_> = seen.iter().filter(|(_, locations)| locations.len() > 1).collect();
+        duplicates.sort();
+        for (value, locations) in duplicates {
+            problems.push(format!("duplicate {} '{}' at {}", kind, value, locations.join(", ")));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::ValidationError(format!(
+            "Navigation validation failed: {}",
+            problems.join("; ")
+        )))
+    }
+}
+
+/// Recursively records the location (e.g. `items[1].children[0]`) of every id and path.
+fn collect_navigation_keys<'a>(
+    items: &'a [NavigationItem],
+    prefix: &str,
+    ids: &mut HashMap<&'a str, Vec<String>>,
+    paths: &mut HashMap<&'a str, Vec<String>>,
+) {
+    for (index, item) in items.iter().enumerate() {
+        let location = format!("{}[{}]", prefix, index);
+
+        if !item.id.is_empty() {
+            ids.entry(item.id.as_str()).or_default().push(location.clone());
+        }
+        if let Some(path) = item.path.as_deref() {
+            paths.entry(path).or_default().push(location.clone());
+        }
+        if let Some(children) = &item.children {
+            collect_navigation_keys(children, &format!("{}.children", location), ids, paths);
+        }
+    }
+}
+
+
+// ====================================================================
+// SECTION 5: Request Header Helpers
+// Description: Content negotiation (JSON default, YAML via Accept) and
+// per-request schema overrides.
+// ====================================================================
+
+/// Returns the schema named by the `X-Schema-Override` header, if present.
+/// A malformed schema name is a 400 (see `validate_identifier`).
+fn schema_override(headers: &HeaderMap) -> ApiResult<Option<&str>> {
+    headers
+        .get(SCHEMA_OVERRIDE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|schema| validate_identifier("schema override", schema))
+        .transpose()
+}
+
+/// Returns true if the client's Accept header asks for a YAML representation.
+fn wants_yaml(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .map(|accept| {
+            accept.split(',').any(|media| {
+                let media = media.split(';').next().unwrap_or("").trim();
+                matches!(media, "text/yaml" | "application/yaml" | "application/x-yaml" | "text/x-yaml")
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Serializes a JSON `Value` back into a YAML document string.
+pub fn value_to_yaml(value: &Value) -> ApiResult<String> {
+    serde_yaml::to_string(value)
+        .map_err(|e| ApiError::SerializationError(format!("YAML serialization failed: {}", e)))
+}
+
+/// Builds the response body as YAML when requested via Accept, otherwise JSON.
+fn negotiate(headers: &HeaderMap, value: Value) -> ApiResult<Response> {
+    if wants_yaml(headers) {
+        let yaml = value_to_yaml(&value)?;
+        return Ok(([(header::CONTENT_TYPE, YAML_CONTENT_TYPE)], yaml).into_response());
+    }
+
+    Ok(Json(value).into_response())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{StatusCode, Uri};
+    use crate::services::file_store::InMemoryFileStore;
+
+    async fn navigation_status(state: &AppState, uri: &str) -> StatusCode {
+        let response = get_navigation(Query(query(uri)), State(state.clone()), HeaderMap::new()).await;
+        response.map_or_else(|e| e.into_response().status(), |r| r.status())
+    }
+
+    fn query(uri: &str) -> NavigationQuery {
+        let uri: Uri = uri.parse().unwrap();
+        Query::<NavigationQuery>::try_from_uri(&uri).unwrap().0
+    }
+
+    #[test]
+    fn missing_params_fall_back_to_defaults() {
+        let params = query("/api/navigation");
+        assert_eq!(schema_or(&params, DEFAULT_NAVIGATION_SCHEMA).unwrap(), DEFAULT_NAVIGATION_SCHEMA);
+        assert_eq!(schema_or(&params, SETTINGS_NAVIGATION_SCHEMA).unwrap(), SETTINGS_NAVIGATION_SCHEMA);
+        assert!(params.file.is_none() && params.role.is_none());
+        assert!(!validation_disabled(&params));
+        assert!(!allow_additional(&params));
+        assert!(data_format(&params).unwrap().is_none());
+    }
+
+    #[test]
+    fn explicit_params_override_defaults() {
+        let params = query("/api/navigation/yaml?schema=alt&file=alt.yaml&role=admin&validate=FALSE&format=json");
+        assert_eq!(schema_or(&params, DEFAULT_NAVIGATION_SCHEMA).unwrap(), "alt");
+        assert_eq!(file_param(&params).unwrap(), Some("alt.yaml"));
+        assert_eq!(params.role.as_deref(), Some("admin"));
+        assert!(validation_disabled(&params));
+        assert!(data_format(&params).unwrap().is_some());
+    }
+
+    #[test]
+    fn malformed_schema_and_file_params_are_rejected() {
+        let params = query("/api/navigation/yaml?schema=..%2Fsecrets&file=..%2F..%2Fetc%2Fpasswd");
+        assert!(matches!(schema_or(&params, DEFAULT_NAVIGATION_SCHEMA), Err(ApiError::BadRequest(_))));
+        assert!(matches!(file_param(&params), Err(ApiError::BadRequest(_))));
+    }
+
+    #[test]
+    fn typed_navigation_keeps_the_frontend_wire_shape() {
+        let data = serde_json::json!([{
+            "id": "management",
+            "title": "Management",
+            "icon": "Settings",
+            "badge": "new",
+            "children": [{ "title": "Image Uploads", "url": "/management/image-uploads" }]
+        }]);
+        assert_eq!(typed_navigation(data.clone()).unwrap(), data);
+
+        let config = serde_json::json!({ "items": [{ "id": "home", "label": "Home", "path": "/" }] });
+        assert_eq!(
+            typed_navigation(config).unwrap(),
+            serde_json::json!({ "items": [{ "id": "home", "title": "Home", "url": "/" }] })
+        );
+    }
+
+    #[tokio::test]
+    async fn role_overlay_honors_validate_and_format() {
+        let store = InMemoryFileStore::new();
+        store.insert("/data/navigation.yaml", "- id: home\n  title: Home\n  url: /\n");
+        store.insert("/data/navigation.admin.yaml", "- id: admin\n  title: Admin\n  url: /admin\n");
+        let state = AppState::for_tests_with_store(store).await;
+
+        // No navigation schema is loaded: validated reads are 404, unvalidated ones served.
+        assert_eq!(navigation_status(&state, "/api/navigation?role=admin").await, StatusCode::NOT_FOUND);
+        assert_eq!(navigation_status(&state, "/api/navigation?role=admin&validate=false").await, StatusCode::OK);
+        assert_eq!(navigation_status(&state, "/api/navigation?role=viewer&validate=false").await, StatusCode::OK);
+
+        // The YAML base file isn't TOML, so forcing that parser must fail.
+        let forced = navigation_status(&state, "/api/navigation?role=admin&validate=false&format=toml").await;
+        assert!(forced.is_client_error(), "got {}", forced);
+    }
 }
@@ -0,0 +1,116 @@
+// File Path: backend/src/api/sse.rs
+
+// ====================================================================
+// SECTION 1: Imports and Constants
+// Description: Imports necessary libraries and defines global constants.
+// ====================================================================
+
+use axum::{
+    extract::{Path, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::{stream, Stream};
+use metrics::gauge;
+use std::convert::Infallible;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{info, warn};
+
+use crate::{
+    api::state::{channel_matches, AppState},
+    models::{ApiError, ApiResult},
+    services::redis_service::{self, RedisMessage},
+};
+
+/// SSE event name of relayed job messages.
+const JOB_EVENT: &str = "job_event";
+
+/// SSE event name telling the client that messages were skipped because it fell behind.
+const LAGGED_EVENT: &str = "lagged";
+
+
+// ====================================================================
+// SECTION 2: Job Event Stream
+// Description: Server-Sent Events fallback for clients that can't open a WebSocket.
+// ====================================================================
+
+/// Streams a job's messages as Server-Sent Events, for environments whose proxies
+/// block WebSocket upgrades.
+///
+/// Each message published on `ws_channel:job:<job_id>` becomes a `job_event` frame
+/// whose `data:` is the payload. Matching uses the same `channel_matches` rule as
+/// WebSocket subscriptions. A client that falls behind gets a `lagged` frame with the
+/// number of skipped messages. When the client disconnects the stream is dropped, which
+/// releases its broadcast receiver.
+pub async fn stream_job_events(
+    Path(job_id): Path<String>,
+    State(state): State<AppState>,
+) -> ApiResult<Sse<impl Stream<Item = Result<Event, Infallible>>>> {
+    let channel = redis_service::job_channel(&job_id);
+    if redis_service::parse_job_channel(&channel).is_none() {
+        return Err(ApiError::BadRequest(format!("Invalid job id: {:?}", job_id)));
+    }
+
+    info!("SSE client subscribed to {}", channel);
+    let subscription = SseSubscription::new(channel, state.connection_manager.broadcast_sender.subscribe());
+    Ok(Sse::new(stream::unfold(subscription, next_event)).keep_alive(KeepAlive::default()))
+}
+
+/// One SSE client's view of the broadcast channel. Dropping it (client gone) updates
+/// the `sse_connections` gauge.
+struct SseSubscription {
+    channel: String,
+    rx: broadcast::Receiver<RedisMessage>,
+}
+
+impl SseSubscription {
+    fn new(channel: String, rx: broadcast::Receiver<RedisMessage>) -> Self {
+        gauge!("sse_connections").increment(1.0);
+        Self { channel, rx }
+    }
+}
+
+impl Drop for SseSubscription {
+    fn drop(&mut self) {
+        gauge!("sse_connections").decrement(1.0);
+        info!("SSE client for {} disconnected", self.channel);
+    }
+}
+
+/// Waits for the next message on the subscribed channel; ends the stream if the
+/// broadcast channel closes.
+async fn next_event(mut subscription: SseSubscription) -> Option<(Result<Event, Infallible>, SseSubscription)> {
+    loop {
+        match subscription.rx.recv().await {
+            Ok(msg) if channel_matches(&subscription.channel, &msg.channel) => {
+                let event = Event::default().event(JOB_EVENT).data(msg.data);
+                return Some((Ok(event), subscription));
+            }
+            Ok(_) => continue,
+            Err(RecvError::Lagged(skipped)) => {
+                warn!("SSE client for {} lagged, skipped {} messages", subscription.channel, skipped);
+                let event = Event::default().event(LAGGED_EVENT).data(skipped.to_string());
+                return Some((Ok(event), subscription));
+            }
+            Err(RecvError::Closed) => return None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn only_messages_for_the_job_are_streamed() {
+        let (tx, rx) = broadcast::channel(8);
+        let subscription = SseSubscription::new(redis_service::job_channel("a"), rx);
+
+        tx.send(RedisMessage::new(redis_service::job_channel("b"), "other")).unwrap();
+        tx.send(RedisMessage::new(redis_service::job_channel("a"), "mine")).unwrap();
+        drop(tx);
+
+        let (event, subscription) = next_event(subscription).await.unwrap();
+        assert!(event.is_ok());
+        assert!(next_event(subscription).await.is_none(), "stream ends once the channel closes");
+    }
+}
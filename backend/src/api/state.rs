@@ -1,10 +1,103 @@
 // File Path: backend/src/api/state.rs
 
-use std::{sync::Arc, collections::HashMap};
+use std::{
+    env,
+    collections::HashMap,
+    sync::{atomic::{AtomicBool, AtomicU64, Ordering}, Arc, RwLock},
+    time::{Duration, Instant},
+};
+use chrono::{DateTime, Utc};
+use axum::extract::ws::{close_code, CloseFrame, Message};
+use metrics_exporter_prometheus::PrometheusHandle;
+use tracing_subscriber::{reload, EnvFilter, Registry};
 use tokio::sync::{broadcast, mpsc, Mutex};
-use crate::services::{yaml_service::YamlService, redis_service::RedisMessage};
+use tokio_util::sync::CancellationToken;
+use crate::services::{yaml_service::YamlService, redis_service::{self, ClientCount, RedisMessage}, job_store::JobEventStore, ticket_store::TicketStore};
+use crate::services::{latest_event_cache::LatestEventCache, readiness::ReadinessCache, session_store::SessionStore};
+use crate::models::ConnectionDetails;
+use crate::services::task_supervisor::{Heartbeat, HeartbeatRegistry, HEARTBEAT_INTERVAL};
 use tracing::{info, warn};
 
+/// Returns true if a stored subscription matches a Redis channel.
+///
+/// Subscriptions ending in `*` (e.g. "ws_channel:job:*") match by prefix;
+/// all others require an exact match.
+pub fn channel_matches(subscription: &str, channel: &str) -> bool {
+    match subscription.strip_suffix('*') {
+        Some(prefix) => channel.starts_with(prefix),
+        None => subscription == channel,
+    }
+}
+
+/// Live details and counters of one WebSocket connection (`GET /api/admin/connections/:id`).
+pub struct ConnectionStats {
+    pub remote_addr: Option<String>,
+    pub user_agent: Option<String>,
+    pub connected_at: DateTime<Utc>,
+    /// Set by PAUSE: the sender task buffers instead of delivering.
+    pub paused: AtomicBool,
+    /// Messages relayed to the client (each message of a BATCH counts).
+    pub delivered: AtomicU64,
+    /// Messages discarded for this client (e.g. pause buffer overflow).
+    pub dropped: AtomicU64,
+    /// Messages delivered during the last one-second sample.
+    pub messages_per_second: AtomicU64,
+    /// Messages waiting to be sent to this client (broadcast backlog + targeted frames).
+    pub outbound_queue: AtomicU64,
+    /// Set while the outbound queue has stayed above the slow-client threshold.
+    pub slow: AtomicBool,
+    /// Self-reported client name (`/ws?client_label=` or `client_label` on SUBSCRIBE).
+    client_label: RwLock<Option<String>>,
+}
+
+impl ConnectionStats {
+    pub fn new(remote_addr: Option<String>, user_agent: Option<String>) -> Self {
+        Self {
+            remote_addr,
+            user_agent,
+            connected_at: Utc::now(),
+            paused: AtomicBool::new(false),
+            delivered: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            messages_per_second: AtomicU64::new(0),
+            outbound_queue: AtomicU64::new(0),
+            slow: AtomicBool::new(false),
+            client_label: RwLock::new(None),
+        }
+    }
+
+    pub fn client_label(&self) -> Option<String> {
+        self.client_label.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Stores the sanitized label; a label with nothing printable left clears it.
+    pub fn set_client_label(&self, raw: &str) {
+        *self.client_label.write().unwrap_or_else(|e| e.into_inner()) = sanitize_client_label(raw);
+    }
+}
+
+/// Longest client label kept, in characters.
+const MAX_CLIENT_LABEL_LEN: usize = 64;
+
+/// Keeps only letters, digits and `-_.:/@` (spaces become `-`) and truncates to
+/// `MAX_CLIENT_LABEL_LEN`, so labels are safe to log and display.
+pub fn sanitize_client_label(raw: &str) -> Option<String> {
+    let label: String = raw
+        .trim()
+        .chars()
+        .map(|c| if c == ' ' { '-' } else { c })
+        .filter(|c| c.is_ascii_alphanumeric() || "-_.:/@".contains(*c))
+        .take(MAX_CLIENT_LABEL_LEN)
+        .collect();
+    (!label.is_empty()).then_some(label)
+}
+
+/// A registered connection: its direct frame channel plus its live stats.
+pub struct ConnectionHandle {
+    pub sender: mpsc::Sender<Message>,
+    pub stats: Arc<ConnectionStats>,
+}
+
 // --- 1. ConnectionManager ---
 /// Manages active WebSocket connections, the global broadcast channel, 
 /// and client job subscriptions.
@@ -17,8 +110,18 @@ pub struct ConnectionManager {
     /// Value: The Redis channel name (String, e.g., "ws_channel:job:UUID")
     pub subscriptions: Mutex<HashMap<String, String>>,
     
-    /// Map to track individual connections (kept for future targeted messaging/cleanup).
-    pub connections: Mutex<HashMap<String, mpsc::Sender<String>>>,
+    /// Map to track individual connections and their direct (targeted) frame channel.
+    /// Also the source of truth for the orphaned-subscription sweep.
+    pub connections: Mutex<HashMap<String, ConnectionHandle>>,
+
+    /// Number of entries in `connections`, readable without the lock by broadcasters.
+    pub clients: ClientCount,
+
+    /// Latest event per job id, fed from the broadcast channel (`GET /api/jobs/:job_id/latest`).
+    pub latest_events: Arc<LatestEventCache>,
+
+    /// Cancelled when the server shuts down; live WebSockets then close with 1001 (going away).
+    pub shutdown: CancellationToken,
 }
 
 impl ConnectionManager {
@@ -34,18 +137,25 @@ impl ConnectionManager {
             broadcast_sender: tx,
             subscriptions: Mutex::new(HashMap::new()),
             connections: Mutex::new(HashMap::new()),
+            clients: ClientCount::default(),
+            latest_events: Arc::new(LatestEventCache::from_env()),
+            shutdown: CancellationToken::new(),
         }
     }
     
     /// Publishes a generic message to all clients via the global broadcast channel.
     /// Primarily used for diagnostic or non-job messages.
+    ///
+    /// Local clients get it immediately; it is also published on `ws_channel:broadcast`
+    /// so other backend instances relay it to theirs.
     pub async fn broadcast(&self, message: &str) {
-        let msg = RedisMessage {
-            channel: "broadcast".to_string(),
-            data: message.to_string(),
-        };
-        if let Err(e) = self.broadcast_sender.send(msg) {
-            tracing::warn!("Failed to broadcast message: {}", e);
+        redis_service::broadcast_to_clients(
+            &self.broadcast_sender,
+            &self.clients,
+            RedisMessage::new(redis_service::LOCAL_BROADCAST_CHANNEL, message),
+        );
+        if let Err(e) = redis_service::publish_broadcast(message).await {
+            warn!("Broadcast delivered to local clients only, publishing to Redis failed: {}", e);
         }
     }
     
@@ -57,6 +167,12 @@ impl ConnectionManager {
         info!("Client {} subscribed to channel: {}", connection_id, channel_name);
     }
     
+    /// Channels `connection_id` is currently subscribed to.
+    pub async fn subscriptions_of(&self, connection_id: &str) -> Vec<String> {
+        let subs = self.subscriptions.lock().await;
+        subs.get(connection_id).cloned().into_iter().collect()
+    }
+
     /// Removes a client's job subscription.
     pub async fn unsubscribe(&self, connection_id: &str) {
         let mut subs = self.subscriptions.lock().await;
@@ -64,16 +180,193 @@ impl ConnectionManager {
         info!("Client {} unsubscribed.", connection_id);
     }
 
+    /// Returns true if any client's subscription matches `channel` (wildcards included).
+    pub async fn has_subscribers(&self, channel: &str) -> bool {
+        let subs = self.subscriptions.lock().await;
+        subs.values().any(|sub| channel_matches(sub, channel))
+    }
+
+    /// Counts clients whose subscription matches `channel` (wildcards included).
+    pub async fn subscriber_count(&self, channel: &str) -> usize {
+        let subs = self.subscriptions.lock().await;
+        subs.values().filter(|sub| channel_matches(sub, channel)).count()
+    }
+
+    /// Counts subscribers per subscribed channel (wildcard subscriptions are counted
+    /// under their pattern, e.g. "ws_channel:job:*").
+    pub async fn subscriber_counts(&self) -> HashMap<String, usize> {
+        let subs = self.subscriptions.lock().await;
+        let mut counts = HashMap::new();
+        for channel in subs.values() {
+            *counts.entry(channel.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Registers a live connection, the channel used to send it targeted frames, and its stats.
+    pub async fn add_connection(&self, connection_id: &str, sender: mpsc::Sender<Message>, stats: Arc<ConnectionStats>) {
+        let mut connections = self.connections.lock().await;
+        connections.insert(connection_id.to_string(), ConnectionHandle { sender, stats });
+        self.clients.set(connections.len());
+    }
+
+    /// Snapshot of everything known about a live connection, or `None` if it isn't connected.
+    pub async fn connection_details(&self, connection_id: &str) -> Option<ConnectionDetails> {
+        let (stats, queue_depth) = {
+            let connections = self.connections.lock().await;
+            let handle = connections.get(connection_id)?;
+            (handle.stats.clone(), handle.sender.max_capacity() - handle.sender.capacity())
+        };
+
+        Some(ConnectionDetails {
+            connection_id: connection_id.to_string(),
+            subscriptions: self.subscriptions_of(connection_id).await,
+            remote_addr: stats.remote_addr.clone(),
+            user_agent: stats.user_agent.clone(),
+            client_label: stats.client_label(),
+            connected_at: stats.connected_at,
+            paused: stats.paused.load(Ordering::Relaxed),
+            queue_depth,
+            messages_delivered: stats.delivered.load(Ordering::Relaxed),
+            messages_dropped: stats.dropped.load(Ordering::Relaxed),
+            messages_per_second: stats.messages_per_second.load(Ordering::Relaxed),
+            outbound_queue: stats.outbound_queue.load(Ordering::Relaxed),
+            slow: stats.slow.load(Ordering::Relaxed),
+        })
+    }
+
+    /// Details of every live connection, ordered by connect time.
+    pub async fn all_connection_details(&self) -> Vec<ConnectionDetails> {
+        let ids: Vec<String> = self.connections.lock().await.keys().cloned().collect();
+
+        let mut details = Vec::with_capacity(ids.len());
+        for id in ids {
+            // A connection may close between the snapshot and the lookup.
+            if let Some(connection) = self.connection_details(&id).await {
+                details.push(connection);
+            }
+        }
+        details.sort_by_key(|connection| connection.connected_at);
+        details
+    }
+
+    /// Tells every connection subscribed to a channel starting with `prefix` that it is
+    /// being drained (`{"type":"DRAINING"}`), then closes it with 1001 (going away) after
+    /// `close_after`, so the client reconnects elsewhere. Wildcard subscriptions match
+    /// only if the pattern itself starts with `prefix`.
+    ///
+    /// Never waits on a client: a connection whose outbound queue is full (or already
+    /// closed) is skipped rather than blocking the drain. Returns how many connections
+    /// were notified and how many were skipped.
+    pub async fn drain(&self, prefix: &str, close_after: Duration) -> (usize, usize) {
+        let targets: Vec<String> = {
+            let subs = self.subscriptions.lock().await;
+            subs.iter()
+                .filter(|(_, channel)| channel.starts_with(prefix))
+                .map(|(connection_id, _)| connection_id.clone())
+                .collect()
+        };
+        let senders: Vec<mpsc::Sender<Message>> = {
+            let connections = self.connections.lock().await;
+            targets.iter().filter_map(|id| connections.get(id)).map(|handle| handle.sender.clone()).collect()
+        };
+
+        let notice = serde_json::json!({
+            "type": "DRAINING",
+            "channel_prefix": prefix,
+            "close_in_ms": close_after.as_millis() as u64,
+        })
+        .to_string();
+        let total = senders.len();
+        let notified: Vec<mpsc::Sender<Message>> = senders
+            .into_iter()
+            .filter(|sender| sender.try_send(Message::Text(notice.clone())).is_ok())
+            .collect();
+        let skipped = total - notified.len();
+        if skipped > 0 {
+            warn!("Skipped {} connection(s) under {} whose outbound queue is full or closed", skipped, prefix);
+        }
+        info!("Draining {} connection(s) subscribed under {}", notified.len(), prefix);
+
+        let count = notified.len();
+        tokio::spawn(async move {
+            tokio::time::sleep(close_after).await;
+            for sender in notified {
+                let close = CloseFrame { code: close_code::AWAY, reason: "draining".into() };
+                if sender.try_send(Message::Close(Some(close))).is_err() {
+                    warn!("Could not queue the drain close frame; the connection's queue is full or closed");
+                }
+            }
+        });
+        (count, skipped)
+    }
+
+    /// Removes subscriptions whose connection id is no longer in `connections`
+    /// (cleanup paths that were missed). Returns how many were reaped.
+    pub async fn reap_orphaned_subscriptions(&self) -> usize {
+        // Snapshot live ids first so the two locks are never held together.
+        let live: Vec<String> = self.connections.lock().await.keys().cloned().collect();
+
+        let mut subs = self.subscriptions.lock().await;
+        let before = subs.len();
+        subs.retain(|connection_id, _| live.contains(connection_id));
+        before - subs.len()
+    }
+
     /// Removes a connection from the active connections map and ensures unsubscribe.
     pub async fn remove_connection(&self, connection_id: &str) {
         self.unsubscribe(connection_id).await; // Unsubscribe upon disconnect
 
         let mut connections = self.connections.lock().await;
         connections.remove(connection_id);
+        self.clients.set(connections.len());
         tracing::info!("Removed connection ID: {}", connection_id);
     }
 }
 
+/// Default seconds between orphaned-subscription sweeps.
+const DEFAULT_SUBSCRIPTION_SWEEP_SECS: u64 = 60;
+
+/// Reads the sweep interval from `SUBSCRIPTION_SWEEP_INTERVAL_SECS`. A value of `0` disables the sweep.
+pub fn subscription_sweep_interval_from_env() -> Option<Duration> {
+    let secs = env::var("SUBSCRIPTION_SWEEP_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_SUBSCRIPTION_SWEEP_SECS);
+
+    (secs > 0).then_some(Duration::from_secs(secs))
+}
+
+/// Periodically reaps orphaned subscriptions until `cancel` is triggered.
+pub async fn start_subscription_sweep(
+    manager: Arc<ConnectionManager>,
+    interval: Duration,
+    cancel: CancellationToken,
+    heartbeat: Heartbeat,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    let mut heartbeat_ticker = tokio::time::interval(HEARTBEAT_INTERVAL);
+    info!("Subscription sweep started (interval: {:?})", interval);
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = heartbeat_ticker.tick() => heartbeat.beat(),
+            _ = ticker.tick() => {
+                let reaped = manager.reap_orphaned_subscriptions().await;
+                if reaped > 0 {
+                    warn!("Subscription sweep reaped {} orphaned subscription(s)", reaped);
+                }
+            }
+        }
+    }
+
+    info!("Subscription sweep stopped.");
+}
+
+
+/// Handle used to swap the global tracing `EnvFilter` at runtime.
+pub type LogReloadHandle = reload::Handle<EnvFilter, Registry>;
 
 /// --- 2. AppState ---
 /// Holds application-wide shared state.
@@ -81,14 +374,180 @@ impl ConnectionManager {
 pub struct AppState {
     pub connection_manager: Arc<ConnectionManager>,
     pub yaml_service: Arc<YamlService>,
+    /// Handle to the installed Prometheus recorder, rendered by `/metrics`.
+    pub metrics_handle: PrometheusHandle,
+    /// Recorded job events, used by the HTTP job status endpoints.
+    pub job_store: Arc<JobEventStore>,
+    /// Reload handle for the tracing filter (runtime log-level changes).
+    pub log_reload_handle: LogReloadHandle,
+    /// Outstanding single-use WebSocket tickets (`POST /api/ws-ticket`).
+    pub ticket_store: Arc<TicketStore>,
+    /// Heartbeats of the background tasks, reported by `GET /api/health/tasks`.
+    pub heartbeats: Arc<HeartbeatRegistry>,
+    /// When the server started (uptime in `GET /api/info`).
+    pub started_at: Instant,
+    /// Recently disconnected WebSocket sessions, resumable with `/ws?session=<id>`.
+    pub sessions: Arc<SessionStore>,
+    /// Cached Redis readiness result served by `GET /ready`.
+    pub readiness: Arc<ReadinessCache>,
 }
 
 impl AppState {
     /// Creates a new AppState instance.
-    pub fn new(connection_manager: Arc<ConnectionManager>, yaml_service: Arc<YamlService>) -> Self {
+    pub fn new(
+        connection_manager: Arc<ConnectionManager>,
+        yaml_service: Arc<YamlService>,
+        metrics_handle: PrometheusHandle,
+        job_store: Arc<JobEventStore>,
+        log_reload_handle: LogReloadHandle,
+        ticket_store: Arc<TicketStore>,
+        heartbeats: Arc<HeartbeatRegistry>,
+    ) -> Self {
         Self {
             connection_manager,
             yaml_service,
+            metrics_handle,
+            job_store,
+            log_reload_handle,
+            ticket_store,
+            heartbeats,
+            started_at: Instant::now(),
+            sessions: Arc::new(SessionStore::new()),
+            readiness: Arc::new(ReadinessCache::from_env()),
         }
     }
 }
+
+#[cfg(test)]
+impl AppState {
+    /// A state for handler tests: empty in-memory schema/data dirs, no Redis, and
+    /// metrics/log handles that aren't installed globally.
+    pub async fn for_tests() -> Self {
+        Self::for_tests_with_store(crate::services::file_store::InMemoryFileStore::new()).await
+    }
+
+    /// Like `for_tests`, with extra files under `/schemas` and `/data` already in `store`.
+    pub async fn for_tests_with_store(store: crate::services::file_store::InMemoryFileStore) -> Self {
+        use metrics_exporter_prometheus::PrometheusBuilder;
+
+        store.insert("/schemas/.keep", "");
+        store.insert("/data/.keep", "");
+        let yaml_service = YamlService::new_with_store("/schemas", "/data", None, Arc::new(store))
+            .await
+            .expect("in-memory YamlService");
+        let (_, log_reload_handle) = reload::Layer::new(EnvFilter::new("info"));
+
+        Self::new(
+            Arc::new(ConnectionManager::new()),
+            Arc::new(yaml_service),
+            PrometheusBuilder::new().build_recorder().handle(),
+            Arc::new(JobEventStore::new()),
+            log_reload_handle,
+            Arc::new(TicketStore::new()),
+            Arc::new(HeartbeatRegistry::new()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn sweep_reaps_only_orphaned_subscriptions() {
+        let manager = ConnectionManager::new();
+        let (tx, _rx) = mpsc::channel(1);
+        manager.add_connection("live", tx, Arc::new(ConnectionStats::new(None, None))).await;
+        manager.subscribe("live", "ws_channel:job:1").await;
+        manager.subscribe("gone", "ws_channel:job:2").await;
+
+        assert_eq!(manager.reap_orphaned_subscriptions().await, 1);
+        let subs = manager.subscriptions.lock().await;
+        assert!(subs.contains_key("live"));
+        assert!(!subs.contains_key("gone"));
+    }
+
+    #[tokio::test]
+    async fn drain_notifies_then_closes_only_matching_connections() {
+        let manager = ConnectionManager::new();
+        let (backup_tx, mut backup_rx) = mpsc::channel(4);
+        let (other_tx, mut other_rx) = mpsc::channel(4);
+        manager.add_connection("backup", backup_tx, Arc::new(ConnectionStats::new(None, None))).await;
+        manager.add_connection("other", other_tx, Arc::new(ConnectionStats::new(None, None))).await;
+        manager.subscribe("backup", "ws_channel:job:backup-1").await;
+        manager.subscribe("other", "ws_channel:job:restore-1").await;
+
+        assert_eq!(manager.drain("ws_channel:job:backup-", Duration::ZERO).await, (1, 0));
+
+        let Some(Message::Text(notice)) = backup_rx.recv().await else {
+            panic!("expected a DRAINING notice");
+        };
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&notice).unwrap()["type"], "DRAINING");
+        let Some(Message::Close(Some(frame))) = backup_rx.recv().await else {
+            panic!("expected a close frame");
+        };
+        assert_eq!(frame.code, close_code::AWAY);
+        assert!(other_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn drain_skips_connections_with_a_full_queue() {
+        let manager = ConnectionManager::new();
+        let (stalled_tx, _stalled_rx) = mpsc::channel(1);
+        stalled_tx.try_send(Message::Text("unread".to_string())).unwrap();
+        let (live_tx, mut live_rx) = mpsc::channel(4);
+        manager.add_connection("stalled", stalled_tx, Arc::new(ConnectionStats::new(None, None))).await;
+        manager.add_connection("live", live_tx, Arc::new(ConnectionStats::new(None, None))).await;
+        manager.subscribe("stalled", "ws_channel:job:backup-1").await;
+        manager.subscribe("live", "ws_channel:job:backup-2").await;
+
+        let drained = tokio::time::timeout(Duration::from_secs(1), manager.drain("ws_channel:job:backup-", Duration::ZERO));
+        assert_eq!(drained.await.expect("drain must not wait on a stalled client"), (1, 1));
+        assert!(matches!(live_rx.recv().await, Some(Message::Text(_))));
+    }
+
+    #[tokio::test]
+    async fn wildcard_subscription_counts_as_subscriber() {
+        let manager = ConnectionManager::new();
+        manager.subscribe("a", "ws_channel:job:*").await;
+        manager.subscribe("b", "ws_channel:job:1").await;
+
+        assert!(manager.has_subscribers("ws_channel:job:2").await);
+        assert!(!manager.has_subscribers("ws_channel:device:1").await);
+        assert_eq!(manager.subscriber_counts().await.get("ws_channel:job:1"), Some(&1));
+    }
+
+    #[tokio::test]
+    async fn connection_details_report_stats_and_subscriptions() {
+        let manager = ConnectionManager::new();
+        let (tx, _rx) = mpsc::channel(4);
+        let stats = Arc::new(ConnectionStats::new(Some("10.0.0.5:5000".to_string()), None));
+        manager.add_connection("c1", tx.clone(), stats.clone()).await;
+        manager.subscribe("c1", "ws_channel:job:1").await;
+        stats.delivered.fetch_add(3, Ordering::Relaxed);
+        tx.send(Message::Text("queued".to_string())).await.unwrap();
+
+        let details = manager.connection_details("c1").await.unwrap();
+        assert_eq!(details.subscriptions, vec!["ws_channel:job:1"]);
+        assert_eq!(details.remote_addr.as_deref(), Some("10.0.0.5:5000"));
+        assert_eq!((details.messages_delivered, details.queue_depth), (3, 1));
+        assert!(manager.connection_details("gone").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn client_labels_are_sanitized_and_reported() {
+        assert_eq!(sanitize_client_label(" dashboard prod ").as_deref(), Some("dashboard-prod"));
+        assert_eq!(sanitize_client_label("cli-v2\n<script>").as_deref(), Some("cli-v2script"));
+        assert_eq!(sanitize_client_label(&"x".repeat(200)).map(|l| l.len()), Some(MAX_CLIENT_LABEL_LEN));
+        assert_eq!(sanitize_client_label("\t<>"), None);
+
+        let manager = ConnectionManager::new();
+        let (tx, _rx) = mpsc::channel(4);
+        let stats = Arc::new(ConnectionStats::new(None, None));
+        manager.add_connection("c1", tx, stats.clone()).await;
+        stats.set_client_label("cli-v2");
+
+        let details = manager.connection_details("c1").await.unwrap();
+        assert_eq!(details.client_label.as_deref(), Some("cli-v2"));
+    }
+}
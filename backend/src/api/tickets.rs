@@ -0,0 +1,41 @@
+// File Path: backend/src/api/tickets.rs
+
+// ====================================================================
+// SECTION 1: Imports
+// Description: Imports necessary libraries.
+// ====================================================================
+
+use axum::{extract::State, http::HeaderMap, Json};
+use serde_json::Value;
+use tracing::info;
+
+use crate::{
+    api::{auth, state::AppState},
+    models::ApiResult,
+};
+
+
+// ====================================================================
+// SECTION 2: Ticket Handlers
+// Description: Issues single-use tickets for the WebSocket handshake.
+// ====================================================================
+
+/// Issues a single-use WebSocket ticket (valid for 30 seconds).
+///
+/// Requires `Authorization: Bearer <token>` where the token is `WS_AUTH_TOKEN`, or
+/// `WS_ADMIN_TOKEN` for a ticket that also unlocks admin-only WebSocket features.
+/// The client then connects with `/ws?ticket=<ticket>`.
+pub async fn issue_ticket(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> ApiResult<Json<Value>> {
+    let is_admin = auth::require_bearer(&headers)?;
+    let ticket = state.ticket_store.issue(is_admin).await;
+    info!("Issued WebSocket ticket (admin: {})", is_admin);
+
+    Ok(Json(serde_json::json!({
+        "ticket": ticket,
+        "expires_in_secs": state.ticket_store.ttl().as_secs()
+    })))
+}
+
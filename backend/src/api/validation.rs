@@ -0,0 +1,198 @@
+// File Path: backend/src/api/validation.rs
+
+// ====================================================================
+// SECTION 1: Imports
+// Description: Imports necessary libraries.
+// ====================================================================
+
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::Value;
+use std::{collections::{BTreeMap, HashMap}, sync::Arc};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{
+    api::{navigation::value_to_yaml, state::AppState},
+    models::{
+        ApiError, ApiResult, FileValidationReport, JobEvent, SchemaDataFile, SchemaHealth, ValidateFileRequest,
+        ValidationSummary,
+    },
+    services::{redis_service, yaml_service::YamlService},
+    util::input::{validate_identifier, validate_relative_path},
+};
+
+/// `device` reported on events of hub-internal jobs (not tied to a network device).
+const HUB_DEVICE: &str = "hub";
+
+/// `job_type` of the background validate-all job.
+const VALIDATE_ALL_JOB_TYPE: &str = "validate_all";
+
+
+// ====================================================================
+// SECTION 2: File Validation Handlers
+// Description: On-demand validation for the config-editing UI.
+// ====================================================================
+
+/// Re-validates a single data file and returns every issue with a fix suggestion.
+///
+/// Body: `{"schema":"navigation","file":"nav.yaml"}`. A file that fails validation
+/// is still a 200 with `{"valid":false,"issues":[...]}`; an unknown schema is 404
+/// and a missing or unparseable file keeps its usual error status.
+pub async fn validate_file(
+    State(state): State<AppState>,
+    Json(request): Json<ValidateFileRequest>,
+) -> ApiResult<Json<FileValidationReport>> {
+    let report = state
+        .yaml_service
+        .validate_file_report(
+            validate_identifier("schema", &request.schema)?,
+            validate_relative_path("file", &request.file)?,
+        )
+        .await?;
+
+    info!(
+        "Validated {} against '{}': {} issue(s)",
+        request.file,
+        request.schema,
+        report.issues.len()
+    );
+    Ok(Json(report))
+}
+
+/// Reports which schemas loaded, which failed, and any schema-name collisions.
+pub async fn get_schema_health(State(state): State<AppState>) -> Json<SchemaHealth> {
+    Json(state.yaml_service.schema_set().health.clone())
+}
+
+/// Lists the data files belonging to a schema, with their sizes and modification times,
+/// for the editing UI's file picker (see `YamlService::schema_data_files`).
+///
+/// Files are matched by name; `?validate=true` also parses every data file, adding files
+/// whose content validates and each file's current `valid` flag and `errors`. Nested
+/// schema names are passed URL-encoded (`network%2Fbgp`). Unknown schemas are 404.
+pub async fn get_schema_files(
+    Path(schema_name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> ApiResult<Json<Vec<SchemaDataFile>>> {
+    let validate = params.get("validate").is_some_and(|v| v.eq_ignore_ascii_case("true") || v == "1");
+    let schema_name = validate_identifier("schema", &schema_name)?;
+    let files = state.yaml_service.schema_data_files(schema_name, validate).await?;
+    Ok(Json(files))
+}
+
+/// Exports every loaded schema as one object mapping schema name to its source
+/// document, e.g. for generating frontend types from the schemas the backend enforces.
+///
+/// `?format=yaml` returns the same bundle as YAML.
+pub async fn get_schema_bundle(
+    Query(params): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> ApiResult<Response> {
+    let set = state.yaml_service.schema_set();
+    let bundle: BTreeMap<&String, &Value> = set.sources.iter().collect();
+    let bundle = serde_json::to_value(bundle).map_err(|e| ApiError::SerializationError(e.to_string()))?;
+
+    match params.get("format").map(|f| f.to_ascii_lowercase()).as_deref() {
+        None | Some("json") => Ok(Json(bundle).into_response()),
+        Some("yaml") | Some("yml") => {
+            Ok(([(header::CONTENT_TYPE, "text/yaml")], value_to_yaml(&bundle)?).into_response())
+        }
+        Some(other) => Err(ApiError::BadRequest(format!("Unsupported bundle format: {}", other))),
+    }
+}
+
+/// Reloads every schema from disk and reports the new schema health. Validations in
+/// flight keep using the previous schemas; the current set stays active if the reload fails.
+pub async fn reload_schemas(State(state): State<AppState>) -> ApiResult<Json<SchemaHealth>> {
+    let health = state.yaml_service.reload_schemas().await?;
+    info!("Reloaded {} schema(s)", health.loaded.len());
+    Ok(Json(health))
+}
+
+
+// ====================================================================
+// SECTION 3: Background Validation Job
+// Description: Validates every data file, streaming progress like a device job.
+// ====================================================================
+
+/// Starts validating every data file in the background and returns its job id.
+///
+/// Progress (one event per file) and a final summary are published as `JobEvent`s
+/// on `ws_channel:job:<job_id>`, so clients watch it by subscribing to `job:<job_id>`.
+pub async fn start_validate_all(State(state): State<AppState>) -> Json<Value> {
+    let job_id = Uuid::new_v4().to_string();
+    info!("Starting validate-all job {}", job_id);
+
+    tokio::spawn(run_validate_all(state.yaml_service.clone(), job_id.clone()));
+
+    Json(serde_json::json!({
+        "job_id": job_id,
+        "channel": format!("job:{}", job_id)
+    }))
+}
+
+/// Validates each data file in turn, publishing a progress event per file.
+async fn run_validate_all(yaml_service: Arc<YamlService>, job_id: String) {
+    let files = match yaml_service.data_files().await {
+        Ok(files) => files,
+        Err(e) => {
+            let event = JobEvent::with_error(&job_id, HUB_DEVICE, VALIDATE_ALL_JOB_TYPE, &e.to_string(), Value::Null);
+            publish_event(&event).await;
+            return;
+        }
+    };
+
+    let total = files.len();
+    let mut summary = ValidationSummary::default();
+    for (index, path) in files.iter().enumerate() {
+        let Some(result) = yaml_service.validate_data_file(path).await else {
+            continue;
+        };
+
+        let progress = JobEvent::new(
+            &job_id,
+            HUB_DEVICE,
+            VALIDATE_ALL_JOB_TYPE,
+            "progress",
+            "running",
+            serde_json::json!({
+                "file": result.file,
+                "valid": result.valid,
+                "errors": result.errors,
+                "progress_percent": (index + 1) as f64 * 100.0 / total as f64,
+            }),
+        );
+        publish_event(&progress).await;
+        summary.record(result);
+    }
+
+    let data = serde_json::to_value(&summary).unwrap_or(Value::Null);
+    let done = JobEvent::new(&job_id, HUB_DEVICE, VALIDATE_ALL_JOB_TYPE, "completed", "completed", data);
+    publish_event(&done).await;
+    info!(
+        "Validate-all job {} finished: {} passed, {} failed, {} skipped",
+        job_id, summary.passed, summary.failed, summary.skipped
+    );
+}
+
+/// Publishes an event to its job channel; failures are logged and the job carries on.
+async fn publish_event(event: &JobEvent) {
+    let channel = redis_service::job_channel(&event.job_id);
+    let payload = match serde_json::to_string(event) {
+        Ok(p) => p,
+        Err(e) => {
+            warn!("Failed to serialize event for job {}: {}", event.job_id, e);
+            return;
+        }
+    };
+
+    if let Err(e) = redis_service::publish_message(&channel, &payload).await {
+        warn!("Failed to publish to {}: {}", channel, e);
+    }
+}
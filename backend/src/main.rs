@@ -5,43 +5,107 @@
 //! Sets up the asynchronous environment, initializes application-wide shared state, 
 //! and starts the Axum WebSocket server, including the background Redis subscriber.
 
-use std::{net::SocketAddr, sync::Arc};
-use tracing::info;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tracing::{error, info};
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt};
 use tokio::net::TcpListener;
-use tokio::spawn; // 🔑 FIX: Import tokio::spawn for background tasks
+use metrics_exporter_prometheus::PrometheusBuilder;
 
 // Import modules from the project structure
 mod api;
 mod routes;
 mod services;
 mod models;
+mod util;
 
 // Import core components
-use api::state::{AppState, ConnectionManager};
+use api::state::{self, AppState, ConnectionManager};
 use services::yaml_service::YamlService;
-use routes::create_router;
+use routes::{base_path_from_env, create_router};
 
 // Import the Redis service module
 use services::redis_service; 
+use services::job_monitor;
+use services::latest_event_cache;
+use services::redis_mirror::{self, RedisMirror};
+use services::job_store::{self, JobEventStore};
+use services::ticket_store::TicketStore;
+use services::task_supervisor::{self, HeartbeatRegistry, TaskSupervisor};
+use services::system_heartbeat;
 
-/// The main entry point for the Tokio runtime.
-#[tokio::main]
-async fn main() {
+/// Default cap on Tokio's blocking-task threads (Tokio's own default).
+const DEFAULT_MAX_BLOCKING_THREADS: usize = 512;
+
+/// Tokio runtime sizing, read from the environment so small pods can be right-sized.
+#[derive(Debug, Clone, Copy)]
+struct RuntimeConfig {
+    /// Async worker threads (`WORKER_THREADS`, default: number of CPUs).
+    worker_threads: usize,
+    /// Upper bound on threads for `spawn_blocking` / blocking fs calls (`MAX_BLOCKING_THREADS`, default 512).
+    max_blocking_threads: usize,
+}
+
+impl RuntimeConfig {
+    fn from_env() -> Self {
+        let positive = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .filter(|n| *n > 0)
+        };
+
+        Self {
+            worker_threads: positive("WORKER_THREADS").unwrap_or_else(|| {
+                std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+            }),
+            max_blocking_threads: positive("MAX_BLOCKING_THREADS").unwrap_or(DEFAULT_MAX_BLOCKING_THREADS),
+        }
+    }
+}
+
+/// Builds the multi-threaded Tokio runtime explicitly (instead of `#[tokio::main]`)
+/// so its size is configurable, then runs the server on it.
+fn main() {
+    let runtime_config = RuntimeConfig::from_env();
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(runtime_config.worker_threads)
+        .max_blocking_threads(runtime_config.max_blocking_threads)
+        .enable_all()
+        .build()
+        .expect("Failed to build the Tokio runtime");
+
+    runtime.block_on(run(runtime_config));
+}
+
+/// The server's async entry point, running on the runtime built in `main`.
+async fn run(runtime_config: RuntimeConfig) {
     // Define the directories for configuration files (must match Docker copy paths)
     const SCHEMA_DIR: &str = "/app/shared/schemas";
     const DATA_DIR: &str = "/app/shared/data";
     
     // 1. Setup Logging
+    // The filter is wrapped in a reload layer so it can be changed at runtime
+    // via POST /api/admin/log-level.
+    let (filter_layer, log_reload_handle) = reload::Layer::new(
+        tracing_subscriber::EnvFilter::try_from_default_env()
+            .unwrap_or_else(|_| "rust_websocket_backend=info,tower_http=debug".into()),
+    );
+    // Logs go to stderr so stdout stays clean for `--validate-all --json`.
     tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "rust_websocket_backend=info,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
+        .with(filter_layer)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
         .init();
 
     info!("Starting Rust WebSocket Backend Server...");
+    info!(
+        "Tokio runtime: {} worker thread(s), up to {} blocking thread(s)",
+        runtime_config.worker_threads, runtime_config.max_blocking_threads
+    );
+
+    // Install the global metrics recorder before any service records metrics
+    let metrics_handle = PrometheusBuilder::new()
+        .install_recorder()
+        .expect("Failed to install Prometheus metrics recorder");
 
     // 2. Initialize Shared State 
     
@@ -49,25 +113,150 @@ async fn main() {
     let yaml_service = YamlService::new(SCHEMA_DIR, DATA_DIR)
         .await
         .expect("Failed to initialize YamlService. Check shared/data and shared/schemas paths/contents.");
+
+    // Optional one-shot validation of every data file (CI smoke test / pre-deploy check)
+    if let Some(options) = startup_validation_options() {
+        let summary = yaml_service
+            .validate_all()
+            .await
+            .expect("Failed to scan data directory for validation.");
+
+        if options.json {
+            if let Err(e) = write_validation_summary(&summary, &mut std::io::stdout().lock()) {
+                error!("Failed to write validation summary: {}", e);
+            }
+        }
+
+        if !summary.is_success() {
+            error!("{} data file(s) failed schema validation.", summary.failed);
+            std::process::exit(1);
+        }
+
+        if options.check_only {
+            info!("All data files passed validation. Exiting (check-only mode).");
+            return;
+        }
+    }
     
+    let yaml_service = Arc::new(yaml_service);
+
     // Initialize ConnectionManager (Contains the global broadcast channel)
     let connection_manager = Arc::new(ConnectionManager::new());
     
     // 3. 🚀 CRITICAL NEW STEP: Start Redis Listener Task
-    // Get a clone of the broadcast sender from the ConnectionManager.
-    let ws_broadcast_tx = connection_manager.broadcast_sender.clone();
+    // The listener only holds a weak handle, so it notices if the channel ever closes.
+    let ws_broadcast_tx = connection_manager.broadcast_sender.downgrade();
     
-    // Spawn the Redis listener into a background task
-    spawn(async move {
-        match redis_service::start_redis_listener(ws_broadcast_tx).await {
-            Ok(_) => info!("Redis listener exited gracefully."),
-            Err(e) => panic!("Redis listener failed critically: {}", e),
+    // Every background task is owned by the supervisor and shares its cancellation token.
+    let mut tasks = TaskSupervisor::new();
+    let redis_token = tasks.token();
+
+    // Each task reports liveness through its own heartbeat (GET /api/health/tasks).
+    let heartbeats = Arc::new(HeartbeatRegistry::new());
+    let redis_heartbeat = heartbeats.register("redis_listener");
+    let redis_yaml_service = yaml_service.clone();
+    let redis_clients = connection_manager.clients.clone();
+
+    // Spawn the Redis listener into a background task. A critical failure is logged
+    // and retried rather than panicking, so the server keeps serving HTTP/WebSocket.
+    tasks.spawn("redis_listener", async move {
+        loop {
+            match redis_service::start_redis_listener(
+                ws_broadcast_tx.clone(),
+                redis_clients.clone(),
+                redis_yaml_service.clone(),
+                redis_token.clone(),
+                redis_heartbeat.clone(),
+            ).await {
+                Ok(_) => {
+                    info!("Redis listener exited gracefully.");
+                    break;
+                }
+                Err(e) if !e.is_retryable() => {
+                    error!("Redis listener stopped for good: {}", e);
+                    break;
+                }
+                Err(e) => {
+                    error!("Redis listener failed critically: {}. Restarting in 5 seconds...", e);
+                    tokio::select! {
+                        _ = redis_token.cancelled() => break,
+                        _ = tokio::time::sleep(Duration::from_secs(5)) => {}
+                    }
+                }
+            }
         }
     });
 
+    // Spawn the stalled-job watchdog (disabled when JOB_STALE_TIMEOUT_SECS=0)
+    if let Some(timeout) = job_monitor::stale_timeout_from_env() {
+        tasks.spawn("job_watchdog", job_monitor::start_job_watchdog(
+            connection_manager.broadcast_sender.clone(),
+            connection_manager.clients.clone(),
+            timeout,
+            tasks.token(),
+            heartbeats.register("job_watchdog"),
+        ));
+    }
+
+    // Spawn the job event recorder that backs the HTTP job status endpoints
+    let job_store = Arc::new(JobEventStore::from_env());
+    tasks.spawn("job_event_recorder", job_store::start_event_recorder(
+        job_store.clone(),
+        connection_manager.broadcast_sender.clone(),
+        tasks.token(),
+        heartbeats.register("job_event_recorder"),
+    ));
+
+    // Spawn the tracker that keeps the latest event per job
+    tasks.spawn("latest_event_tracker", latest_event_cache::start_latest_event_tracker(
+        connection_manager.latest_events.clone(),
+        connection_manager.broadcast_sender.clone(),
+        tasks.token(),
+        heartbeats.register("latest_event_tracker"),
+    ));
+
+    // Spawn the opt-in capture of relayed messages (REDIS_MIRROR_FILE)
+    if let Some(mirror) = RedisMirror::from_env() {
+        tasks.spawn("redis_mirror", redis_mirror::start_redis_mirror(
+            mirror,
+            connection_manager.broadcast_sender.clone(),
+            tasks.token(),
+            heartbeats.register("redis_mirror"),
+        ));
+    }
+
+    // Spawn the orphaned-subscription sweep (disabled when SUBSCRIPTION_SWEEP_INTERVAL_SECS=0)
+    if let Some(interval) = state::subscription_sweep_interval_from_env() {
+        tasks.spawn("subscription_sweep", state::start_subscription_sweep(
+            connection_manager.clone(),
+            interval,
+            tasks.token(),
+            heartbeats.register("subscription_sweep"),
+        ));
+    }
+
+    // Spawn the application-level liveness signal (disabled unless SYSTEM_HEARTBEAT_SECS > 0)
+    if let Some(interval) = system_heartbeat::interval_from_env() {
+        tasks.spawn("system_heartbeat", system_heartbeat::start_system_heartbeat(
+            connection_manager.broadcast_sender.clone(),
+            connection_manager.clients.clone(),
+            interval,
+            tasks.token(),
+            heartbeats.register("system_heartbeat"),
+        ));
+    }
+
     // 4. Initialize AppState and Router
-    let app_state = AppState::new(connection_manager.clone(), Arc::new(yaml_service));
-    let app = create_router(app_state);
+    let app_state = AppState::new(
+        connection_manager.clone(),
+        yaml_service,
+        metrics_handle,
+        job_store,
+        log_reload_handle,
+        Arc::new(TicketStore::new()),
+        heartbeats,
+    );
+    let app = create_router(app_state, base_path_from_env().as_deref());
 
     // 5. Start the Axum Server
     let addr = SocketAddr::from(([0, 0, 0, 0], 3100));
@@ -77,7 +266,105 @@ async fn main() {
         
     info!("Server listening on {}", addr);
     
-    axum::serve(listener, app.into_make_service())
+    // Connect info gives WebSocket connections their remote address (admin inspection).
+    // On shutdown, live WebSockets are closed (1001) so the graceful drain doesn't wait on them.
+    let ws_shutdown = connection_manager.shutdown.clone();
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            ws_shutdown.cancel();
+        })
         .await
         .unwrap();
+
+    // 6. Stop background tasks once the server has drained
+    info!("Shutting down background tasks...");
+    tasks.shutdown(task_supervisor::shutdown_timeout_from_env()).await;
+    info!("Server shutdown complete.");
+}
+
+/// Options controlling the startup data-file validation pass.
+struct StartupValidation {
+    /// Exit after validating instead of starting the server (`--validate-all`).
+    check_only: bool,
+    /// Print a machine-readable JSON summary to stdout (`--json`).
+    json: bool,
+}
+
+/// Parses `--validate-all` / `--json` CLI flags and the `VALIDATE_ON_START` env var.
+///
+/// `--validate-all` runs in pure-check mode; `VALIDATE_ON_START=true` validates once
+/// and then continues serving. Returns `None` when neither is requested.
+fn startup_validation_options() -> Option<StartupValidation> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let check_only = args.iter().any(|a| a == "--validate-all");
+    let on_start = std::env::var("VALIDATE_ON_START")
+        .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+        .unwrap_or(false);
+
+    (check_only || on_start).then(|| StartupValidation {
+        check_only,
+        json: args.iter().any(|a| a == "--json"),
+    })
+}
+
+/// Writes the `--json` validation summary as pretty-printed JSON followed by a newline.
+fn write_validation_summary(
+    summary: &models::ValidationSummary,
+    out: &mut impl std::io::Write,
+) -> std::io::Result<()> {
+    serde_json::to_writer_pretty(&mut *out, summary)?;
+    writeln!(out)
+}
+
+/// Resolves when the process receives Ctrl+C or (on Unix) SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("Shutdown signal received, starting graceful shutdown.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::{FileValidationResult, ValidationSummary};
+
+    #[test]
+    fn validation_summary_output_is_parseable_json() {
+        let mut summary = ValidationSummary::default();
+        summary.record(FileValidationResult {
+            file: "items.yaml".to_string(),
+            schema: Some("items".to_string()),
+            valid: true,
+            errors: Vec::new(),
+        });
+
+        let mut stdout = Vec::new();
+        write_validation_summary(&summary, &mut stdout).unwrap();
+
+        let parsed: ValidationSummary = serde_json::from_slice(&stdout).unwrap();
+        assert_eq!(parsed.total, 1);
+        assert_eq!(parsed.passed, 1);
+        assert!(stdout.ends_with(b"\n"));
+    }
 }
@@ -40,6 +40,9 @@ pub enum ApiError {
     #[error("Bad request: {0}")]
     BadRequest(String),
     
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
     
@@ -49,20 +52,23 @@ pub enum ApiError {
     #[error("Deserialization error: {0}")]
     DeserializationError(String),
     
-    #[error("WebSocket error: {0}")]
-    WebSocketError(String),
-    
     #[error("Validation error: {0}")]
     ValidationError(String),
     
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Precondition required: {0}")]
+    PreconditionRequired(String),
+
+    #[error("Timed out: {0}")]
+    Timeout(String),
+    
     #[error("Internal server error: {0}")]
     InternalError(String),
     
     #[error("Execution error: {0}")]
     ExecutionError(String),
-    
-    #[error("Job execution error: {0}")]
-    JobExecutionError(String),
 }
 
 impl IntoResponse for ApiError {
@@ -72,14 +78,16 @@ impl IntoResponse for ApiError {
             ApiError::FileNotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
             ApiError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
             ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            ApiError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
             ApiError::IoError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string()),
             ApiError::SerializationError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Serialization failed".to_string()),
             ApiError::DeserializationError(_) => (StatusCode::BAD_REQUEST, "Invalid request format".to_string()),
-            ApiError::WebSocketError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "WebSocket error".to_string()),
             ApiError::ValidationError(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            ApiError::Conflict(_) => (StatusCode::CONFLICT, self.to_string()),
+            ApiError::PreconditionRequired(_) => (StatusCode::PRECONDITION_REQUIRED, self.to_string()),
+            ApiError::Timeout(_) => (StatusCode::GATEWAY_TIMEOUT, self.to_string()),
             ApiError::InternalError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string()),
             ApiError::ExecutionError(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
-            ApiError::JobExecutionError(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
         };
 
         let body = serde_json::json!({
@@ -143,18 +151,27 @@ impl JobEvent {
     }
 }
 
-/// Request structure for subscribing to job events
+/// Aggregated, point-in-time view of a job computed from its recorded events.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct JobSubscriptionRequest {
-    pub device_filter: Option<String>,
-    pub job_type_filter: Option<String>,
+pub struct JobStatus {
+    pub job_id: String,
+    pub device: Option<String>,
+    pub job_type: Option<String>,
+    pub status: String,
+    pub progress_percent: Option<f64>,
+    pub last_event_at: DateTime<Utc>,
+    pub error: Option<String>,
 }
 
-/// Response structure for job subscription confirmation
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct JobSubscriptionResponse {
-    pub subscription_id: String,
-    pub topics: Vec<String>,
+/// Per-device job counts computed from recorded events (`GET /api/devices/summary`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceSummary {
+    pub device: String,
+    pub running: usize,
+    pub completed: usize,
+    /// Failed and stalled jobs.
+    pub failed: usize,
+    pub last_activity: DateTime<Utc>,
 }
 
 // =========================================================================================
@@ -164,17 +181,50 @@ pub struct JobSubscriptionResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NavigationConfig {
     pub items: Vec<NavigationItem>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub settings: Option<NavigationSettings>,
 }
 
+impl NavigationConfig {
+    /// Deserializes navigation data, accepting either root layout:
+    /// an object with an `items` key, or a bare array of items (as in navigation.yaml),
+    /// which is wrapped as `NavigationConfig { items, settings: None }`.
+    pub fn from_value(value: serde_json::Value) -> ApiResult<Self> {
+        let config_error = match serde_json::from_value::<NavigationConfig>(value.clone()) {
+            Ok(config) => return Ok(config),
+            Err(e) => e,
+        };
+
+        serde_json::from_value::<Vec<NavigationItem>>(value)
+            .map(|items| NavigationConfig { items, settings: None })
+            .map_err(|items_error| {
+                ApiError::ValidationError(format!(
+                    "Navigation data is neither a config object ({}) nor an item list ({})",
+                    config_error, items_error
+                ))
+            })
+    }
+}
+
+/// A single navigation entry. Serialized with the YAML/frontend field names (`title`,
+/// `url`), also accepting `label` and `path`; nested children may omit `id`. Fields the
+/// model doesn't know are kept in `extra` and passed through unchanged.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NavigationItem {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub id: String,
+    #[serde(rename = "title", alias = "label")]
     pub label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub icon: Option<String>,
+    #[serde(rename = "url", alias = "path", default, skip_serializing_if = "Option::is_none")]
     pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub children: Option<Vec<NavigationItem>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -184,6 +234,24 @@ pub struct NavigationSettings {
     pub collapsible: Option<bool>,
 }
 
+/// Query parameters accepted by the navigation endpoints. Every field is optional;
+/// handlers fall back to their own default schema when `schema` is absent.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NavigationQuery {
+    pub schema: Option<String>,
+    pub file: Option<String>,
+    pub role: Option<String>,
+    /// `yaml`, `json` or `toml`, for files whose extension is ambiguous.
+    pub format: Option<String>,
+    /// `false` skips schema validation.
+    pub validate: Option<String>,
+    /// `true` ignores `additionalProperties` violations, serving unknown fields as-is.
+    pub allow_additional: Option<String>,
+    /// `true` adds a `repaired` copy of the data with schema defaults filled in
+    /// (`/api/navigation/yaml` only).
+    pub apply_defaults: Option<String>,
+}
+
 // =========================================================================================
 // SECTION 6: BACKUP & RESTORE MODELS (Content as provided)
 // =========================================================================================
@@ -196,25 +264,329 @@ pub struct BackupRequest {
     pub inventory_file: Option<String>,
 }
 
+/// A device expanded from an inventory file (`BackupRequest.inventory_file`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InventoryDevice {
+    pub host_name: String,
+    pub ip_address: String,
+    #[serde(default)]
+    pub vendor: Option<String>,
+    #[serde(default)]
+    pub platform: Option<String>,
+    /// Location the device is listed under (e.g. "BASEMENT").
+    #[serde(default)]
+    pub location: Option<String>,
+    /// Device group it is listed in (e.g. "routers").
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+/// A single backup file produced for a device.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupFileEntry {
+    pub device: String,
+    /// Path relative to the backup root (e.g. "router1/router1_20240101.conf").
+    pub path: String,
+    pub size_bytes: u64,
+    /// Hex-encoded SHA-256 of the file contents.
+    pub sha256: String,
+}
+
+/// Listings longer than this are sent gzipped in `files_gzip_base64` instead of `files`.
+pub const BACKUP_LISTING_COMPRESS_THRESHOLD: usize = 500;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupResponse {
     pub status: String,
     pub message: String,
     pub logs: Option<String>,
-    pub files: Option<serde_json::Value>,
+    /// The file listing; empty when it was compressed into `files_gzip_base64`.
+    #[serde(default)]
+    pub files: Vec<BackupFileEntry>,
+    /// Total number of files, whether listed inline or compressed.
+    #[serde(default)]
+    pub file_count: usize,
+    /// Base64 of the gzipped JSON array of `BackupFileEntry`, for large listings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub files_gzip_base64: Option<String>,
+}
+
+impl BackupResponse {
+    /// Builds a response, compressing the listing when it exceeds
+    /// `BACKUP_LISTING_COMPRESS_THRESHOLD` entries.
+    pub fn new(
+        status: impl Into<String>,
+        message: impl Into<String>,
+        logs: Option<String>,
+        files: Vec<BackupFileEntry>,
+    ) -> ApiResult<Self> {
+        let file_count = files.len();
+        let (files, files_gzip_base64) = if file_count > BACKUP_LISTING_COMPRESS_THRESHOLD {
+            (Vec::new(), Some(compress_listing(&files)?))
+        } else {
+            (files, None)
+        };
+
+        Ok(Self {
+            status: status.into(),
+            message: message.into(),
+            logs,
+            files,
+            file_count,
+            files_gzip_base64,
+        })
+    }
+
+    /// Returns the full listing, decompressing `files_gzip_base64` when present.
+    #[cfg(test)]
+    pub fn file_entries(&self) -> ApiResult<Vec<BackupFileEntry>> {
+        match &self.files_gzip_base64 {
+            Some(blob) => decompress_listing(blob),
+            None => Ok(self.files.clone()),
+        }
+    }
+}
+
+/// Serializes a listing to JSON, gzips it, and base64-encodes the result.
+fn compress_listing(files: &[BackupFileEntry]) -> ApiResult<String> {
+    use base64::Engine;
+    use std::io::Write;
+
+    let json = serde_json::to_vec(files).map_err(|e| ApiError::SerializationError(e.to_string()))?;
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&json)?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(encoder.finish()?))
 }
 
+/// Reverses `compress_listing`.
+#[cfg(test)]
+fn decompress_listing(blob: &str) -> ApiResult<Vec<BackupFileEntry>> {
+    use base64::Engine;
+
+    let gzipped = base64::engine::general_purpose::STANDARD
+        .decode(blob)
+        .map_err(|e| ApiError::DeserializationError(format!("Invalid base64 listing: {}", e)))?;
+    serde_json::from_reader(flate2::read::GzDecoder::new(gzipped.as_slice()))
+        .map_err(|e| ApiError::DeserializationError(format!("Invalid compressed listing: {}", e)))
+}
+
+// =========================================================================================
+// SECTION 7: DEBUG / REPLAY MODELS
+// =========================================================================================
+
+/// Request body for replaying recorded job events through Redis (DEV_MODE only).
+/// Either `events` or `fixture` must be provided; `events` wins if both are set.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RestoreRequest {
-    pub hostname: String,
-    pub username: String,
-    pub password: String,
-    pub backup_file: String,
+pub struct ReplayRequest {
+    pub events: Option<Vec<JobEvent>>,
+    pub fixture: Option<String>,
+    pub delay_ms: Option<u64>,
 }
 
+// =========================================================================================
+// SECTION 8: DATA VALIDATION SUMMARY MODELS
+// =========================================================================================
+
+/// Outcome of validating a single data file against its schema.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RestoreResponse {
-    pub status: String,
+pub struct FileValidationResult {
+    pub file: String,
+    pub schema: Option<String>,
+    pub valid: bool,
+    pub errors: Vec<String>,
+}
+
+/// Aggregate result of validating every data file (see `--validate-all`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ValidationSummary {
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub results: Vec<FileValidationResult>,
+}
+
+impl ValidationSummary {
+    /// True when no validated file failed.
+    pub fn is_success(&self) -> bool {
+        self.failed == 0
+    }
+
+    /// Adds one file's result to the counts and results.
+    pub fn record(&mut self, result: FileValidationResult) {
+        self.total += 1;
+        match (&result.schema, result.valid) {
+            (None, _) => self.skipped += 1,
+            (Some(_), true) => self.passed += 1,
+            (Some(_), false) => self.failed += 1,
+        }
+        self.results.push(result);
+    }
+}
+
+/// Request body for `POST /api/validate-file`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidateFileRequest {
+    pub schema: String,
+    pub file: String,
+}
+
+/// A single schema violation, with a hint on how to fix it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    /// JSON pointer to the failing value (e.g. "/items/0/title"); empty for the document root.
+    pub pointer: String,
+    /// The JSON Schema keyword that failed (e.g. "required", "type").
+    pub keyword: String,
+    /// The validator's own error message.
     pub message: String,
-    pub logs: Option<String>,
+    /// Human-readable suggestion for config authors.
+    pub suggestion: String,
+}
+
+/// Result of re-validating a single file, as returned by `POST /api/validate-file`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileValidationReport {
+    pub valid: bool,
+    pub issues: Vec<ValidationIssue>,
+}
+
+/// A data file belonging to a schema (`GET /api/schemas/:name/files`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaDataFile {
+    /// Path relative to the data directory (usable as `?file=`).
+    pub path: String,
+    pub size_bytes: u64,
+    pub modified: Option<DateTime<Utc>>,
+    /// With `?validate=true`: whether the file currently passes the schema.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid: Option<bool>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// A schema file that could not be read or compiled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaLoadFailure {
+    pub name: String,
+    pub file: String,
+    pub error: String,
+}
+
+/// Several schema files mapping to the same schema name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaCollision {
+    pub name: String,
+    pub files: Vec<String>,
+    /// The file actually compiled under `name`.
+    pub used: String,
+}
+
+/// Outcome of loading the schema directory (`GET /api/schemas/health`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SchemaHealth {
+    pub loaded: Vec<String>,
+    pub failed: Vec<SchemaLoadFailure>,
+    pub collisions: Vec<SchemaCollision>,
+}
+
+// =========================================================================================
+// SECTION 9: ADMIN MODELS
+// =========================================================================================
+
+/// Request body for changing the tracing filter at runtime (e.g. "rust_websocket_backend=debug").
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLevelRequest {
+    pub filter: String,
+}
+
+/// Request body for sending a message to every WebSocket client (`POST /api/admin/broadcast`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BroadcastRequest {
+    pub message: String,
+}
+
+/// Request body for draining the WebSocket clients watching some channels
+/// (e.g. `{"channel_prefix":"ws_channel:job:backup-"}`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrainRequest {
+    pub channel_prefix: String,
+}
+
+/// Everything known about one live WebSocket connection (`GET /api/admin/connections/:id`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionDetails {
+    pub connection_id: String,
+    pub subscriptions: Vec<String>,
+    pub remote_addr: Option<String>,
+    pub user_agent: Option<String>,
+    /// Label the client chose for itself (e.g. "dashboard-prod"), if any.
+    pub client_label: Option<String>,
+    pub connected_at: DateTime<Utc>,
+    pub paused: bool,
+    /// Targeted frames (replies, close) queued but not yet sent.
+    pub queue_depth: usize,
+    pub messages_delivered: u64,
+    pub messages_dropped: u64,
+    /// Delivery rate over the last one-second sample.
+    pub messages_per_second: u64,
+    /// Messages waiting to be sent (broadcast backlog + targeted frames).
+    pub outbound_queue: u64,
+    /// True while the outbound queue has stayed above the slow-client threshold.
+    pub slow: bool,
+}
+
+// =========================================================================================
+// SECTION 10: FEATURE FLAG MODELS
+// =========================================================================================
+
+/// UI feature flags derived from `FEATURE_<NAME>` environment variables
+/// (e.g. `FEATURE_BACKUP=true`). Served by `GET /api/features` and in the WELCOME frame.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Features {
+    pub backup: bool,
+    pub restore: bool,
+    pub image_uploads: bool,
+    pub code_upgrades: bool,
+    pub validation: bool,
+}
+
+impl Default for Features {
+    /// Defaults: every shipped workflow (backup, restore, image uploads, code upgrades,
+    /// JSNAPy validation) is enabled unless explicitly turned off.
+    fn default() -> Self {
+        Self {
+            backup: true,
+            restore: true,
+            image_uploads: true,
+            code_upgrades: true,
+            validation: true,
+        }
+    }
+}
+
+impl Features {
+    /// Builds the flags from the environment, falling back to the defaults for any
+    /// unset or unparseable variable. Unknown `FEATURE_*` variables are ignored.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let flag = |name: &str, default: bool| {
+            std::env::var(format!("FEATURE_{}", name))
+                .ok()
+                .and_then(|v| match v.to_lowercase().as_str() {
+                    "1" | "true" | "yes" | "on" => Some(true),
+                    "0" | "false" | "no" | "off" => Some(false),
+                    _ => None,
+                })
+                .unwrap_or(default)
+        };
+
+        Self {
+            backup: flag("BACKUP", defaults.backup),
+            restore: flag("RESTORE", defaults.restore),
+            image_uploads: flag("IMAGE_UPLOADS", defaults.image_uploads),
+            code_upgrades: flag("CODE_UPGRADES", defaults.code_upgrades),
+            validation: flag("VALIDATION", defaults.validation),
+        }
+    }
 }
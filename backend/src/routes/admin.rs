@@ -0,0 +1,82 @@
+// File Path: backend/src/routes/admin.rs
+
+//! Admin Routes
+//!
+//! Provides operational endpoints for adjusting the running server. Every route
+//! requires the admin bearer token (`WS_ADMIN_TOKEN`).
+
+use axum::{middleware, routing::{get, post}, Router};
+use crate::api::state::AppState;
+use crate::api::{admin, auth};
+
+/// Creates admin-related routes, all behind the admin bearer token.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Route to change the tracing log filter at runtime
+        .route("/api/admin/log-level", post(admin::set_log_level))
+        // Route to list subscribed channels and their subscriber counts
+        .route("/api/admin/channels", get(admin::get_channels))
+        // Route to check whether a single channel has any subscribers
+        .route("/api/admin/channels/:channel", get(admin::get_channel))
+        // Route to list WebSocket connections (?slow=true for slow clients only)
+        .route("/api/admin/connections", get(admin::get_connections))
+        // Route to inspect one WebSocket connection's full state
+        .route("/api/admin/connections/:id", get(admin::get_connection))
+        // Route to drain the clients watching channels under a prefix (admin token)
+        .route("/api/admin/drain", post(admin::drain_connections))
+        // Route to reload schemas and cached config state in one call (admin token)
+        .route("/api/admin/reload", post(admin::reload_all))
+        // Route to send a message to every WebSocket client on every instance (admin token)
+        .route("/api/admin/broadcast", post(admin::broadcast_message))
+        .route_layer(middleware::from_fn(auth::require_admin_bearer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::{header, Request, StatusCode}};
+    use tower::ServiceExt;
+
+    /// Status of an admin request sent with `token` as its bearer token, if any.
+    async fn admin_status(method: &str, uri: &str, token: Option<&str>) -> StatusCode {
+        let router = routes().with_state(AppState::for_tests().await);
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        router.oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn log_level_requires_admin_token() {
+        assert_eq!(admin_status("POST", "/api/admin/log-level", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(admin_status("POST", "/api/admin/log-level", Some("not-a-token")).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn channel_listings_require_admin_token() {
+        for uri in ["/api/admin/channels", "/api/admin/channels/job:1"] {
+            assert_eq!(admin_status("GET", uri, None).await, StatusCode::UNAUTHORIZED, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn connection_detail_requires_admin_token() {
+        let uri = "/api/admin/connections/some-connection";
+        assert_eq!(admin_status("GET", uri, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(admin_status("GET", uri, Some("not-a-token")).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn connection_listing_requires_admin_token() {
+        assert_eq!(admin_status("GET", "/api/admin/connections", None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(admin_status("GET", "/api/admin/connections", Some("not-a-token")).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn operations_require_admin_token() {
+        for uri in ["/api/admin/drain", "/api/admin/reload", "/api/admin/broadcast"] {
+            assert_eq!(admin_status("POST", uri, None).await, StatusCode::UNAUTHORIZED, "{}", uri);
+        }
+    }
+}
@@ -0,0 +1,16 @@
+// File Path: backend/src/routes/backups.rs
+
+//! Backup Routes
+//!
+//! Provides the endpoint listing the backup files on disk.
+
+use axum::{routing::get, Router};
+use crate::api::state::AppState;
+use crate::api::backups;
+
+/// Creates backup-related routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Route to list the backup files on disk, per device
+        .route("/api/backups", get(backups::list_backups))
+}
@@ -0,0 +1,15 @@
+// File Path: backend/src/routes/features.rs
+
+//! Feature Flag Routes
+//!
+//! Provides the endpoint clients use to discover which UI features are enabled.
+
+use axum::{routing::get, Router};
+use crate::api::state::AppState;
+use crate::api::features;
+
+/// Creates feature-flag routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/features", get(features::get_features))
+}
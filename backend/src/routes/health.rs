@@ -3,8 +3,12 @@
 //! 
 //! Provides health monitoring and system status endpoints
 
-use axum::{routing::get, Router};
+use std::collections::HashMap;
+use chrono::{DateTime, SecondsFormat, Utc};
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use serde_json::{json, Value};
 use crate::api::state::AppState; // Use the correct path for AppState
+use crate::services::{readiness::ReadinessReport, redis_service, task_supervisor::{self, TaskLiveness}};
 
 /// Health check endpoint
 /// Returns "OK" if the server is running correctly
@@ -12,8 +16,106 @@ pub async fn health_check() -> &'static str {
     "OK"
 }
 
+/// JSON health check endpoint
+/// Returns `{"status":"ok"}` as `application/json` for monitors that expect structured output.
+pub async fn health_check_json() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Readiness endpoint
+/// Returns 200 when Redis answers a PING, 503 otherwise. The result is cached briefly
+/// (`READY_CACHE_TTL_MS`) and each check is capped (`READY_CHECK_TIMEOUT_MS`).
+pub async fn readiness_check(State(state): State<AppState>) -> (StatusCode, Json<ReadinessReport>) {
+    let report = state.readiness.status(redis_service::ping).await;
+    let status = if report.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
+/// Background task liveness endpoint
+/// Reports each task's seconds since its last heartbeat, flagging silent tasks as `stale`
+/// (threshold: `TASK_STALE_AFTER_SECS`, default 30).
+pub async fn task_health(State(state): State<AppState>) -> Json<HashMap<&'static str, TaskLiveness>> {
+    Json(state.heartbeats.liveness(task_supervisor::task_stale_after_from_env()))
+}
+
+/// Build info endpoint
+/// Reports the crate version, git sha, build time and rustc version baked in by `build.rs`,
+/// plus the seconds since startup, so a deploy can be verified without shelling in.
+pub async fn build_info(State(state): State<AppState>) -> Json<Value> {
+    Json(json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_sha": env!("GIT_SHA"),
+        "build_time": env!("BUILD_TIME"),
+        "uptime_seconds": state.started_at.elapsed().as_secs(),
+        "rust_version": env!("RUST_VERSION"),
+    }))
+}
+
+/// Server time endpoint
+/// Returns the current server UTC time so clients can correct for clock skew when
+/// rendering event timestamps (same body as the `SERVER_TIME` WebSocket reply).
+pub async fn get_server_time() -> Json<Value> {
+    Json(server_time(Utc::now()))
+}
+
+/// Builds `{"utc":"<RFC3339>","epoch_ms":N}` for `now`.
+pub fn server_time(now: DateTime<Utc>) -> Value {
+    json!({
+        "utc": now.to_rfc3339_opts(SecondsFormat::Millis, true),
+        "epoch_ms": now.timestamp_millis(),
+    })
+}
+
 /// Creates health-related routes and merges them into the main router.
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/health", get(health_check))
+        .route("/health/json", get(health_check_json))
+        .route("/ready", get(readiness_check))
+        .route("/api/health/tasks", get(task_health))
+        .route("/api/info", get(build_info))
+        .route("/api/time", get(get_server_time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::{header, Request}};
+    use tower::ServiceExt;
+
+    /// Returns the content type and body of `GET uri`.
+    async fn get_health(uri: &str) -> (String, String) {
+        let router = Router::new()
+            .route("/health", get(health_check))
+            .route("/health/json", get(health_check_json));
+        let response = router
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap().to_string();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (content_type, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn plain_health_stays_text() {
+        let (content_type, body) = get_health("/health").await;
+        assert!(content_type.starts_with("text/plain"), "got {}", content_type);
+        assert_eq!(body, "OK");
+    }
+
+    #[test]
+    fn server_time_reports_rfc3339_and_epoch_millis() {
+        let now = DateTime::parse_from_rfc3339("2024-05-01T12:00:00.250Z").unwrap().with_timezone(&Utc);
+        assert_eq!(server_time(now), json!({ "utc": "2024-05-01T12:00:00.250Z", "epoch_ms": 1_714_564_800_250_i64 }));
+    }
+
+    #[tokio::test]
+    async fn json_health_is_application_json() {
+        let (content_type, body) = get_health("/health/json").await;
+        assert_eq!(content_type, "application/json");
+        assert_eq!(serde_json::from_str::<Value>(&body).unwrap(), json!({ "status": "ok" }));
+    }
 }
@@ -0,0 +1,28 @@
+// File Path: backend/src/routes/jobs.rs
+
+//! Job Routes
+//!
+//! Provides HTTP endpoints for querying job state recorded from Redis.
+
+use axum::{routing::{get, post}, Router};
+use crate::api::state::AppState;
+use crate::api::jobs;
+
+/// Creates job-related routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Route to get a single job's aggregated status
+        .route("/api/jobs/:job_id", get(jobs::get_job_status))
+        // Route to fetch a job's events incrementally (?since=<RFC3339>)
+        .route("/api/jobs/:job_id/events", get(jobs::get_job_events))
+        // Route to get the most recent event of a job (bounded in-memory cache)
+        .route("/api/jobs/:job_id/latest", get(jobs::get_latest_job_event))
+        // Route to publish an event onto a job's channel (bearer token required)
+        .route("/api/jobs/:job_id/publish", post(jobs::publish_job_event))
+        // Route to ask the orchestrator to cancel a job (relayed on ws_channel:control:<job_id>)
+        .route("/api/jobs/:job_id/cancel", post(jobs::cancel_job))
+        // Route to queue a backup job for a host or every device of an inventory file
+        .route("/api/backups", post(jobs::start_backup))
+        // Route to summarize job counts per device (?since=<RFC3339>)
+        .route("/api/devices/summary", get(jobs::get_device_summary))
+}
@@ -0,0 +1,27 @@
+// File Path: backend/src/routes/metrics.rs
+//! Metrics Routes
+//!
+//! Exposes application metrics in the Prometheus text exposition format.
+
+use axum::{
+    extract::State,
+    http::header,
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use crate::api::state::AppState;
+
+/// Renders all recorded metrics for Prometheus scraping.
+pub async fn metrics_handler(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics_handle.render(),
+    )
+}
+
+/// Creates metrics-related routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/metrics", get(metrics_handler))
+}
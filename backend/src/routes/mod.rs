@@ -1,25 +1,200 @@
 // backend/src/routes/mod.rs (Final Corrected Version)
 
-use axum::{routing::get, Router};
+use std::sync::Arc;
+use axum::{http::StatusCode, middleware, response::IntoResponse, routing::get, Json, Router};
+use tower_http::limit::RequestBodyLimitLayer;
 use crate::api::state::AppState; // Changed from AppState to crate::api::state::AppState
+use crate::api::auth::{self, ApiKeys};
 
 pub mod websocket;
 pub mod navigation;
 pub mod health; // Now points to the health.rs file you provided
+pub mod debug;
+pub mod metrics;
+pub mod jobs;
+pub mod admin;
+pub mod features;
+pub mod validation;
+pub mod tickets;
+pub mod backups;
+pub mod sse;
+
+/// Default cap on request body size for API routes (2 MB).
+const DEFAULT_MAX_REQUEST_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Reads the API request body cap from `MAX_REQUEST_BODY_BYTES`, falling back to 2 MB.
+fn max_request_body_bytes() -> usize {
+    std::env::var("MAX_REQUEST_BODY_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_REQUEST_BODY_BYTES)
+}
+
+/// Reads the optional mount path from `BASE_PATH` (e.g. `/hub`), normalized to a leading
+/// slash and no trailing slash. Unset, empty, or `/` mounts everything at the root.
+pub fn base_path_from_env() -> Option<String> {
+    normalize_base_path(&std::env::var("BASE_PATH").ok()?)
+}
+
+fn normalize_base_path(raw: &str) -> Option<String> {
+    let trimmed = raw.trim().trim_matches('/');
+    (!trimmed.is_empty()).then(|| format!("/{}", trimmed))
+}
 
 /// Creates and configures the main application router.
-pub fn create_router(state: AppState) -> Router {
-    Router::new()
-        // Define the main WebSocket route at the root path '/ws'
+///
+/// With a `base_path`, every route (WebSocket, health, metrics and `/api`) is nested
+/// under it, e.g. `/hub/ws` and `/hub/api/...`.
+pub fn create_router(state: AppState, base_path: Option<&str>) -> Router {
+    // Merge navigation/YAML data routes
+    let mut api_routes = Router::new()
+        .merge(navigation::routes()) // Use navigation::routes() instead of yaml::routes()
+
+        // Merge job status routes
+        .merge(jobs::routes())
+
+        // Merge admin/operations routes
+        .merge(admin::routes())
+
+        // Merge feature flag routes
+        .merge(features::routes())
+
+        // Merge data file validation routes
+        .merge(validation::routes())
+
+        // Merge WebSocket ticket routes
+        .merge(tickets::routes())
+
+        // Merge backup listing routes
+        .merge(backups::routes())
+
+        // Merge Server-Sent Events routes (WebSocket fallback)
+        .merge(sse::routes());
+
+    // Developer-only routes are never mounted in production
+    let dev_mode = debug::dev_mode_enabled();
+    if dev_mode {
+        tracing::warn!("DEV_MODE enabled: mounting /api/debug routes");
+        api_routes = api_routes.merge(debug::routes());
+    }
+
+    // Merge the Redis round-trip probe (admin token outside DEV_MODE)
+    api_routes = api_routes.merge(debug::probe_routes(dev_mode));
+
+    // Oversized bodies on /api routes are rejected with 413 Payload Too Large
+    let mut api_routes = api_routes.layer(RequestBodyLimitLayer::new(max_request_body_bytes()));
+
+    // With REQUIRE_API_KEY=true every /api route needs a valid X-API-Key
+    // (/ws, /health, /api/health/tasks, /api/info, /api/time and /metrics stay open)
+    if auth::api_key_required() {
+        let keys = ApiKeys::from_env();
+        if keys.is_empty() {
+            tracing::warn!("REQUIRE_API_KEY is set but API_KEYS is empty: every /api request will be rejected");
+        }
+        api_routes = api_routes.layer(middleware::from_fn_with_state(Arc::new(keys), auth::require_api_key));
+    }
+
+    let app_routes = Router::new()
+        // Define the main WebSocket route at the root path '/ws' (no body limit)
         .route("/ws", get(websocket::websocket_handler))
 
         // Merge health monitoring routes
         .merge(health::routes())
 
-        // Merge navigation/YAML data routes
-        .merge(navigation::routes()) // Use navigation::routes() instead of yaml::routes()
-        
-        .with_state(state)
+        // Merge Prometheus metrics route
+        .merge(metrics::routes())
 
+        .merge(api_routes);
         // NOTE: The previous line `.merge(yaml::routes())` is REMOVED
+
+    let app_routes = match base_path {
+        Some(base_path) => {
+            tracing::info!("Mounting all routes under {}", base_path);
+            Router::new().nest(base_path, app_routes)
+        }
+        None => app_routes,
+    };
+
+    with_error_fallbacks(app_routes.with_state(state))
+}
+
+/// Answers unmatched paths (404) and unsupported methods (405) with the same
+/// `{"error", "status"}` envelope as `ApiError` instead of Axum's empty bodies.
+fn with_error_fallbacks(router: Router) -> Router {
+    router
+        .fallback(not_found)
+        .method_not_allowed_fallback(method_not_allowed)
+}
+
+async fn not_found() -> impl IntoResponse {
+    error_envelope(StatusCode::NOT_FOUND, "Not found")
+}
+
+async fn method_not_allowed() -> impl IntoResponse {
+    error_envelope(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed")
+}
+
+fn error_envelope(status: StatusCode, error: &str) -> impl IntoResponse {
+    (status, Json(serde_json::json!({ "error": error, "status": status.as_u16() })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    async fn call(router: Router, method: &str, uri: &str) -> (StatusCode, serde_json::Value) {
+        let request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    fn test_router() -> Router {
+        with_error_fallbacks(Router::new().route("/health", get(|| async { "OK" })))
+    }
+
+    #[tokio::test]
+    async fn api_key_middleware_rejects_missing_and_invalid_keys() {
+        let keys = Arc::new(ApiKeys::new(["k1".to_string()]));
+        let router = Router::new()
+            .route("/api/thing", get(|| async { Json(serde_json::json!({ "ok": true })) }))
+            .layer(middleware::from_fn_with_state(keys, auth::require_api_key));
+
+        let (status, body) = call(router.clone(), "GET", "/api/thing").await;
+        assert_eq!((status, body["status"].clone()), (StatusCode::UNAUTHORIZED, serde_json::json!(401)));
+
+        for (key, expected) in [("wrong", StatusCode::UNAUTHORIZED), ("k1", StatusCode::OK)] {
+            let request = Request::builder()
+                .uri("/api/thing")
+                .header(auth::API_KEY_HEADER, key)
+                .body(Body::empty())
+                .unwrap();
+            assert_eq!(router.clone().oneshot(request).await.unwrap().status(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn unknown_path_gets_json_404() {
+        let (status, body) = call(test_router(), "GET", "/nope").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body, serde_json::json!({ "error": "Not found", "status": 404 }));
+    }
+
+    #[tokio::test]
+    async fn wrong_method_gets_json_405() {
+        let (status, body) = call(test_router(), "POST", "/health").await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(body, serde_json::json!({ "error": "Method not allowed", "status": 405 }));
+    }
+
+    #[test]
+    fn base_path_is_normalized() {
+        assert_eq!(normalize_base_path("hub/"), Some("/hub".to_string()));
+        assert_eq!(normalize_base_path("/ops/hub"), Some("/ops/hub".to_string()));
+        assert_eq!(normalize_base_path("/"), None);
+        assert_eq!(normalize_base_path(""), None);
+    }
 }
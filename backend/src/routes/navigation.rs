@@ -4,8 +4,8 @@
 //!
 //! Provides endpoints for fetching and validating UI navigation data.
 
-use axum::{routing::get, Router};
-use crate::api::state::AppState;
+use axum::{handler::Handler, middleware, routing::get, Router};
+use crate::api::{auth, state::AppState};
 // FIX: Instead of declaring a local 'api' module, import the required handlers
 // from the existing top-level 'api' module (which contains navigation handlers).
 use crate::api::navigation;
@@ -17,7 +17,33 @@ pub fn routes() -> Router<AppState> {
         // Route to get generic navigation data
         .route("/api/navigation", get(navigation::get_navigation))
         // Route to get navigation data loaded directly from a validated YAML file
-        .route("/api/navigation/yaml", get(navigation::get_navigation_from_yaml))
+        // (PUT saves it back, admin only, with If-Match optimistic concurrency)
+        .route(
+            "/api/navigation/yaml",
+            get(navigation::get_navigation_from_yaml)
+                .put(navigation::save_navigation_yaml.layer(middleware::from_fn(auth::require_admin_bearer))),
+        )
         // Route to get settings-specific navigation items
         .route("/api/navigation/settings", get(navigation::get_settings_navigation))
+        // Route to get all navigation variants in a single response
+        .route("/api/navigation/all", get(navigation::get_all_navigation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::{Request, StatusCode}};
+    use tower::ServiceExt;
+
+    async fn yaml_status(method: &str) -> StatusCode {
+        let router = routes().with_state(AppState::for_tests().await);
+        let request = Request::builder().method(method).uri("/api/navigation/yaml").body(Body::empty()).unwrap();
+        router.oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn only_saving_requires_admin_token() {
+        assert_eq!(yaml_status("PUT").await, StatusCode::UNAUTHORIZED);
+        assert_ne!(yaml_status("GET").await, StatusCode::UNAUTHORIZED);
+    }
 }
@@ -0,0 +1,16 @@
+// File Path: backend/src/routes/sse.rs
+
+//! SSE Routes
+//!
+//! Provides Server-Sent Events streams for clients that can't use WebSockets.
+
+use axum::{routing::get, Router};
+use crate::api::state::AppState;
+use crate::api::sse;
+
+/// Creates Server-Sent Events routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Route to stream a job's events as SSE (fallback when WebSockets are blocked)
+        .route("/api/sse/jobs/:job_id", get(sse::stream_job_events))
+}
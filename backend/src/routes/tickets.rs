@@ -0,0 +1,16 @@
+// File Path: backend/src/routes/tickets.rs
+
+//! Ticket Routes
+//!
+//! Provides the HTTP half of the ticket-based WebSocket handshake.
+
+use axum::{routing::post, Router};
+use crate::api::state::AppState;
+use crate::api::tickets;
+
+/// Creates WebSocket ticket routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Route to obtain a single-use ticket for /ws?ticket=...
+        .route("/api/ws-ticket", post(tickets::issue_ticket))
+}
@@ -0,0 +1,26 @@
+// File Path: backend/src/routes/validation.rs
+
+//! Validation Routes
+//!
+//! Provides on-demand data file validation for config authors.
+
+use axum::{routing::{get, post}, Router};
+use crate::api::state::AppState;
+use crate::api::validation;
+
+/// Creates validation-related routes.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        // Route to re-validate a single file and return fix suggestions
+        .route("/api/validate-file", post(validation::validate_file))
+        // Route to validate every data file as a background job (progress over WebSocket)
+        .route("/api/validate-all", post(validation::start_validate_all))
+        // Route to report schema load failures and name collisions
+        .route("/api/schemas/health", get(validation::get_schema_health))
+        // Route to export every loaded schema source as a single bundle
+        .route("/api/schemas/bundle", get(validation::get_schema_bundle))
+        // Route to list the data files belonging to a schema (?validate=true adds their status)
+        .route("/api/schemas/:name/files", get(validation::get_schema_files))
+        // Route to reload schemas from disk without a restart
+        .route("/api/schemas/reload", post(validation::reload_schemas))
+}
@@ -1,4 +1,4 @@
-/**
+/*
  * =================================================================
  * ⚙️ Rust WebSocket Hub (Router & Handler)
  * =================================================================
@@ -18,44 +18,738 @@
  */
 
 use axum::{
-    extract::{ws::{Message, WebSocket, WebSocketUpgrade}, State},
+    extract::{ws::{close_code, CloseFrame, Message, WebSocketUpgrade}, ConnectInfo, Query, State},
+    http::{header, HeaderMap},
     response::IntoResponse
 };
+use std::net::SocketAddr;
+use std::collections::{HashMap, VecDeque};
 use uuid::Uuid;
-use futures::{StreamExt, SinkExt};
-use tracing::{info, warn};
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use tracing::{info, info_span, warn, Instrument};
 use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tokio::{sync::{mpsc, Notify}, time::Instant};
 
 // Import core components
-use crate::api::state::AppState; 
-use crate::services::redis_service::RedisMessage; 
+use crate::api::state::{channel_matches, AppState, ConnectionStats}; 
+use crate::services::redis_service::{self, RedisMessage}; 
+use crate::models::{ApiError, ApiResult, Features};
+use crate::services::ticket_store;
+use crate::routes::health;
 
 // Client command struct for SUBSCRIBE/UNSUBSCRIBE messages
 #[derive(Debug, Deserialize, Serialize)]
 struct ClientCommand {
     #[serde(rename = "type")] 
     command_type: String,
-    channel: String, // e.g., "job:backup-UUID" sent by frontend
+    /// e.g., "job:backup-UUID" sent by frontend; unused by PAUSE/RESUME.
+    #[serde(default)]
+    channel: String,
+    /// Optional client capabilities negotiated on SUBSCRIBE (e.g., ["CBOR"]).
+    #[serde(default)]
+    capabilities: Vec<String>,
+    /// Optional batching window negotiated on SUBSCRIBE; messages are coalesced into
+    /// BATCH frames for this many milliseconds (0 or absent disables batching).
+    #[serde(default)]
+    batch_ms: Option<u64>,
+    /// Optional keepalive hint (seconds between server pings), clamped to the server bounds.
+    #[serde(default)]
+    heartbeat_interval: Option<u64>,
+    /// Optional outbound message shape on SUBSCRIBE: "envelope" (default) or "raw".
+    #[serde(default)]
+    format: Option<String>,
+    /// Optional replay window on SUBSCRIBE: job events the hub received in the last this
+    /// many milliseconds are sent right after SUBSCRIBED (at most `MAX_REPLAY_SINCE_MS`).
+    #[serde(default)]
+    replay_since_ms: Option<u64>,
+    /// Optional self-chosen name shown in the admin connection listing (e.g. "cli-v2").
+    #[serde(default)]
+    client_label: Option<String>,
+    /// Optional client-chosen id, echoed as `in_reply_to` in every reply frame.
+    #[serde(default)]
+    id: Option<serde_json::Value>,
+}
+
+/// Decodes a command from a JSON/CBOR value, keeping its `id` even if the rest is invalid
+/// so the ERROR reply can still be correlated.
+fn parse_command(raw: Result<serde_json::Value, String>) -> (Option<serde_json::Value>, Result<ClientCommand, String>) {
+    let value = match raw {
+        Ok(value) => value,
+        Err(detail) => return (None, Err(detail)),
+    };
+    let id = value.get("id").cloned();
+    let cmd = serde_json::from_value(value).map_err(|e| format!("Invalid client command: {}", e));
+    (id, cmd)
+}
+
+/// Decodes a binary frame's CBOR payload into the same JSON value a text frame yields.
+fn decode_cbor_command(bytes: &[u8]) -> Result<serde_json::Value, String> {
+    ciborium::de::from_reader(bytes).map_err(|e| format!("Failed to parse client command as CBOR: {}", e))
+}
+
+/// Capability a client sends in SUBSCRIBE to receive outbound frames as binary CBOR.
+const CBOR_CAPABILITY: &str = "CBOR";
+
+/// Version of the client/hub message protocol. Bump on breaking frame changes.
+const PROTOCOL_VERSION: u32 = 1;
+
+/// Features advertised to clients in the WELCOME frame.
+const CAPABILITIES: &[&str] = &["SUBSCRIBE", "UNSUBSCRIBE", "PAUSE", "RESUME", "SERVER_TIME", "BATCH", CBOR_CAPABILITY];
+
+/// Upper bound on the negotiated batching window, keeping latency bounded.
+const MAX_BATCH_MS: u64 = 1000;
+
+/// Longest replay window a client may request with `replay_since_ms`.
+const MAX_REPLAY_SINCE_MS: u64 = 300_000;
+
+/// Default seconds between server pings (overridable with `WS_HEARTBEAT_INTERVAL_SECS`).
+const DEFAULT_HEARTBEAT_INTERVAL_SECS: u64 = 30;
+
+/// Bounds a client's `heartbeat_interval` hint is clamped to.
+const MIN_HEARTBEAT_INTERVAL_SECS: u64 = 5;
+const MAX_HEARTBEAT_INTERVAL_SECS: u64 = 300;
+
+/// Clamps a heartbeat interval to the server-enforced bounds.
+fn clamp_heartbeat_interval(secs: u64) -> u64 {
+    secs.clamp(MIN_HEARTBEAT_INTERVAL_SECS, MAX_HEARTBEAT_INTERVAL_SECS)
+}
+
+/// Resolves the connection's ping interval from its `?heartbeat_interval=` hint, else
+/// `WS_HEARTBEAT_INTERVAL_SECS`, else 30 seconds. Non-numeric hints are rejected.
+fn negotiate_heartbeat_interval(hint: Option<&String>) -> ApiResult<u64> {
+    let requested = match hint {
+        Some(raw) => raw.parse::<u64>().map_err(|_| {
+            ApiError::BadRequest(format!("Invalid heartbeat_interval '{}': expected seconds", raw))
+        })?,
+        None => std::env::var("WS_HEARTBEAT_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_HEARTBEAT_INTERVAL_SECS),
+    };
+
+    let negotiated = clamp_heartbeat_interval(requested);
+    if negotiated != requested {
+        warn!(
+            "heartbeat_interval {}s is outside {}..={}s; using {}s",
+            requested, MIN_HEARTBEAT_INTERVAL_SECS, MAX_HEARTBEAT_INTERVAL_SECS, negotiated
+        );
+    }
+    Ok(negotiated)
+}
+
+/// First frame sent to every client after the upgrade, describing the server.
+#[derive(Debug, Serialize)]
+struct WelcomeMessage {
+    #[serde(rename = "type")]
+    message_type: &'static str,
+    server_version: &'static str,
+    protocol_version: u32,
+    capabilities: &'static [&'static str],
+    features: Features,
+    /// Identifies this connection in admin tooling (`GET /api/admin/connections/:id`).
+    connection_id: String,
+    /// Reconnect with `/ws?session=<id>` to restore this connection's subscriptions.
+    session_id: String,
+    /// True when this connection resumed a previous session.
+    resumed: bool,
+    /// Seconds between server pings; the connection is closed after two silent intervals.
+    heartbeat_interval: u64,
+}
+
+impl WelcomeMessage {
+    fn new(connection_id: &str, session_id: &str, resumed: bool, heartbeat_interval: u64) -> Self {
+        Self {
+            message_type: "WELCOME",
+            server_version: env!("CARGO_PKG_VERSION"),
+            protocol_version: PROTOCOL_VERSION,
+            capabilities: CAPABILITIES,
+            features: Features::from_env(),
+            connection_id: connection_id.to_string(),
+            session_id: session_id.to_string(),
+            resumed,
+            heartbeat_interval,
+        }
+    }
+}
+
+/// Why the server is closing a connection; each maps to a distinct WebSocket close code
+/// so clients can tell "reconnect later" from "fix your client".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CloseReason {
+    /// The server is shutting down (1001 going away): reconnect to another instance.
+    Shutdown,
+    /// The client kept the outbound queue full for too long (1008 policy violation).
+    SlowClient,
+    /// No frames (not even a Pong) for two heartbeat intervals (1008 policy violation).
+    IdleTimeout,
+    /// Too many consecutive malformed commands (1002 protocol error).
+    MalformedCommands,
+}
+
+impl CloseReason {
+    fn code(self) -> u16 {
+        match self {
+            Self::Shutdown => close_code::AWAY,
+            Self::SlowClient | Self::IdleTimeout => close_code::POLICY,
+            Self::MalformedCommands => close_code::PROTOCOL,
+        }
+    }
+
+    fn reason(self) -> &'static str {
+        match self {
+            Self::Shutdown => "server shutting down",
+            Self::SlowClient => "client too slow",
+            Self::IdleTimeout => "heartbeat timeout",
+            Self::MalformedCommands => "too many malformed commands",
+        }
+    }
+
+    /// The Close frame announcing this reason.
+    fn frame(self) -> Message {
+        Message::Close(Some(CloseFrame {
+            code: self.code(),
+            reason: self.reason().into(),
+        }))
+    }
+}
+
+/// Sends a Close frame explaining why the server is dropping the connection.
+/// Used by the sender task; the receiver loop queues `reason.frame()` on the direct channel.
+async fn close_with<W>(ws_sender: &mut W, reason: CloseReason)
+where
+    W: Sink<Message, Error = axum::Error> + Unpin,
+{
+    if let Err(e) = ws_sender.send(reason.frame()).await {
+        warn!("Could not send Close frame ({}): {}", reason.reason(), e);
+    }
+}
+
+/// Default number of consecutive malformed commands tolerated before closing.
+const DEFAULT_MAX_MALFORMED_COMMANDS: u32 = 5;
+
+/// Reads the malformed-command limit from `WS_MAX_MALFORMED_COMMANDS` (default 5).
+fn max_malformed_commands() -> u32 {
+    std::env::var("WS_MAX_MALFORMED_COMMANDS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|limit| *limit > 0)
+        .unwrap_or(DEFAULT_MAX_MALFORMED_COMMANDS)
+}
+
+/// Counts consecutive malformed commands; any valid command resets the count.
+struct MalformedCommandGuard {
+    consecutive: u32,
+    limit: u32,
+}
+
+impl MalformedCommandGuard {
+    fn new(limit: u32) -> Self {
+        Self { consecutive: 0, limit }
+    }
+
+    /// Records a malformed command. Returns true once the limit has been reached.
+    fn record_malformed(&mut self) -> bool {
+        self.consecutive += 1;
+        self.consecutive >= self.limit
+    }
+
+    fn reset(&mut self) {
+        self.consecutive = 0;
+    }
+}
+
+/// Default number of messages buffered while a connection is paused.
+const DEFAULT_PAUSE_BUFFER: usize = 100;
+
+/// Reads the paused-delivery buffer size from `WS_PAUSE_BUFFER` (default 100).
+/// Once full, the oldest buffered message is dropped; `0` drops everything while paused.
+fn pause_buffer_capacity() -> usize {
+    std::env::var("WS_PAUSE_BUFFER")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_PAUSE_BUFFER)
+}
+
+/// Messages held back while a connection is paused, bounded by dropping the oldest.
+struct PauseBuffer {
+    messages: VecDeque<RedisMessage>,
+    capacity: usize,
+    dropped: usize,
+}
+
+impl PauseBuffer {
+    fn new(capacity: usize) -> Self {
+        Self { messages: VecDeque::new(), capacity, dropped: 0 }
+    }
+
+    fn push(&mut self, msg: RedisMessage) {
+        if self.capacity == 0 {
+            self.dropped += 1;
+            return;
+        }
+        if self.messages.len() == self.capacity {
+            self.messages.pop_front();
+            self.dropped += 1;
+        }
+        self.messages.push_back(msg);
+    }
+
+    /// Takes the buffered messages (oldest first) and the number dropped, resetting both.
+    fn drain(&mut self) -> (Vec<RedisMessage>, usize) {
+        let dropped = std::mem::take(&mut self.dropped);
+        (self.messages.drain(..).collect(), dropped)
+    }
+}
+
+/// Default outbound queue length above which a connection counts as falling behind.
+const DEFAULT_SLOW_CLIENT_QUEUE: u64 = 50;
+
+/// Default seconds the queue must stay above the threshold before the client is flagged slow.
+const DEFAULT_SLOW_CLIENT_AFTER_SECS: u64 = 10;
+
+/// Outcome of one slow-client sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClientSpeed {
+    Ok,
+    Slow,
+    /// Slow for longer than the disconnect grace period.
+    Evict,
+}
+
+/// Flags connections whose outbound queue stays above a threshold for a sustained period.
+///
+/// Configured by `SLOW_CLIENT_QUEUE` (default 50), `SLOW_CLIENT_AFTER_SECS` (default 10)
+/// and the optional `SLOW_CLIENT_DISCONNECT_SECS` (unset: slow clients are never evicted).
+struct SlowClientDetector {
+    threshold: u64,
+    slow_after: Duration,
+    disconnect_after: Option<Duration>,
+    /// When the queue last rose above the threshold.
+    over_since: Option<Instant>,
+}
+
+impl SlowClientDetector {
+    fn from_env() -> Self {
+        let parse = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        Self::new(
+            parse("SLOW_CLIENT_QUEUE").unwrap_or(DEFAULT_SLOW_CLIENT_QUEUE),
+            Duration::from_secs(parse("SLOW_CLIENT_AFTER_SECS").unwrap_or(DEFAULT_SLOW_CLIENT_AFTER_SECS)),
+            parse("SLOW_CLIENT_DISCONNECT_SECS").filter(|secs| *secs > 0).map(Duration::from_secs),
+        )
+    }
+
+    fn new(threshold: u64, slow_after: Duration, disconnect_after: Option<Duration>) -> Self {
+        Self { threshold, slow_after, disconnect_after, over_since: None }
+    }
+
+    /// Records the current outbound queue length at `now`.
+    fn observe(&mut self, queued: u64, now: Instant) -> ClientSpeed {
+        if queued <= self.threshold {
+            self.over_since = None;
+            return ClientSpeed::Ok;
+        }
+
+        let over_for = now - *self.over_since.get_or_insert(now);
+        if over_for < self.slow_after {
+            ClientSpeed::Ok
+        } else if self.disconnect_after.is_some_and(|grace| over_for >= self.slow_after + grace) {
+            ClientSpeed::Evict
+        } else {
+            ClientSpeed::Slow
+        }
+    }
+}
+
+/// Default cap on messages per second delivered to a `*` (firehose) subscriber.
+const DEFAULT_FIREHOSE_MAX_PER_SEC: u32 = 200;
+
+/// Caps how many messages per second an admin subscribed to `*` receives, so the full
+/// firehose can't overwhelm an operations console (`FIREHOSE_MAX_PER_SEC`, default 200).
+/// Messages over the cap are dropped and reported once a second in a RATE_LIMITED frame.
+struct FirehoseLimiter {
+    max_per_sec: u32,
+    window_start: Instant,
+    sent_in_window: u32,
+    dropped: u64,
+}
+
+impl FirehoseLimiter {
+    fn from_env() -> Self {
+        let max = std::env::var("FIREHOSE_MAX_PER_SEC")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|max| *max > 0)
+            .unwrap_or(DEFAULT_FIREHOSE_MAX_PER_SEC);
+        Self::new(max, Instant::now())
+    }
+
+    fn new(max_per_sec: u32, now: Instant) -> Self {
+        Self { max_per_sec, window_start: now, sent_in_window: 0, dropped: 0 }
+    }
+
+    /// Whether one more message may be delivered at `now`; counts it as dropped if not.
+    fn allow(&mut self, now: Instant) -> bool {
+        if now - self.window_start >= Duration::from_secs(1) {
+            self.window_start = now;
+            self.sent_in_window = 0;
+        }
+        if self.sent_in_window < self.max_per_sec {
+            self.sent_in_window += 1;
+            true
+        } else {
+            self.dropped += 1;
+            false
+        }
+    }
+
+    /// Messages dropped since the last call.
+    fn take_dropped(&mut self) -> u64 {
+        std::mem::take(&mut self.dropped)
+    }
+}
+
+/// Messages coalesced for one BATCH frame, in arrival order.
+struct EventBatch {
+    messages: Vec<RedisMessage>,
+    /// When the batch must be flushed; set by the first message of the window.
+    deadline: Option<Instant>,
+}
+
+impl EventBatch {
+    fn new() -> Self {
+        Self { messages: Vec::new(), deadline: None }
+    }
+
+    /// Adds a message; the first message of a batch starts a `window`-long timer.
+    fn push(&mut self, msg: RedisMessage, window: Duration) {
+        if self.deadline.is_none() {
+            self.deadline = Some(Instant::now() + window);
+        }
+        self.messages.push(msg);
+    }
+
+    /// Takes the batched messages and clears the timer.
+    fn take(&mut self) -> Vec<RedisMessage> {
+        self.deadline = None;
+        std::mem::take(&mut self.messages)
+    }
+}
+
+/// Outbound frame carrying several relayed messages: `{"type":"BATCH","messages":[...]}`.
+/// Messages are envelopes, or bare payloads for `raw` connections.
+#[derive(Debug, Serialize)]
+struct BatchFrame<'a, T> {
+    #[serde(rename = "type")]
+    message_type: &'static str,
+    messages: &'a [T],
+}
+
+impl<'a, T> BatchFrame<'a, T> {
+    fn new(messages: &'a [T]) -> Self {
+        Self { message_type: "BATCH", messages }
+    }
+}
+
+/// Shape of relayed messages, negotiated with `format` on SUBSCRIBE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MessageFormat {
+    /// The full `{"channel": "...", "data": "..."}` envelope (default).
+    Envelope,
+    /// Only the `data` payload (the original JobEvent JSON), sent as a text frame.
+    Raw,
+}
+
+impl MessageFormat {
+    fn parse(format: &str) -> Option<Self> {
+        match format.to_ascii_lowercase().as_str() {
+            "envelope" => Some(Self::Envelope),
+            "raw" => Some(Self::Raw),
+            _ => None,
+        }
+    }
 }
 
+/// A `raw` message inside a BATCH: the payload as JSON, or as a string if it isn't JSON.
+fn raw_payload(msg: &RedisMessage) -> serde_json::Value {
+    serde_json::from_str(&msg.data).unwrap_or_else(|_| serde_json::Value::String(msg.data.clone()))
+}
+
+/// Builds a reply frame, adding `in_reply_to` when the command carried an id. Replies
+/// are binary CBOR once the client negotiated it, text JSON otherwise.
+fn reply_frame(mut frame: serde_json::Value, in_reply_to: Option<&serde_json::Value>, as_cbor: bool) -> Message {
+    if let (Some(id), Some(fields)) = (in_reply_to, frame.as_object_mut()) {
+        fields.insert("in_reply_to".to_string(), id.clone());
+    }
+    encode_outbound(&frame, as_cbor).unwrap_or_else(|_| Message::Text(frame.to_string()))
+}
+
+/// Builds an ACK frame: `{"type":"ACK","command":"..."}`.
+fn ack_frame(command: &str, in_reply_to: Option<&serde_json::Value>, as_cbor: bool) -> Message {
+    reply_frame(serde_json::json!({ "type": "ACK", "command": command }), in_reply_to, as_cbor)
+}
+
+/// Builds an ERROR frame: `{"type":"ERROR","code":"...","detail":"..."}`.
+fn error_frame(code: &str, detail: &str, in_reply_to: Option<&serde_json::Value>, as_cbor: bool) -> Message {
+    reply_frame(
+        serde_json::json!({ "type": "ERROR", "code": code, "detail": detail }),
+        in_reply_to,
+        as_cbor,
+    )
+}
+
+/// Per-connection settings shared between the receiver loop and the sender task.
+struct ConnectionContext {
+    connection_id: String,
+    /// Resumable session id announced in WELCOME (`/ws?session=`).
+    session_id: String,
+    /// Set once the client opts into binary CBOR frames.
+    use_cbor: AtomicBool,
+    /// Set when the client asked for `format: "raw"` (payload only, no envelope).
+    raw_format: AtomicBool,
+    /// True when the client upgraded with an admin ticket (or the deprecated `?token=`).
+    is_admin: bool,
+    /// Pause state and delivery counters, shared with `ConnectionManager` for admin inspection.
+    stats: Arc<ConnectionStats>,
+    /// Signalled by RESUME so the sender task flushes its buffer immediately.
+    resume: Notify,
+    /// Batching window in milliseconds negotiated on SUBSCRIBE (0 = send immediately).
+    batch_ms: AtomicU64,
+    /// Seconds between server pings, negotiated on upgrade and adjustable on SUBSCRIBE.
+    heartbeat_secs: AtomicU64,
+    /// Signalled by SUBSCRIBE when it changes `heartbeat_secs`, so the sender task
+    /// reschedules its next ping instead of waiting out the old interval.
+    heartbeat_changed: Notify,
+}
+
+impl ConnectionContext {
+    /// Whether the client negotiated binary CBOR frames.
+    fn wants_cbor(&self) -> bool {
+        self.use_cbor.load(Ordering::Relaxed)
+    }
+}
+
+/// Returns true if `token` matches the `WS_ADMIN_TOKEN` environment variable.
+/// Admin access is disabled entirely when the variable is unset or empty.
+fn is_admin_token(token: Option<&String>) -> bool {
+    token.is_some_and(|token| ticket_store::env_token_matches("WS_ADMIN_TOKEN", token))
+}
+
+/// Whether the deprecated `/ws?token=` admin login is still accepted (`WS_ALLOW_QUERY_TOKEN`,
+/// default true). Tokens in URLs end up in access logs; clients should present an admin
+/// ticket from `POST /api/ws-ticket` instead.
+fn query_token_allowed() -> bool {
+    std::env::var("WS_ALLOW_QUERY_TOKEN")
+        .map(|v| !matches!(v.to_lowercase().as_str(), "0" | "false" | "no"))
+        .unwrap_or(true)
+}
+
+/// Checks a deprecated `?token=` admin login. Each use logs a deprecation warning; once
+/// `allowed` is off, presenting a token at all is a 401 so stale clients fail loudly.
+fn query_token_admin(token: Option<&String>, allowed: bool) -> ApiResult<bool> {
+    match token {
+        None => Ok(false),
+        Some(_) if !allowed => Err(ApiError::Unauthorized(
+            "?token= is no longer accepted; connect with a ticket from POST /api/ws-ticket".to_string(),
+        )),
+        Some(_) => {
+            warn!("Client authenticated with the deprecated ?token= parameter; use POST /api/ws-ticket instead.");
+            Ok(is_admin_token(token))
+        }
+    }
+}
+
+/// Protocol versions this hub speaks, offered by clients in `Sec-WebSocket-Protocol`.
+const SUPPORTED_SUBPROTOCOLS: [&str; 1] = ["thinknet.v1"];
+
+/// Whether upgrades must name a supported subprotocol (`WS_REQUIRE_SUBPROTOCOL`, default
+/// false: clients that send none are served the current protocol in legacy mode).
+fn subprotocol_required() -> bool {
+    std::env::var("WS_REQUIRE_SUBPROTOCOL")
+        .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+        .unwrap_or(false)
+}
+
+/// Picks the first supported subprotocol the client offered.
+///
+/// Offering only unknown subprotocols is always a 400. Offering none is a 400 when
+/// `required`, otherwise `Ok(None)` (legacy client).
+fn negotiate_subprotocol(headers: &HeaderMap, required: bool) -> ApiResult<Option<&'static str>> {
+    let offered: Vec<&str> = headers
+        .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .filter(|protocol| !protocol.is_empty())
+        .collect();
+
+    if let Some(protocol) = offered
+        .iter()
+        .find_map(|offer| SUPPORTED_SUBPROTOCOLS.iter().find(|supported| *supported == offer))
+    {
+        return Ok(Some(*protocol));
+    }
+    if offered.is_empty() && !required {
+        return Ok(None);
+    }
+    Err(ApiError::BadRequest(format!(
+        "Unsupported WebSocket subprotocol {:?}; expected one of {:?}",
+        offered, SUPPORTED_SUBPROTOCOLS
+    )))
+}
 
 /// Router handler for the WebSocket upgrade request.
+///
+/// A `?ticket=` obtained from `POST /api/ws-ticket` is consumed on upgrade; unknown,
+/// reused, or expired tickets get 401. Tickets are mandatory when `WS_REQUIRE_TICKET` is set.
+/// The admin token as `?token=` still works but is deprecated (see `query_token_admin`).
+/// `?session=` names the previous session (from WELCOME) whose subscriptions should be restored.
+/// `?heartbeat_interval=<secs>` adjusts the ping interval within the server bounds.
+/// A supported `Sec-WebSocket-Protocol` (e.g. `thinknet.v1`) is echoed back; unknown
+/// ones are rejected, as is offering none when `WS_REQUIRE_SUBPROTOCOL` is set.
+///
+/// The ticket is consumed only after everything else was accepted, so a request
+/// rejected for another reason doesn't burn it.
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
+    Query(params): Query<HashMap<String, String>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
     State(state): State<AppState>,
-) -> impl IntoResponse {
-    ws.on_upgrade(|socket| handle_socket(socket, state))
+) -> ApiResult<impl IntoResponse> {
+    if !params.contains_key("ticket") && ticket_store::tickets_required() {
+        return Err(ApiError::Unauthorized("A WebSocket ticket is required".to_string()));
+    }
+
+    let subprotocol = negotiate_subprotocol(&headers, subprotocol_required())?;
+    if subprotocol.is_none() {
+        info!("WebSocket client offered no subprotocol; serving it in legacy mode.");
+    }
+    let heartbeat_secs = negotiate_heartbeat_interval(params.get("heartbeat_interval"))?;
+    let token_admin = query_token_admin(params.get("token"), query_token_allowed())?;
+
+    let ticket = match params.get("ticket") {
+        Some(id) => Some(state.ticket_store.consume(id).await.ok_or_else(|| {
+            ApiError::Unauthorized("Invalid or expired WebSocket ticket".to_string())
+        })?),
+        None => None,
+    };
+    let is_admin = token_admin || ticket.is_some_and(|t| t.is_admin);
+    let stats = Arc::new(ConnectionStats::new(
+        connect_info.map(|ConnectInfo(addr)| addr.to_string()),
+        headers
+            .get(header::USER_AGENT)
+            .and_then(|agent| agent.to_str().ok())
+            .map(str::to_string),
+    ));
+    if let Some(label) = params.get("client_label") {
+        stats.set_client_label(label);
+    }
+    let options = ConnectionOptions {
+        is_admin,
+        resume_session: params.get("session").cloned(),
+        stats,
+        heartbeat_secs,
+        policy: DeliveryPolicy::from_env(),
+    };
+    Ok(ws.protocols(SUPPORTED_SUBPROTOCOLS).on_upgrade(move |socket| {
+        // Every log line for this connection (both tasks) carries the connection_id.
+        let connection_id = Uuid::new_v4();
+        let span = info_span!("ws", connection_id = %connection_id);
+        let (ws_sender, ws_receiver) = socket.split();
+        handle_socket(ws_sender, ws_receiver, state, connection_id, options).instrument(span)
+    }))
+}
+
+/// Flow-control limits of a connection's sender task.
+struct DeliveryPolicy {
+    slow_detector: SlowClientDetector,
+    firehose_limiter: FirehoseLimiter,
+}
+
+impl DeliveryPolicy {
+    fn from_env() -> Self {
+        Self { slow_detector: SlowClientDetector::from_env(), firehose_limiter: FirehoseLimiter::from_env() }
+    }
+}
+
+/// What the upgrade request negotiated for a connection.
+struct ConnectionOptions {
+    is_admin: bool,
+    /// Session to resume (`?session=`), if any.
+    resume_session: Option<String>,
+    stats: Arc<ConnectionStats>,
+    heartbeat_secs: u64,
+    policy: DeliveryPolicy,
 }
 
 /// Core function that handles the WebSocket connection lifecycle and message passing.
-async fn handle_socket(socket: WebSocket, state: AppState) {
-    let connection_id = Uuid::new_v4();
-    info!("New WebSocket connection established: {}", connection_id);
+///
+/// Runs inside the connection's `ws` span; the sender task is instrumented with the same span.
+/// Takes the two halves of the socket, so tests can drive it over plain channels.
+async fn handle_socket<W, R>(
+    mut ws_sender: W,
+    mut ws_receiver: R,
+    state: AppState,
+    connection_id: Uuid,
+    options: ConnectionOptions,
+) where
+    W: Sink<Message, Error = axum::Error> + Send + Unpin + 'static,
+    R: Stream<Item = Result<Message, axum::Error>> + Unpin,
+{
+    let ConnectionOptions { is_admin, resume_session, stats, heartbeat_secs, policy } = options;
+    info!("New WebSocket connection established (admin: {})", is_admin);
 
-    let (mut ws_sender, mut ws_receiver) = socket.split();
+    // Restore the previous session's subscriptions if it is still within its grace window;
+    // otherwise start a fresh session.
+    let restored = match &resume_session {
+        Some(session_id) => state.sessions.resume(session_id).await,
+        None => None,
+    };
+    let session_id = match (&restored, resume_session) {
+        (Some(_), Some(session_id)) => session_id,
+        _ => Uuid::new_v4().to_string(),
+    };
 
-    // Placeholder channel (currently unused)
-    let (_tx, mut rx) = tokio::sync::mpsc::channel::<String>(32); 
+    let ctx = Arc::new(ConnectionContext {
+        connection_id: connection_id.to_string(),
+        session_id: session_id.clone(),
+        use_cbor: AtomicBool::new(false),
+        raw_format: AtomicBool::new(false),
+        is_admin,
+        stats: stats.clone(),
+        resume: Notify::new(),
+        batch_ms: AtomicU64::new(0),
+        heartbeat_secs: AtomicU64::new(heartbeat_secs),
+        heartbeat_changed: Notify::new(),
+    });
+
+    // Greet the client so it can detect protocol/server version mismatches after a deploy.
+    match serde_json::to_string(&WelcomeMessage::new(&ctx.connection_id, &session_id, restored.is_some(), heartbeat_secs)) {
+        Ok(welcome) => {
+            if ws_sender.send(Message::Text(welcome)).await.is_err() {
+                warn!("Could not send WELCOME. Client disconnected.");
+                return;
+            }
+        }
+        Err(e) => warn!("Failed to serialize WELCOME: {}", e),
+    }
+
+    // Direct channel for frames addressed to this client only (ERROR replies, Close).
+    let (direct_tx, mut rx) = mpsc::channel::<Message>(32); 
+    state
+        .connection_manager
+        .add_connection(&ctx.connection_id, direct_tx.clone(), stats)
+        .await;
+
+    if let Some(channels) = restored {
+        restore_subscriptions(&state, &ctx, channels, &direct_tx).await;
+    }
 
     // Subscribe to the global broadcast channel that carries all Redis messages.
     let mut broadcast_rx = state.connection_manager.broadcast_sender.subscribe();
@@ -63,106 +757,1112 @@ async fn handle_socket(socket: WebSocket, state: AppState) {
     // --- Sender Task (Relays messages from Redis to Client) ---
     // This task listens for the global Redis broadcast and filters it down to 
     // only the messages the current client is subscribed to.
-    let connection_id_clone = connection_id.to_string();
     let state_clone = state.clone();
+    let ctx_sender = ctx.clone();
     tokio::spawn(async move {
+        let mut paused_buffer = PauseBuffer::new(pause_buffer_capacity());
+        let mut batch = EventBatch::new();
+        let mut next_ping = Instant::now() + Duration::from_secs(heartbeat_secs);
+        let DeliveryPolicy { mut slow_detector, mut firehose_limiter } = policy;
+        let mut sample_ticker = tokio::time::interval(Duration::from_secs(1));
+        let mut delivered_at_last_sample = 0;
         loop {
+            let batch_deadline = batch.deadline;
             tokio::select! {
-                // 1. Handle targeted messages (mpsc, currently unused/placeholder)
+                // 1. Handle targeted messages (mpsc) from the receiver loop
                 Some(msg) = rx.recv() => {
-                    if ws_sender.send(Message::Text(msg)).await.is_err() {
-                        warn!("Could not send targeted message to client {}.", connection_id_clone);
+                    let closing = matches!(msg, Message::Close(_));
+                    if ws_sender.send(msg).await.is_err() {
+                        warn!("Could not send targeted message to client.");
                         break;
                     }
+                    if closing {
+                        break; // Server-initiated close: nothing more to send
+                    }
                 }
                 
                 // 2. CORE LOGIC: Handle incoming RedisMessage from the global broadcast
                 Ok(redis_msg) = broadcast_rx.recv() => {
                     // redis_msg.channel will be "ws_channel:job:UUID"
-                    let is_subscribed = {
+                    let (matched, firehose) = {
                         let subs = state_clone.connection_manager.subscriptions.lock().await;
                         
                         // This check REQUIRES the stored subscription (sub_channel) 
-                        // to be "ws_channel:job:UUID" to match redis_msg.channel.
-                        subs.get(&connection_id_clone)
-                            .map(|sub_channel| sub_channel == &redis_msg.channel)
-                            .unwrap_or(false)
+                        // to be "ws_channel:job:UUID" to match redis_msg.channel,
+                        // or a wildcard prefix such as "ws_channel:job:*".
+                        subs.get(&ctx_sender.connection_id)
+                            .map(|sub_channel| {
+                                let requested = sub_channel.strip_prefix("ws_channel:").unwrap_or(sub_channel);
+                                (channel_matches(sub_channel, &redis_msg.channel), is_global_wildcard(requested))
+                            })
+                            .unwrap_or((false, false))
                     };
+                    // Admin broadcasts reach every client, subscribed or not. Admin `*`
+                    // subscribers see every message except round-trip probes, up to the
+                    // firehose rate cap.
+                    let probe = firehose && redis_service::is_roundtrip_channel(&redis_msg.channel);
+                    let is_subscribed = redis_msg.channel == redis_service::LOCAL_BROADCAST_CHANNEL
+                        || (matched && !probe && (!firehose || firehose_limiter.allow(Instant::now())));
 
-                    if is_subscribed {
-                        // Serialize the full RedisMessage struct {channel: "...", data: "{...}"}
-                        let serialized_msg = match serde_json::to_string(&redis_msg) {
-                             Ok(s) => s,
-                             Err(e) => {
-                                 warn!("Failed to serialize RedisMessage for client {}: {}", connection_id_clone, e);
-                                 continue;
-                             }
-                        };
-                        
-                        // Send the message to the client over the WebSocket
-                        if ws_sender.send(Message::Text(serialized_msg)).await.is_err() {
-                            warn!("Could not send job message to client {}. Client disconnected.", connection_id_clone);
-                            break; // Exit the loop on send failure (disconnected client)
+                    let batch_ms = ctx_sender.batch_ms.load(Ordering::Relaxed);
+                    if is_subscribed && ctx_sender.stats.paused.load(Ordering::Relaxed) {
+                        // Flow control: hold the message until RESUME.
+                        paused_buffer.push(redis_msg);
+                    } else if is_subscribed && batch_ms > 0 {
+                        // Coalesce into the current window; flushed by the timer arm below.
+                        batch.push(redis_msg, Duration::from_millis(batch_ms));
+                    } else if is_subscribed
+                        && relay(&mut ws_sender, &redis_msg, &ctx_sender).await.is_err()
+                    {
+                        warn!("Could not send job message. Client disconnected.");
+                        break; // Exit the loop on send failure (disconnected client)
+                    }
+                }
+
+                // 3. Batching window elapsed: send everything collected as one BATCH frame
+                _ = tokio::time::sleep_until(batch_deadline.unwrap_or_else(Instant::now)), if batch_deadline.is_some() => {
+                    let messages = batch.take();
+                    let use_cbor = ctx_sender.wants_cbor();
+                    let encoded = if ctx_sender.raw_format.load(Ordering::Relaxed) {
+                        let payloads: Vec<serde_json::Value> = messages.iter().map(raw_payload).collect();
+                        encode_outbound(&BatchFrame::new(&payloads), use_cbor)
+                    } else {
+                        encode_outbound(&BatchFrame::new(&messages), use_cbor)
+                    };
+                    match encoded {
+                        Ok(frame) => {
+                            if ws_sender.send(frame).await.is_err() {
+                                warn!("Could not send batch. Client disconnected.");
+                                break;
+                            }
+                            ctx_sender.stats.delivered.fetch_add(messages.len() as u64, Ordering::Relaxed);
                         }
+                        Err(e) => warn!("Failed to serialize BATCH frame: {}", e),
+                    }
+                }
+
+                // 4. RESUME: flush what was buffered while paused, oldest first
+                _ = ctx_sender.resume.notified() => {
+                    let (buffered, dropped) = paused_buffer.drain();
+                    if dropped > 0 {
+                        warn!("Dropped {} message(s) that overflowed the pause buffer.", dropped);
+                        ctx_sender.stats.dropped.fetch_add(dropped as u64, Ordering::Relaxed);
+                    }
+                    let mut disconnected = false;
+                    for redis_msg in &buffered {
+                        if relay(&mut ws_sender, redis_msg, &ctx_sender).await.is_err() {
+                            disconnected = true;
+                            break;
+                        }
+                    }
+                    if disconnected {
+                        warn!("Could not flush paused messages. Client disconnected.");
+                        break;
                     }
                 }
                 
+                // 5. Keepalive: ping at the negotiated heartbeat interval
+                _ = tokio::time::sleep_until(next_ping) => {
+                    if ws_sender.send(Message::Ping(Vec::new())).await.is_err() {
+                        warn!("Could not send ping. Client disconnected.");
+                        break;
+                    }
+                    let interval = ctx_sender.heartbeat_secs.load(Ordering::Relaxed);
+                    next_ping = Instant::now() + Duration::from_secs(interval);
+                }
+
+                // SUBSCRIBE changed the interval: the receiver's idle timeout already uses
+                // the new value, so a shorter interval must not wait for the old ping.
+                _ = ctx_sender.heartbeat_changed.notified() => {
+                    let interval = ctx_sender.heartbeat_secs.load(Ordering::Relaxed);
+                    next_ping = next_ping.min(Instant::now() + Duration::from_secs(interval));
+                }
+
+                // 6. Once a second: sample delivery rate and outbound queue (slow-client detection)
+                _ = sample_ticker.tick() => {
+                    let stats = &ctx_sender.stats;
+                    let delivered = stats.delivered.load(Ordering::Relaxed);
+                    stats.messages_per_second.store(delivered - delivered_at_last_sample, Ordering::Relaxed);
+                    delivered_at_last_sample = delivered;
+
+                    let queued = (broadcast_rx.len() + rx.len()) as u64;
+                    stats.outbound_queue.store(queued, Ordering::Relaxed);
+                    let speed = slow_detector.observe(queued, Instant::now());
+                    if speed != ClientSpeed::Ok && !stats.slow.swap(true, Ordering::Relaxed) {
+                        warn!("Client is slow: {} message(s) queued.", queued);
+                    } else if speed == ClientSpeed::Ok {
+                        stats.slow.store(false, Ordering::Relaxed);
+                    }
+                    if speed == ClientSpeed::Evict {
+                        warn!("Disconnecting slow client ({} message(s) queued).", queued);
+                        close_with(&mut ws_sender, CloseReason::SlowClient).await;
+                        break;
+                    }
+
+                    let rate_limited = firehose_limiter.take_dropped();
+                    if rate_limited > 0 {
+                        stats.dropped.fetch_add(rate_limited, Ordering::Relaxed);
+                        let notice = serde_json::json!({ "type": "RATE_LIMITED", "dropped": rate_limited });
+                        if ws_sender.send(Message::Text(notice.to_string())).await.is_err() {
+                            warn!("Could not send RATE_LIMITED notice. Client disconnected.");
+                            break;
+                        }
+                    }
+                }
+
+                // 7. Server shutdown: tell the client to go elsewhere
+                _ = state_clone.connection_manager.shutdown.cancelled() => {
+                    close_with(&mut ws_sender, CloseReason::Shutdown).await;
+                    break;
+                }
+
                 // If any side of the select fails (e.g., channel closed), break the loop
                 else => break, 
             }
         }
-        info!("Job message worker stopped for client {}", connection_id_clone);
-    });
+        info!("Job message worker stopped");
+    }.instrument(tracing::Span::current()));
     
     // --- Receiver Loop (Handles commands from Client to Hub) ---
-    let connection_id_rcv = connection_id.to_string();
-    while let Some(result) = ws_receiver.next().await {
-        match result {
-            Ok(msg) => {
-                match msg {
-                    Message::Text(text) => {
-                        info!("Received command from {}: {}", connection_id, text);
-                        
-                        match serde_json::from_str::<ClientCommand>(&text) {
-                            Ok(cmd) => {
-                                match cmd.command_type.as_str() {
-                                    "SUBSCRIBE" => {
-                                        // 🔑 THE CRITICAL FIX: Add the prefix to match Redis publication
-                                        // If client sends "job:UUID", we store "ws_channel:job:UUID"
-                                        let full_channel_name = format!("ws_channel:{}", cmd.channel); 
-                                        info!("Attempting to subscribe client {} to Redis channel: {}", connection_id_rcv, full_channel_name);
-                                        
-                                        // Call to ConnectionManager.subscribe in state.rs
-                                        state.connection_manager.subscribe(&connection_id_rcv, &full_channel_name).await;
-                                    },
-                                    "UNSUBSCRIBE" => {
-                                        info!("Unsubscribing client {} from current job.", connection_id_rcv);
-                                        state.connection_manager.unsubscribe(&connection_id_rcv).await;
-                                    },
-                                    _ => warn!("Unknown client command type: {}", cmd.command_type),
-                                }
-                            }
-                            Err(e) => {
-                                warn!("Failed to parse client command as JSON: {}. Message: {}", e, text);
-                            }
-                        }
-                    }
-                    Message::Close(c) => {
-                        info!("Client {} closed connection: {:?}", connection_id, c);
-                        break;
-                    }
-                    // Ignore non-text messages
-                    _ => info!("Client {} sent non-text message.", connection_id), 
-                }
+    let mut malformed = MalformedCommandGuard::new(max_malformed_commands());
+    loop {
+        // Any frame (including the Pong answering our Ping) proves the client is alive;
+        // two heartbeat intervals of silence mean the connection is dead.
+        let idle_timeout = Duration::from_secs(2 * ctx.heartbeat_secs.load(Ordering::Relaxed));
+        let received = tokio::select! {
+            // The sender task sends the Close frame on shutdown.
+            _ = state.connection_manager.shutdown.cancelled() => break,
+            received = tokio::time::timeout(idle_timeout, ws_receiver.next()) => received,
+        };
+        let result = match received {
+            Ok(Some(result)) => result,
+            Ok(None) => break,
+            Err(_) => {
+                warn!("No frames from client for {:?}; closing idle connection.", idle_timeout);
+                let _ = direct_tx.send(CloseReason::IdleTimeout.frame()).await;
+                break;
             }
+        };
+        let msg = match result {
+            Ok(msg) => msg,
             Err(e) => {
-                warn!("WebSocket error for client {}: {}", connection_id, e);
+                warn!("WebSocket error: {}", e);
+                break;
+            }
+        };
+
+        let raw: Result<serde_json::Value, String> = match msg {
+            Message::Text(text) => {
+                info!("Received command: {}", text);
+                serde_json::from_str(&text)
+                    .map_err(|e| format!("Failed to parse client command as JSON: {}", e))
+            }
+            Message::Binary(bytes) => {
+                info!("Received binary command ({} bytes)", bytes.len());
+                decode_cbor_command(&bytes)
+            }
+            Message::Close(c) => {
+                info!("Client closed connection: {:?}", c);
                 break;
             }
+            // Ignore Ping/Pong control frames (answered automatically by axum)
+            _ => {
+                info!("Client sent control frame.");
+                continue;
+            }
+        };
+
+        let (command_id, parsed) = parse_command(raw);
+        match parsed {
+            Ok(cmd) => {
+                malformed.reset();
+                handle_command(cmd, &state, &ctx, &direct_tx).await;
+            }
+            Err(detail) => {
+                // Tell the client its command was ignored instead of failing silently.
+                warn!("{}", detail);
+                let _ = direct_tx.send(error_frame("bad_command", &detail, command_id.as_ref(), ctx.wants_cbor())).await;
+
+                if malformed.record_malformed() {
+                    warn!("Closing connection after {} consecutive malformed commands.", malformed.limit);
+                    let _ = direct_tx.send(CloseReason::MalformedCommands.frame()).await;
+                    break;
+                }
+            }
         }
     }
 
-    // Cleanup when the connection is dropped (Receiver loop exits)
-    state.connection_manager.remove_connection(&connection_id_rcv).await;
-    info!("WebSocket handler finished for client {}", connection_id);
+    // Keep the subscriptions resumable for the grace window, then clean up
+    // (Receiver loop exits when the connection is dropped).
+    let subscriptions = state.connection_manager.subscriptions_of(&ctx.connection_id).await;
+    state.sessions.save(&session_id, subscriptions).await;
+    state.connection_manager.remove_connection(&ctx.connection_id).await;
+    info!("WebSocket handler finished");
+}
+
+/// Processes a decoded client command. Text (JSON) and binary (CBOR) frames share this path.
+async fn handle_command(
+    cmd: ClientCommand,
+    state: &AppState,
+    ctx: &ConnectionContext,
+    direct_tx: &mpsc::Sender<Message>,
+) {
+    let connection_id = ctx.connection_id.as_str();
+    let reply_to = cmd.id.as_ref();
+    match cmd.command_type.as_str() {
+        "SUBSCRIBE" => {
+            if let Err((code, detail)) = authorize_subscription(ctx, &cmd.channel) {
+                let _ = direct_tx.send(error_frame(code, &detail, reply_to, ctx.wants_cbor())).await;
+                return;
+            }
+
+            if let Some(replay_ms) = cmd.replay_since_ms.filter(|ms| *ms > MAX_REPLAY_SINCE_MS) {
+                let detail = format!("replay_since_ms {} exceeds the maximum of {}", replay_ms, MAX_REPLAY_SINCE_MS);
+                let _ = direct_tx.send(error_frame("bad_replay", &detail, reply_to, ctx.wants_cbor())).await;
+                return;
+            }
+
+            // 🔑 THE CRITICAL FIX: Add the prefix to match Redis publication
+            // If client sends "job:UUID", we store "ws_channel:job:UUID"
+            let full_channel_name = format!("ws_channel:{}", cmd.channel); 
+            info!("Attempting to subscribe to Redis channel: {}", full_channel_name);
+
+            if let Some(requested) = cmd.format.as_deref() {
+                let Some(format) = MessageFormat::parse(requested) else {
+                    let detail = format!("Unknown format '{}'; expected \"envelope\" or \"raw\"", requested);
+                    let _ = direct_tx.send(error_frame("bad_format", &detail, reply_to, ctx.wants_cbor())).await;
+                    return;
+                };
+                info!("Client negotiated {:?} message format.", format);
+                ctx.raw_format.store(format == MessageFormat::Raw, Ordering::Relaxed);
+            }
+
+            if let Some(label) = cmd.client_label.as_deref() {
+                ctx.stats.set_client_label(label);
+                info!("Client labelled itself {:?}.", ctx.stats.client_label());
+            }
+
+            if cmd.capabilities.iter().any(|c| c.eq_ignore_ascii_case(CBOR_CAPABILITY)) {
+                info!("Client negotiated binary CBOR frames.");
+                ctx.use_cbor.store(true, Ordering::Relaxed);
+            }
+
+            if let Some(batch_ms) = cmd.batch_ms {
+                let batch_ms = batch_ms.min(MAX_BATCH_MS);
+                info!("Client negotiated {}ms message batching.", batch_ms);
+                ctx.batch_ms.store(batch_ms, Ordering::Relaxed);
+            }
+
+            if let Some(requested) = cmd.heartbeat_interval {
+                let interval = clamp_heartbeat_interval(requested);
+                info!("Client negotiated a {}s heartbeat interval (requested {}s).", interval, requested);
+                if ctx.heartbeat_secs.swap(interval, Ordering::Relaxed) != interval {
+                    ctx.heartbeat_changed.notify_one();
+                }
+            }
+            
+            // Call to ConnectionManager.subscribe in state.rs
+            state.connection_manager.subscribe(connection_id, &full_channel_name).await;
+            checkpoint_session(state, ctx).await;
+            let format = if ctx.raw_format.load(Ordering::Relaxed) { "raw" } else { "envelope" };
+            let subscribed = serde_json::json!({
+                "type": "SUBSCRIBED",
+                "channel": cmd.channel,
+                "heartbeat_interval": ctx.heartbeat_secs.load(Ordering::Relaxed),
+                "format": format,
+            });
+            let _ = direct_tx.send(reply_frame(subscribed, reply_to, ctx.wants_cbor())).await;
+
+            // A job that already finished publishes nothing more: hand over what it sent.
+            if deliver_final_status() && deliver_if_complete(state, ctx, &full_channel_name, direct_tx).await {
+                return;
+            }
+            if let Some(replay_ms) = cmd.replay_since_ms.filter(|ms| *ms > 0) {
+                replay_recent(state, ctx, &full_channel_name, replay_ms, direct_tx).await;
+            }
+        },
+        "UNSUBSCRIBE" => {
+            info!("Unsubscribing from current job.");
+            state.connection_manager.unsubscribe(connection_id).await;
+            checkpoint_session(state, ctx).await;
+            let unsubscribed = serde_json::json!({ "type": "UNSUBSCRIBED" });
+            let _ = direct_tx.send(reply_frame(unsubscribed, reply_to, ctx.wants_cbor())).await;
+        },
+        "PAUSE" => {
+            info!("Pausing message delivery.");
+            ctx.stats.paused.store(true, Ordering::Relaxed);
+            let _ = direct_tx.send(ack_frame("PAUSE", reply_to, ctx.wants_cbor())).await;
+        },
+        "RESUME" => {
+            info!("Resuming message delivery.");
+            ctx.stats.paused.store(false, Ordering::Relaxed);
+            ctx.resume.notify_one();
+            let _ = direct_tx.send(ack_frame("RESUME", reply_to, ctx.wants_cbor())).await;
+        },
+        "SERVER_TIME" => {
+            // Lets the client compute its clock offset for "time ago" rendering.
+            let mut time = health::server_time(chrono::Utc::now());
+            time["type"] = serde_json::json!("SERVER_TIME");
+            let _ = direct_tx.send(reply_frame(time, reply_to, ctx.wants_cbor())).await;
+        },
+        _ => {
+            warn!("Unknown client command type: {}", cmd.command_type);
+            let detail = format!("Unknown command type: {}", cmd.command_type);
+            let _ = direct_tx.send(error_frame("unknown_command", &detail, reply_to, ctx.wants_cbor())).await;
+        }
+    }
+}
+
+/// Checks that the connection may subscribe to `channel` (as the client names it, without
+/// the `ws_channel:` prefix). Returns the ERROR code and detail when it may not.
+fn authorize_subscription(ctx: &ConnectionContext, channel: &str) -> Result<(), (&'static str, String)> {
+    // A bare "*" would match every channel the hub relays; admins only.
+    if is_global_wildcard(channel) && !ctx.is_admin {
+        warn!("Rejected global wildcard subscription without admin rights.");
+        return Err(("forbidden", "Wildcard subscriptions to every channel require admin rights".to_string()));
+    }
+    Ok(())
+}
+
+/// Re-subscribes a resumed session's channels, applying the same checks as SUBSCRIBE:
+/// the stored session doesn't prove the new connection has the rights the old one had.
+/// Each channel that is refused gets an ERROR frame instead.
+async fn restore_subscriptions(
+    state: &AppState,
+    ctx: &ConnectionContext,
+    channels: Vec<String>,
+    direct_tx: &mpsc::Sender<Message>,
+) {
+    for channel in channels {
+        let requested = channel.strip_prefix("ws_channel:").unwrap_or(&channel);
+        if let Err((code, detail)) = authorize_subscription(ctx, requested) {
+            warn!("Not restoring subscription {}: {}", channel, detail);
+            let _ = direct_tx.send(error_frame(code, &detail, None, ctx.wants_cbor())).await;
+            continue;
+        }
+        info!("Restoring subscription from resumed session: {}", channel);
+        state.connection_manager.subscribe(&ctx.connection_id, &channel).await;
+    }
+    checkpoint_session(state, ctx).await;
+}
+
+/// Persists the connection's current subscriptions (`PERSIST_SUBSCRIPTIONS=true`).
+async fn checkpoint_session(state: &AppState, ctx: &ConnectionContext) {
+    let subscriptions = state.connection_manager.subscriptions_of(&ctx.connection_id).await;
+    state.sessions.checkpoint(&ctx.session_id, &subscriptions).await;
+}
+
+/// True for subscriptions that would match every channel (e.g. "*").
+fn is_global_wildcard(channel: &str) -> bool {
+    channel.trim_end_matches('*').is_empty()
+}
+
+/// Sends the buffered job events received in the last `replay_ms` milliseconds on
+/// channels matching `subscription`, oldest first, in the connection's negotiated format.
+///
+/// Replay goes through the targeted queue, so a live message arriving meanwhile may be
+/// delivered before the replay finishes or appear in both.
+async fn replay_recent(
+    state: &AppState,
+    ctx: &ConnectionContext,
+    subscription: &str,
+    replay_ms: u64,
+    direct_tx: &mpsc::Sender<Message>,
+) {
+    let since = chrono::Utc::now() - chrono::Duration::milliseconds(replay_ms as i64);
+    let events = state
+        .job_store
+        .received_since(since, |job_id| channel_matches(subscription, &redis_service::job_channel(job_id)))
+        .await;
+    info!("Replaying {} event(s) from the last {}ms on {}.", events.len(), replay_ms, subscription);
+
+    for (job_id, payload) in events {
+        if !send_buffered(ctx, direct_tx, &job_id, payload).await {
+            return;
+        }
+    }
+}
+
+/// Whether SUBSCRIBE to an already finished job immediately delivers its buffered
+/// events and an ALREADY_COMPLETE hint (`WS_DELIVER_FINAL_STATUS`, default true).
+fn deliver_final_status() -> bool {
+    std::env::var("WS_DELIVER_FINAL_STATUS")
+        .map(|v| !matches!(v.to_lowercase().as_str(), "0" | "false" | "no"))
+        .unwrap_or(true)
+}
+
+/// If `subscription` is a job channel whose latest cached event is final, sends the
+/// job's buffered events (or just that final event when none are buffered) followed by
+/// `{"type":"ALREADY_COMPLETE"}`, and returns true. This covers a job finishing between
+/// the HTTP trigger response and the client's SUBSCRIBE.
+async fn deliver_if_complete(
+    state: &AppState,
+    ctx: &ConnectionContext,
+    subscription: &str,
+    direct_tx: &mpsc::Sender<Message>,
+) -> bool {
+    let Some(job_id) = redis_service::parse_job_channel(subscription) else {
+        return false;
+    };
+    let Some(terminal) = state.connection_manager.latest_events.terminal(job_id).await else {
+        return false;
+    };
+    info!("Job {} already finished ({}); delivering its final status.", job_id, terminal.status);
+
+    let buffered = match state.job_store.events_since(job_id, None).await {
+        Some(events) if !events.is_empty() => events,
+        _ => serde_json::to_value(&terminal).map(|event| vec![event]).unwrap_or_default(),
+    };
+    for payload in buffered {
+        if !send_buffered(ctx, direct_tx, job_id, payload).await {
+            return true;
+        }
+    }
+
+    let hint = serde_json::json!({
+        "type": "ALREADY_COMPLETE",
+        "channel": subscription.trim_start_matches("ws_channel:"),
+        "status": terminal.status,
+    });
+    let _ = direct_tx.send(reply_frame(hint, None, ctx.wants_cbor())).await;
+    true
+}
+
+/// Queues a buffered job event as if it had just been relayed on the job's channel.
+/// Returns false once the client is gone.
+async fn send_buffered(
+    ctx: &ConnectionContext,
+    direct_tx: &mpsc::Sender<Message>,
+    job_id: &str,
+    payload: serde_json::Value,
+) -> bool {
+    let data = match payload {
+        serde_json::Value::String(text) => text,
+        other => other.to_string(),
+    };
+    let redis_msg = RedisMessage::new(redis_service::job_channel(job_id), data);
+    let encoded = if ctx.raw_format.load(Ordering::Relaxed) {
+        Ok(Message::Text(redis_msg.data))
+    } else {
+        encode_outbound(&redis_msg, ctx.wants_cbor())
+    };
+    match encoded {
+        Ok(frame) => {
+            if direct_tx.send(frame).await.is_err() {
+                return false;
+            }
+            ctx.stats.delivered.fetch_add(1, Ordering::Relaxed);
+        }
+        Err(e) => warn!("Failed to serialize buffered message: {}", e),
+    }
+    true
+}
+
+/// Sends a RedisMessage to the client, encoded for the connection's negotiated format.
+///
+/// Serialization failures are logged and skipped; `Err` means the client is gone.
+async fn relay<W>(
+    ws_sender: &mut W,
+    redis_msg: &RedisMessage,
+    ctx: &ConnectionContext,
+) -> Result<(), axum::Error>
+where
+    W: Sink<Message, Error = axum::Error> + Unpin,
+{
+    // `raw` clients get the payload text as-is. Everyone else gets the full
+    // RedisMessage struct {channel: "...", data: "{...}"} as CBOR for clients
+    // that negotiated it, JSON text otherwise.
+    let encoded = if ctx.raw_format.load(Ordering::Relaxed) {
+        Ok(Message::Text(redis_msg.data.clone()))
+    } else {
+        encode_outbound(redis_msg, ctx.wants_cbor())
+    };
+    match encoded {
+        Ok(frame) => {
+            ws_sender.send(frame).await?;
+            ctx.stats.delivered.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+        Err(e) => {
+            warn!("Failed to serialize RedisMessage: {}", e);
+            Ok(())
+        }
+    }
+}
+
+/// Encodes an outbound frame (a RedisMessage or a BATCH) as binary CBOR or JSON text.
+fn encode_outbound<T: Serialize>(frame: &T, as_cbor: bool) -> Result<Message, String> {
+    if as_cbor {
+        let mut buffer = Vec::new();
+        ciborium::ser::into_writer(frame, &mut buffer).map_err(|e| e.to_string())?;
+        Ok(Message::Binary(buffer))
+    } else {
+        serde_json::to_string(frame)
+            .map(Message::Text)
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn garbage_command_yields_bad_command_error_frame() {
+        let detail = serde_json::from_str::<ClientCommand>("not json").unwrap_err().to_string();
+
+        let Message::Text(frame) = error_frame("bad_command", &detail, None, false) else {
+            panic!("ERROR frame should be text");
+        };
+        let frame: serde_json::Value = serde_json::from_str(&frame).unwrap();
+        assert_eq!(frame["type"], "ERROR");
+        assert_eq!(frame["code"], "bad_command");
+        assert_eq!(frame["detail"], detail.as_str());
+        assert!(frame.get("in_reply_to").is_none());
+    }
+
+    fn frame_json(frame: Message) -> serde_json::Value {
+        let Message::Text(text) = frame else {
+            panic!("reply frames should be text");
+        };
+        serde_json::from_str(&text).unwrap()
+    }
+
+    #[test]
+    fn command_id_round_trips_on_success_reply() {
+        let raw = serde_json::json!({ "type": "SUBSCRIBE", "channel": "job:1", "id": "req-1" });
+        let (id, cmd) = parse_command(Ok(raw));
+        let cmd = cmd.unwrap();
+        assert_eq!(cmd.id, id);
+
+        let reply = reply_frame(serde_json::json!({ "type": "SUBSCRIBED" }), cmd.id.as_ref(), false);
+        assert_eq!(frame_json(reply)["in_reply_to"], "req-1");
+    }
+
+    #[test]
+    fn command_id_round_trips_on_error_reply() {
+        // Missing "type": the command is invalid, but its id is still recovered.
+        let (id, cmd) = parse_command(Ok(serde_json::json!({ "channel": "job:1", "id": 42 })));
+        let detail = cmd.unwrap_err();
+
+        let frame = frame_json(error_frame("bad_command", &detail, id.as_ref(), false));
+        assert_eq!(frame["type"], "ERROR");
+        assert_eq!(frame["in_reply_to"], 42);
+    }
+
+    fn message(n: usize) -> RedisMessage {
+        RedisMessage::new("ws_channel:job:1", n.to_string())
+    }
+
+    #[test]
+    fn pause_buffer_drops_oldest_when_full() {
+        let mut buffer = PauseBuffer::new(2);
+        for n in 0..3 {
+            buffer.push(message(n));
+        }
+
+        let (messages, dropped) = buffer.drain();
+        let data: Vec<&str> = messages.iter().map(|m| m.data.as_str()).collect();
+        assert_eq!(data, vec!["1", "2"]);
+        assert_eq!(dropped, 1);
+        assert!(buffer.drain().0.is_empty());
+    }
+
+    #[test]
+    fn batch_coalesces_messages_in_order() {
+        let mut batch = EventBatch::new();
+        batch.push(message(1), Duration::from_millis(50));
+        let deadline = batch.deadline.expect("first message starts the window");
+        batch.push(message(2), Duration::from_millis(50));
+        batch.push(message(3), Duration::from_millis(50));
+        assert_eq!(batch.deadline, Some(deadline), "later messages don't extend the window");
+
+        let messages = batch.take();
+        let Message::Text(frame) = encode_outbound(&BatchFrame::new(&messages), false).unwrap() else {
+            panic!("JSON batch should be a text frame");
+        };
+        let frame: serde_json::Value = serde_json::from_str(&frame).unwrap();
+        assert_eq!(frame["type"], "BATCH");
+        let data: Vec<&str> = frame["messages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["data"].as_str().unwrap())
+            .collect();
+        assert_eq!(data, vec!["1", "2", "3"]);
+        assert!(batch.deadline.is_none());
+    }
+
+    #[test]
+    fn pause_and_resume_parse_without_channel() {
+        let cmd: ClientCommand = serde_json::from_str(r#"{"type":"PAUSE"}"#).unwrap();
+        assert_eq!(cmd.command_type, "PAUSE");
+        assert!(cmd.channel.is_empty());
+    }
+
+    #[test]
+    fn guard_trips_after_consecutive_malformed_commands() {
+        let mut guard = MalformedCommandGuard::new(3);
+        assert!(!guard.record_malformed());
+        assert!(!guard.record_malformed());
+        assert!(guard.record_malformed());
+    }
+
+    #[test]
+    fn valid_command_resets_malformed_count() {
+        let mut guard = MalformedCommandGuard::new(2);
+        assert!(!guard.record_malformed());
+        guard.reset();
+        assert!(!guard.record_malformed());
+        assert!(guard.record_malformed());
+    }
+
+    #[test]
+    fn heartbeat_hint_is_clamped_to_bounds_and_garbage_rejected() {
+        let hint = |raw: &str| negotiate_heartbeat_interval(Some(&raw.to_string()));
+        assert_eq!(hint("60").unwrap(), 60);
+        assert_eq!(hint("1").unwrap(), MIN_HEARTBEAT_INTERVAL_SECS);
+        assert_eq!(hint("86400").unwrap(), MAX_HEARTBEAT_INTERVAL_SECS);
+        assert!(matches!(hint("soon"), Err(ApiError::BadRequest(_))));
+    }
+
+    #[test]
+    fn supported_subprotocols_are_accepted_and_unknown_ones_rejected() {
+        let offering = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::SEC_WEBSOCKET_PROTOCOL, value.parse().unwrap());
+            headers
+        };
+
+        assert_eq!(negotiate_subprotocol(&offering("thinknet.v1"), true).unwrap(), Some("thinknet.v1"));
+        assert_eq!(negotiate_subprotocol(&offering("thinknet.v9, thinknet.v1"), true).unwrap(), Some("thinknet.v1"));
+        assert!(matches!(negotiate_subprotocol(&offering("thinknet.v9"), false), Err(ApiError::BadRequest(_))));
+
+        // No subprotocol: legacy mode unless one is required.
+        assert_eq!(negotiate_subprotocol(&HeaderMap::new(), false).unwrap(), None);
+        assert!(matches!(negotiate_subprotocol(&HeaderMap::new(), true), Err(ApiError::BadRequest(_))));
+    }
+
+    #[test]
+    fn firehose_limiter_caps_messages_per_second() {
+        let start = Instant::now();
+        let mut limiter = FirehoseLimiter::new(2, start);
+
+        assert!(limiter.allow(start));
+        assert!(limiter.allow(start + Duration::from_millis(100)));
+        assert!(!limiter.allow(start + Duration::from_millis(200)));
+        assert!(!limiter.allow(start + Duration::from_millis(300)));
+        assert_eq!(limiter.take_dropped(), 2);
+        assert_eq!(limiter.take_dropped(), 0);
+
+        // A new one-second window starts fresh.
+        assert!(limiter.allow(start + Duration::from_millis(1100)));
+    }
+
+    #[test]
+    fn slow_client_is_flagged_after_sustained_backlog_then_evicted() {
+        let start = Instant::now();
+        let mut detector = SlowClientDetector::new(10, Duration::from_secs(5), Some(Duration::from_secs(5)));
+
+        assert_eq!(detector.observe(50, start), ClientSpeed::Ok);
+        assert_eq!(detector.observe(50, start + Duration::from_secs(6)), ClientSpeed::Slow);
+        assert_eq!(detector.observe(50, start + Duration::from_secs(11)), ClientSpeed::Evict);
+
+        // Draining the queue resets the clock.
+        assert_eq!(detector.observe(3, start + Duration::from_secs(12)), ClientSpeed::Ok);
+        assert_eq!(detector.observe(50, start + Duration::from_secs(13)), ClientSpeed::Ok);
+    }
+
+    #[test]
+    fn server_initiated_closes_carry_distinct_codes() {
+        let code_of = |reason: CloseReason| match reason.frame() {
+            Message::Close(Some(frame)) => frame.code,
+            other => panic!("expected a Close frame, got {:?}", other),
+        };
+
+        assert_eq!(code_of(CloseReason::Shutdown), 1001);
+        assert_eq!(code_of(CloseReason::SlowClient), 1008);
+        assert_eq!(code_of(CloseReason::IdleTimeout), 1008);
+        assert_eq!(code_of(CloseReason::MalformedCommands), 1002);
+    }
+
+    #[test]
+    fn raw_format_is_negotiable_and_batches_bare_payloads() {
+        assert_eq!(MessageFormat::parse("RAW"), Some(MessageFormat::Raw));
+        assert_eq!(MessageFormat::parse("envelope"), Some(MessageFormat::Envelope));
+        assert_eq!(MessageFormat::parse("xml"), None);
+
+        let messages = [
+            RedisMessage::new("ws_channel:job:a", r#"{"status":"running"}"#),
+            RedisMessage::new("ws_channel:job:a", "not json"),
+        ];
+        let payloads: Vec<serde_json::Value> = messages.iter().map(raw_payload).collect();
+        let Message::Text(frame) = encode_outbound(&BatchFrame::new(&payloads), false).unwrap() else {
+            panic!("BATCH frame should be text");
+        };
+        let frame: serde_json::Value = serde_json::from_str(&frame).unwrap();
+        assert_eq!(frame["messages"], serde_json::json!([{ "status": "running" }, "not json"]));
+    }
+
+    fn test_ctx(connection_id: &str, is_admin: bool) -> ConnectionContext {
+        ConnectionContext {
+            connection_id: connection_id.to_string(),
+            session_id: format!("session-{}", connection_id),
+            use_cbor: AtomicBool::new(false),
+            raw_format: AtomicBool::new(false),
+            is_admin,
+            stats: Arc::new(ConnectionStats::new(None, None)),
+            resume: Notify::new(),
+            batch_ms: AtomicU64::new(0),
+            heartbeat_secs: AtomicU64::new(DEFAULT_HEARTBEAT_INTERVAL_SECS),
+            heartbeat_changed: Notify::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn cbor_subscribe_gets_cbor_subscribed_reply() {
+        let state = AppState::for_tests().await;
+        let ctx = test_ctx("c1", false);
+        let (tx, mut rx) = mpsc::channel(8);
+
+        let command = serde_json::json!({ "type": "SUBSCRIBE", "channel": "job:1", "capabilities": ["CBOR"], "id": 7 });
+        let mut frame = Vec::new();
+        ciborium::ser::into_writer(&command, &mut frame).unwrap();
+
+        let (_, cmd) = parse_command(decode_cbor_command(&frame));
+        handle_command(cmd.unwrap(), &state, &ctx, &tx).await;
+
+        let Some(Message::Binary(reply)) = rx.recv().await else {
+            panic!("SUBSCRIBED should be a binary CBOR frame");
+        };
+        let reply: serde_json::Value = ciborium::de::from_reader(reply.as_slice()).unwrap();
+        assert_eq!(reply["type"], "SUBSCRIBED");
+        assert_eq!(reply["channel"], "job:1");
+        assert_eq!(reply["in_reply_to"], 7);
+    }
+
+    fn subscribe_cmd(channel: &str) -> ClientCommand {
+        let (_, cmd) = parse_command(Ok(serde_json::json!({ "type": "SUBSCRIBE", "channel": channel })));
+        cmd.unwrap()
+    }
+
+    async fn next_reply(rx: &mut mpsc::Receiver<Message>) -> serde_json::Value {
+        frame_json(rx.recv().await.expect("a reply frame"))
+    }
+
+    #[tokio::test]
+    async fn exact_subscription_matches_only_its_channel() {
+        let state = AppState::for_tests().await;
+        let ctx = test_ctx("c1", false);
+        let (tx, mut rx) = mpsc::channel(8);
+
+        handle_command(subscribe_cmd("job:1"), &state, &ctx, &tx).await;
+
+        assert_eq!(next_reply(&mut rx).await["type"], "SUBSCRIBED");
+        let subs = state.connection_manager.subscriptions_of("c1").await;
+        assert_eq!(subs, vec!["ws_channel:job:1".to_string()]);
+        assert!(channel_matches(&subs[0], "ws_channel:job:1"));
+        assert!(!channel_matches(&subs[0], "ws_channel:job:10"));
+    }
+
+    #[tokio::test]
+    async fn wildcard_subscription_matches_by_prefix() {
+        let state = AppState::for_tests().await;
+        let ctx = test_ctx("c1", false);
+        let (tx, mut rx) = mpsc::channel(8);
+
+        handle_command(subscribe_cmd("job:*"), &state, &ctx, &tx).await;
+
+        assert_eq!(next_reply(&mut rx).await["type"], "SUBSCRIBED");
+        assert!(state.connection_manager.has_subscribers("ws_channel:job:42").await);
+        assert!(!state.connection_manager.has_subscribers("ws_channel:device:1").await);
+    }
+
+    #[tokio::test]
+    async fn global_wildcard_requires_admin() {
+        let state = AppState::for_tests().await;
+        let (tx, mut rx) = mpsc::channel(8);
+
+        handle_command(subscribe_cmd("*"), &state, &test_ctx("user", false), &tx).await;
+        let reply = next_reply(&mut rx).await;
+        assert_eq!((reply["type"].as_str(), reply["code"].as_str()), (Some("ERROR"), Some("forbidden")));
+        assert!(state.connection_manager.subscriptions_of("user").await.is_empty());
+
+        handle_command(subscribe_cmd("*"), &state, &test_ctx("admin", true), &tx).await;
+        assert_eq!(next_reply(&mut rx).await["type"], "SUBSCRIBED");
+        assert!(state.connection_manager.has_subscribers("ws_channel:device:1").await);
+    }
+
+    #[test]
+    fn query_token_login_is_deprecated_and_can_be_disabled() {
+        let token = "not-the-admin-token".to_string();
+        assert!(!query_token_admin(None, false).unwrap());
+        assert!(!query_token_admin(Some(&token), true).unwrap());
+        assert!(matches!(query_token_admin(Some(&token), false), Err(ApiError::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn resubscribing_replaces_the_current_subscription() {
+        let state = AppState::for_tests().await;
+        let ctx = test_ctx("c1", false);
+        let (tx, mut rx) = mpsc::channel(8);
+
+        handle_command(subscribe_cmd("job:1"), &state, &ctx, &tx).await;
+        handle_command(subscribe_cmd("job:2"), &state, &ctx, &tx).await;
+
+        assert_eq!(next_reply(&mut rx).await["type"], "SUBSCRIBED");
+        assert_eq!(next_reply(&mut rx).await["type"], "SUBSCRIBED");
+        assert_eq!(state.connection_manager.subscriptions_of("c1").await, vec!["ws_channel:job:2".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn restored_global_wildcard_still_requires_admin() {
+        let state = AppState::for_tests().await;
+        let (tx, mut rx) = mpsc::channel(8);
+        let channels = vec!["ws_channel:*".to_string()];
+
+        restore_subscriptions(&state, &test_ctx("user", false), channels.clone(), &tx).await;
+        assert_eq!(next_reply(&mut rx).await["code"], "forbidden");
+        assert!(state.connection_manager.subscriptions_of("user").await.is_empty());
+
+        restore_subscriptions(&state, &test_ctx("admin", true), channels.clone(), &tx).await;
+        assert_eq!(state.connection_manager.subscriptions_of("admin").await, channels);
+        assert!(rx.try_recv().is_err());
+    }
+
+    /// The client side of a connection served over in-memory channels.
+    struct TestClient {
+        /// Frames sent by the client; kept alive so the server doesn't see a disconnect.
+        sender: futures::channel::mpsc::UnboundedSender<Result<Message, axum::Error>>,
+        frames: futures::channel::mpsc::UnboundedReceiver<Message>,
+    }
+
+    impl TestClient {
+        /// Subscribes to `channel` and waits for the SUBSCRIBED reply.
+        async fn subscribe(&mut self, channel: &str) {
+            let command = serde_json::json!({ "type": "SUBSCRIBE", "channel": channel });
+            self.sender.unbounded_send(Ok(Message::Text(command.to_string()))).unwrap();
+            let reply = self.next_text().await;
+            assert_eq!(reply["type"], "SUBSCRIBED", "{}", reply);
+        }
+
+        /// Next text frame as JSON, skipping pings.
+        async fn next_text(&mut self) -> serde_json::Value {
+            while let Some(frame) = self.frames.next().await {
+                if let Message::Text(text) = frame {
+                    return serde_json::from_str(&text).unwrap();
+                }
+            }
+            panic!("connection ended before a text frame");
+        }
+
+        /// Reads frames until the server's Close frame and returns its code and reason.
+        async fn close_frame(&mut self) -> (u16, String) {
+            while let Some(frame) = self.frames.next().await {
+                if let Message::Close(Some(close)) = frame {
+                    return (close.code, close.reason.to_string());
+                }
+            }
+            panic!("connection ended without a Close frame");
+        }
+    }
+
+    /// Runs `handle_socket` for a new connection and waits for its WELCOME.
+    async fn connect(state: &AppState, heartbeat_secs: u64, policy: DeliveryPolicy) -> TestClient {
+        connect_as(state, false, heartbeat_secs, policy).await
+    }
+
+    async fn connect_as(state: &AppState, is_admin: bool, heartbeat_secs: u64, policy: DeliveryPolicy) -> TestClient {
+        let (server_tx, frames) = futures::channel::mpsc::unbounded();
+        let (client_tx, client_rx) = futures::channel::mpsc::unbounded();
+        let options = ConnectionOptions {
+            is_admin,
+            resume_session: None,
+            stats: Arc::new(ConnectionStats::new(None, None)),
+            heartbeat_secs,
+            policy,
+        };
+        tokio::spawn(handle_socket(server_tx.sink_map_err(axum::Error::new), client_rx, state.clone(), Uuid::new_v4(), options));
+
+        let mut client = TestClient { sender: client_tx, frames };
+        let Some(Message::Text(welcome)) = client.frames.next().await else {
+            panic!("expected WELCOME");
+        };
+        assert!(welcome.contains("WELCOME"));
+        client
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn silent_client_is_closed_after_two_heartbeat_intervals() {
+        let state = AppState::for_tests().await;
+        let mut client = connect(&state, MIN_HEARTBEAT_INTERVAL_SECS, DeliveryPolicy::from_env()).await;
+
+        let started = Instant::now();
+        assert_eq!(client.close_frame().await, (close_code::POLICY, "heartbeat timeout".to_string()));
+        assert_eq!(started.elapsed().as_secs(), 2 * MIN_HEARTBEAT_INTERVAL_SECS);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn lowering_heartbeat_on_subscribe_reschedules_the_next_ping() {
+        let state = AppState::for_tests().await;
+        let mut client = connect(&state, DEFAULT_HEARTBEAT_INTERVAL_SECS, DeliveryPolicy::from_env()).await;
+        let command = serde_json::json!({
+            "type": "SUBSCRIBE",
+            "channel": "job:1",
+            "heartbeat_interval": MIN_HEARTBEAT_INTERVAL_SECS,
+        });
+        client.sender.unbounded_send(Ok(Message::Text(command.to_string()))).unwrap();
+        assert_eq!(client.next_text().await["heartbeat_interval"], MIN_HEARTBEAT_INTERVAL_SECS);
+
+        // A client that only answers pings stays connected well past the old interval.
+        let mut pings = 0;
+        let answering = async {
+            while let Some(frame) = client.frames.next().await {
+                match frame {
+                    Message::Ping(payload) => {
+                        pings += 1;
+                        client.sender.unbounded_send(Ok(Message::Pong(payload))).unwrap();
+                    }
+                    Message::Close(close) => panic!("connection closed: {:?}", close),
+                    _ => {}
+                }
+            }
+        };
+        let outlived = tokio::time::timeout(Duration::from_secs(2 * DEFAULT_HEARTBEAT_INTERVAL_SECS), answering).await;
+        assert!(outlived.is_err(), "connection ended");
+        assert!(pings >= 2 * DEFAULT_HEARTBEAT_INTERVAL_SECS / MIN_HEARTBEAT_INTERVAL_SECS - 1, "{} pings", pings);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_client_is_closed_with_policy_violation() {
+        let state = AppState::for_tests().await;
+        let policy = DeliveryPolicy {
+            // Evict as soon as anything is queued.
+            slow_detector: SlowClientDetector::new(0, Duration::ZERO, Some(Duration::ZERO)),
+            firehose_limiter: FirehoseLimiter::new(DEFAULT_FIREHOSE_MAX_PER_SEC, Instant::now()),
+        };
+        let mut client = connect(&state, DEFAULT_HEARTBEAT_INTERVAL_SECS, policy).await;
+
+        // Queue a backlog and make the next once-a-second sample due in the same instant.
+        for n in 0..100 {
+            let _ = state.connection_manager.broadcast_sender.send(message(n));
+        }
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert_eq!(client.close_frame().await, (close_code::POLICY, "client too slow".to_string()));
+    }
+
+    #[tokio::test]
+    async fn shutdown_closes_connections_with_going_away() {
+        let state = AppState::for_tests().await;
+        let mut client = connect(&state, DEFAULT_HEARTBEAT_INTERVAL_SECS, DeliveryPolicy::from_env()).await;
+
+        state.connection_manager.shutdown.cancel();
+        assert_eq!(client.close_frame().await, (close_code::AWAY, "server shutting down".to_string()));
+    }
+
+    #[tokio::test]
+    async fn admin_broadcast_reaches_clients_subscribed_elsewhere() {
+        let state = AppState::for_tests().await;
+        let mut client = connect(&state, DEFAULT_HEARTBEAT_INTERVAL_SECS, DeliveryPolicy::from_env()).await;
+        client.subscribe("job:other").await;
+
+        // Publishing to Redis fails without a server; local clients get it regardless.
+        state.connection_manager.broadcast("maintenance at 5pm").await;
+
+        let relayed = client.next_text().await;
+        assert_eq!(relayed["channel"], redis_service::LOCAL_BROADCAST_CHANNEL);
+        assert_eq!(relayed["data"], "maintenance at 5pm");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn firehose_subscriber_is_rate_limited() {
+        let state = AppState::for_tests().await;
+        let policy = DeliveryPolicy {
+            slow_detector: SlowClientDetector::from_env(),
+            firehose_limiter: FirehoseLimiter::new(2, Instant::now()),
+        };
+        let mut client = connect_as(&state, true, DEFAULT_HEARTBEAT_INTERVAL_SECS, policy).await;
+        client.subscribe("*").await;
+
+        for n in 0..5 {
+            state.connection_manager.broadcast_sender.send(message(n)).unwrap();
+        }
+
+        assert_eq!(client.next_text().await["data"], "0");
+        assert_eq!(client.next_text().await["data"], "1");
+        // The rest are dropped and reported at the next once-a-second sample.
+        assert_eq!(client.next_text().await, serde_json::json!({ "type": "RATE_LIMITED", "dropped": 3 }));
+    }
+
+    /// Serves `/ws` on a local port, as the real router does.
+    async fn serve_websocket(state: AppState) -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = axum::Router::new()
+            .route("/ws", axum::routing::get(websocket_handler))
+            .with_state(state);
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        addr
+    }
+
+    /// Sends a WebSocket upgrade request for `target` and returns the response head.
+    async fn upgrade(addr: SocketAddr, target: &str, protocol: &str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n\
+             Sec-WebSocket-Version: 13\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+             Sec-WebSocket-Protocol: {}\r\n\r\n",
+            target, protocol
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            let mut byte = [0; 1];
+            assert_eq!(stream.read(&mut byte).await.unwrap(), 1, "connection closed mid-response");
+            head.push(byte[0]);
+        }
+        String::from_utf8(head).unwrap().to_lowercase()
+    }
+
+    #[tokio::test]
+    async fn upgrade_echoes_the_negotiated_subprotocol() {
+        let addr = serve_websocket(AppState::for_tests().await).await;
+
+        let head = upgrade(addr, "/ws", "legacy, thinknet.v1").await;
+        assert!(head.starts_with("http/1.1 101"), "{}", head);
+        assert!(head.contains("sec-websocket-protocol: thinknet.v1\r\n"), "{}", head);
+    }
+
+    #[tokio::test]
+    async fn rejected_upgrade_does_not_consume_the_ticket() {
+        let state = AppState::for_tests().await;
+        let ticket = state.ticket_store.issue(false).await;
+        let addr = serve_websocket(state).await;
+
+        let bad_heartbeat = upgrade(addr, &format!("/ws?ticket={}&heartbeat_interval=soon", ticket), "thinknet.v1").await;
+        assert!(bad_heartbeat.starts_with("http/1.1 400"), "{}", bad_heartbeat);
+        let bad_protocol = upgrade(addr, &format!("/ws?ticket={}", ticket), "unknown.v9").await;
+        assert!(bad_protocol.starts_with("http/1.1 400"), "{}", bad_protocol);
+
+        let accepted = upgrade(addr, &format!("/ws?ticket={}", ticket), "thinknet.v1").await;
+        assert!(accepted.starts_with("http/1.1 101"), "{}", accepted);
+        let reused = upgrade(addr, &format!("/ws?ticket={}", ticket), "thinknet.v1").await;
+        assert!(reused.starts_with("http/1.1 401"), "{}", reused);
+    }
+
+    #[tokio::test]
+    async fn firehose_subscriber_does_not_see_roundtrip_probes() {
+        let state = AppState::for_tests().await;
+        let mut client = connect_as(&state, true, DEFAULT_HEARTBEAT_INTERVAL_SECS, DeliveryPolicy::from_env()).await;
+        client.subscribe("*").await;
+
+        let probe = RedisMessage::new(redis_service::roundtrip_channel("tag"), "tag");
+        state.connection_manager.broadcast_sender.send(probe).unwrap();
+        state.connection_manager.broadcast_sender.send(message(1)).unwrap();
+
+        assert_eq!(client.next_text().await["channel"], "ws_channel:job:1");
+    }
 }
@@ -0,0 +1,105 @@
+// File Path: backend/src/services/backup_listing.rs
+
+//! # Backup Listing
+//!
+//! Builds the typed file listing carried by `BackupResponse` from a backup
+//! directory laid out as `<root>/<device>/<files...>`.
+
+use std::{env, path::{Path, PathBuf}};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+
+use crate::models::{ApiResult, BackupFileEntry};
+
+// Where the worker writes device backups (the `./shared/data` volume in docker-compose).
+const DEFAULT_BACKUP_DIR: &str = "/app/shared/data/backups";
+
+/// The backup root, from `BACKUP_DIR` (default `/app/shared/data/backups`).
+pub fn backup_dir() -> PathBuf {
+    env::var("BACKUP_DIR")
+        .ok()
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_BACKUP_DIR))
+}
+
+/// Scans `root` and returns one entry per backup file, sorted by path.
+///
+/// The first directory below `root` names the device. Files directly in `root`
+/// are not attributed to any device and are skipped.
+pub async fn scan_backup_dir(root: &Path) -> ApiResult<Vec<BackupFileEntry>> {
+    let mut entries = Vec::new();
+    let mut pending: Vec<PathBuf> = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let mut read_dir = fs::read_dir(&dir).await?;
+        while let Some(entry) = read_dir.next_entry().await? {
+            let path = entry.path();
+            if entry.file_type().await?.is_dir() {
+                pending.push(path);
+                continue;
+            }
+
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            let mut components = relative.components();
+            let device = components.next().and_then(|c| c.as_os_str().to_str());
+            let (Some(device), Some(_)) = (device, components.next()) else {
+                continue;
+            };
+
+            let contents = fs::read(&path).await?;
+            entries.push(BackupFileEntry {
+                device: device.to_string(),
+                path: relative.to_string_lossy().replace('\\', "/"),
+                size_bytes: contents.len() as u64,
+                sha256: format!("{:x}", Sha256::digest(&contents)),
+            });
+        }
+    }
+
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{BackupResponse, BACKUP_LISTING_COMPRESS_THRESHOLD};
+
+    #[tokio::test]
+    async fn scan_attributes_files_to_devices() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("r1")).unwrap();
+        std::fs::write(root.path().join("r1/r1.conf"), "abc").unwrap();
+        std::fs::write(root.path().join("stray.txt"), "x").unwrap();
+
+        let files = scan_backup_dir(root.path()).await.unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].device, "r1");
+        assert_eq!(files[0].path, "r1/r1.conf");
+        assert_eq!(files[0].size_bytes, 3);
+        assert_eq!(
+            files[0].sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn large_listing_is_compressed_and_round_trips() {
+        let files: Vec<BackupFileEntry> = (0..=BACKUP_LISTING_COMPRESS_THRESHOLD)
+            .map(|i| BackupFileEntry {
+                device: "r1".to_string(),
+                path: format!("r1/{}.conf", i),
+                size_bytes: i as u64,
+                sha256: String::new(),
+            })
+            .collect();
+
+        let response = BackupResponse::new("success", "done", None, files.clone()).unwrap();
+        assert!(response.files.is_empty());
+        assert_eq!(response.file_count, files.len());
+        assert_eq!(response.file_entries().unwrap(), files);
+    }
+}
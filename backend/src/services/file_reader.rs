@@ -0,0 +1,240 @@
+// File Path: backend/src/services/file_reader.rs
+
+//! Data file reads with retries and a per-file circuit breaker.
+//!
+//! On network filesystems (e.g. an NFS-mounted `data_dir`) reads occasionally fail
+//! transiently. `ResilientReader` retries those with a short backoff and, after repeated
+//! failures of the same file, stops touching it for a cooldown instead of hammering a
+//! sick mount. Reads go through a `FileStore`, so tests can inject failures.
+
+use std::{
+    collections::HashMap,
+    env, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use metrics::counter;
+use tracing::warn;
+
+use crate::services::file_store::FileStore;
+
+/// Default retries after the first failed attempt (`FILE_READ_RETRIES`).
+const DEFAULT_RETRIES: u32 = 2;
+
+/// Default delay before the first retry, doubled for each further one (`FILE_READ_BACKOFF_MS`).
+const DEFAULT_BACKOFF: Duration = Duration::from_millis(50);
+
+/// Default consecutive failed reads of one file that open its breaker (`FILE_BREAKER_THRESHOLD`).
+const DEFAULT_BREAKER_THRESHOLD: u32 = 5;
+
+/// Default time an open breaker rejects reads before one is tried again (`FILE_BREAKER_COOLDOWN_SECS`).
+const DEFAULT_BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Failure tracking for one file.
+#[derive(Default)]
+struct Breaker {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// Wraps a `FileStore`'s reads with retries on transient errors and a per-file circuit breaker.
+pub struct ResilientReader {
+    inner: Arc<dyn FileStore>,
+    retries: u32,
+    backoff: Duration,
+    breaker_threshold: u32,
+    breaker_cooldown: Duration,
+    breakers: Mutex<HashMap<PathBuf, Breaker>>,
+}
+
+impl ResilientReader {
+    pub fn new(
+        inner: Arc<dyn FileStore>,
+        retries: u32,
+        backoff: Duration,
+        breaker_threshold: u32,
+        breaker_cooldown: Duration,
+    ) -> Self {
+        Self {
+            inner,
+            retries,
+            backoff,
+            breaker_threshold: breaker_threshold.max(1),
+            breaker_cooldown,
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reader over `store` configured from `FILE_READ_RETRIES`, `FILE_READ_BACKOFF_MS`,
+    /// `FILE_BREAKER_THRESHOLD` and `FILE_BREAKER_COOLDOWN_SECS`.
+    pub fn from_env(store: Arc<dyn FileStore>) -> Self {
+        let var = |name: &str| env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        Self::new(
+            store,
+            var("FILE_READ_RETRIES").map(|n| n as u32).unwrap_or(DEFAULT_RETRIES),
+            var("FILE_READ_BACKOFF_MS").map(Duration::from_millis).unwrap_or(DEFAULT_BACKOFF),
+            var("FILE_BREAKER_THRESHOLD").map(|n| n as u32).unwrap_or(DEFAULT_BREAKER_THRESHOLD),
+            var("FILE_BREAKER_COOLDOWN_SECS").map(Duration::from_secs).unwrap_or(DEFAULT_BREAKER_COOLDOWN),
+        )
+    }
+
+    /// Reads `path`, retrying transient errors. Permanent errors (missing file, no
+    /// permission, invalid UTF-8) are returned at once and don't count against the breaker.
+    /// While the file's breaker is open the read fails without touching the filesystem.
+    pub async fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        if self.is_open(path) {
+            counter!("file_read_breaker_rejections_total").increment(1);
+            return Err(io::Error::other(format!(
+                "Circuit breaker open for {} after repeated read failures",
+                path.display()
+            )));
+        }
+
+        let mut attempt = 0;
+        loop {
+            match self.inner.read_to_string(path).await {
+                Ok(content) => {
+                    self.record(path, true);
+                    return Ok(content);
+                }
+                Err(e) if !is_transient(&e) => return Err(e),
+                Err(e) if attempt >= self.retries => {
+                    self.record(path, false);
+                    return Err(e);
+                }
+                Err(e) => {
+                    counter!("file_read_retries_total").increment(1);
+                    warn!("Transient error reading {} (attempt {}): {}", path.display(), attempt + 1, e);
+                    tokio::time::sleep(self.backoff * 2u32.saturating_pow(attempt)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    fn is_open(&self, path: &Path) -> bool {
+        let breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        breakers
+            .get(path)
+            .and_then(|breaker| breaker.open_until)
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Resets the breaker on success; on failure opens it once the threshold is reached
+    /// (a failed trial read after the cooldown re-opens it straight away).
+    fn record(&self, path: &Path, success: bool) {
+        let mut breakers = self.breakers.lock().unwrap_or_else(|e| e.into_inner());
+        if success {
+            breakers.remove(path);
+            return;
+        }
+
+        let breaker = breakers.entry(path.to_path_buf()).or_default();
+        breaker.consecutive_failures += 1;
+        if breaker.consecutive_failures >= self.breaker_threshold {
+            warn!(
+                "Opening circuit breaker for {} for {:?} after {} failed reads",
+                path.display(),
+                self.breaker_cooldown,
+                breaker.consecutive_failures
+            );
+            breaker.open_until = Some(Instant::now() + self.breaker_cooldown);
+        }
+    }
+}
+
+/// Errors worth retrying: anything but a missing file, denied access or bad content.
+fn is_transient(error: &io::Error) -> bool {
+    !matches!(
+        error.kind(),
+        io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied | io::ErrorKind::InvalidData
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::file_store::FileMetadata;
+    use futures::future::BoxFuture;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Fails the first `failures` reads with `kind`, then returns "ok".
+    struct FlakyReader {
+        failures: u32,
+        kind: io::ErrorKind,
+        calls: AtomicU32,
+    }
+
+    impl FlakyReader {
+        fn new(failures: u32, kind: io::ErrorKind) -> Arc<Self> {
+            Arc::new(Self { failures, kind, calls: AtomicU32::new(0) })
+        }
+    }
+
+    impl FileStore for FlakyReader {
+        fn read<'a>(&'a self, _path: &'a Path) -> BoxFuture<'a, io::Result<Vec<u8>>> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let result = if call < self.failures {
+                Err(io::Error::new(self.kind, "injected failure"))
+            } else {
+                Ok(b"ok".to_vec())
+            };
+            Box::pin(async move { result })
+        }
+
+        fn list<'a>(&'a self, _dir: &'a Path) -> BoxFuture<'a, io::Result<Vec<PathBuf>>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+
+        fn write<'a>(&'a self, _path: &'a Path, _bytes: &'a [u8]) -> BoxFuture<'a, io::Result<()>> {
+            Box::pin(async { Err(io::Error::from(io::ErrorKind::Unsupported)) })
+        }
+
+        fn metadata<'a>(&'a self, _path: &'a Path) -> BoxFuture<'a, io::Result<FileMetadata>> {
+            Box::pin(async { Ok(FileMetadata { len: 2, is_dir: false, is_symlink: false, modified: None }) })
+        }
+    }
+
+    fn reader(inner: Arc<FlakyReader>, retries: u32, threshold: u32) -> ResilientReader {
+        ResilientReader::new(inner, retries, Duration::ZERO, threshold, Duration::from_secs(60))
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_retried() {
+        let inner = FlakyReader::new(2, io::ErrorKind::TimedOut);
+        let reader = reader(inner.clone(), 2, 5);
+
+        assert_eq!(reader.read_to_string(Path::new("nav.yaml")).await.unwrap(), "ok");
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn not_found_is_not_retried() {
+        let inner = FlakyReader::new(1, io::ErrorKind::NotFound);
+        let reader = reader(inner.clone(), 3, 5);
+
+        let err = reader.read_to_string(Path::new("missing.yaml")).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn breaker_opens_per_file_after_repeated_failures() {
+        let inner = FlakyReader::new(u32::MAX, io::ErrorKind::TimedOut);
+        let reader = reader(inner.clone(), 0, 2);
+        let sick = Path::new("sick.yaml");
+
+        assert!(reader.read_to_string(sick).await.is_err());
+        assert!(reader.read_to_string(sick).await.is_err());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+
+        // Open: rejected without reaching the underlying reader.
+        assert!(reader.read_to_string(sick).await.is_err());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 2);
+
+        // Other files are unaffected.
+        assert!(reader.read_to_string(Path::new("other.yaml")).await.is_err());
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,194 @@
+// File Path: backend/src/services/file_store.rs
+
+//! Storage backend for schema and data files.
+//!
+//! `YamlService` reaches files only through the `FileStore` trait, so it can run
+//! against the local filesystem (`LocalFileStore`, the default), an in-memory map
+//! (`InMemoryFileStore`, test builds only) or, later, a remote object store.
+
+use std::{io, path::{Path, PathBuf}, time::SystemTime};
+#[cfg(test)]
+use std::{collections::BTreeMap, sync::Mutex};
+use futures::future::BoxFuture;
+
+/// The parts of a file's metadata the services care about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileMetadata {
+    pub len: u64,
+    pub is_dir: bool,
+    /// The path is a symbolic link; only ever set by `symlink_metadata`.
+    pub is_symlink: bool,
+    /// Last modification time, when the backend tracks one.
+    pub modified: Option<SystemTime>,
+}
+
+/// Minimal file access used by `YamlService`.
+pub trait FileStore: Send + Sync {
+    /// Reads a whole file.
+    fn read<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, io::Result<Vec<u8>>>;
+
+    /// Lists the direct entries (files and directories) of `dir`.
+    fn list<'a>(&'a self, dir: &'a Path) -> BoxFuture<'a, io::Result<Vec<PathBuf>>>;
+
+    /// Creates or replaces a file.
+    fn write<'a>(&'a self, path: &'a Path, bytes: &'a [u8]) -> BoxFuture<'a, io::Result<()>>;
+
+    fn metadata<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, io::Result<FileMetadata>>;
+
+    /// Like `metadata`, but describes a symbolic link itself instead of its target.
+    /// Backends without links can keep the default.
+    fn symlink_metadata<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, io::Result<FileMetadata>> {
+        self.metadata(path)
+    }
+
+    /// Reads a whole file as UTF-8; invalid UTF-8 is `io::ErrorKind::InvalidData`.
+    fn read_to_string<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, io::Result<String>> {
+        Box::pin(async move {
+            let bytes = self.read(path).await?;
+            String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        })
+    }
+
+    fn exists<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, bool> {
+        Box::pin(async move { self.metadata(path).await.is_ok() })
+    }
+}
+
+/// The local filesystem, via `tokio::fs`.
+pub struct LocalFileStore;
+
+impl FileStore for LocalFileStore {
+    fn read<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, io::Result<Vec<u8>>> {
+        Box::pin(tokio::fs::read(path))
+    }
+
+    fn list<'a>(&'a self, dir: &'a Path) -> BoxFuture<'a, io::Result<Vec<PathBuf>>> {
+        Box::pin(async move {
+            let mut entries = tokio::fs::read_dir(dir).await?;
+            let mut paths = Vec::new();
+            while let Some(entry) = entries.next_entry().await? {
+                paths.push(entry.path());
+            }
+            Ok(paths)
+        })
+    }
+
+    fn write<'a>(&'a self, path: &'a Path, bytes: &'a [u8]) -> BoxFuture<'a, io::Result<()>> {
+        Box::pin(tokio::fs::write(path, bytes))
+    }
+
+    fn metadata<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, io::Result<FileMetadata>> {
+        Box::pin(async move { tokio::fs::metadata(path).await.map(local_metadata) })
+    }
+
+    fn symlink_metadata<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, io::Result<FileMetadata>> {
+        Box::pin(async move { tokio::fs::symlink_metadata(path).await.map(local_metadata) })
+    }
+}
+
+fn local_metadata(metadata: std::fs::Metadata) -> FileMetadata {
+    FileMetadata {
+        len: metadata.len(),
+        is_dir: metadata.is_dir(),
+        is_symlink: metadata.is_symlink(),
+        modified: metadata.modified().ok(),
+    }
+}
+
+/// Files kept in memory, keyed by path. Directories exist implicitly while they
+/// contain at least one file.
+#[cfg(test)]
+#[derive(Default)]
+pub struct InMemoryFileStore {
+    files: Mutex<BTreeMap<PathBuf, Vec<u8>>>,
+}
+
+#[cfg(test)]
+impl InMemoryFileStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds or replaces a file.
+    pub fn insert(&self, path: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) {
+        self.files.lock().unwrap_or_else(|e| e.into_inner()).insert(path.into(), contents.into());
+    }
+
+    fn not_found(path: &Path) -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, format!("{} not found", path.display()))
+    }
+}
+
+#[cfg(test)]
+impl FileStore for InMemoryFileStore {
+    fn read<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, io::Result<Vec<u8>>> {
+        let result = self
+            .files
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(path)
+            .cloned()
+            .ok_or_else(|| Self::not_found(path));
+        Box::pin(async move { result })
+    }
+
+    fn list<'a>(&'a self, dir: &'a Path) -> BoxFuture<'a, io::Result<Vec<PathBuf>>> {
+        let files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        let mut entries: Vec<PathBuf> = files
+            .keys()
+            .filter_map(|path| path.strip_prefix(dir).ok())
+            .filter_map(|relative| relative.components().next())
+            .map(|first| dir.join(first))
+            .collect();
+        entries.dedup();
+        let result = if entries.is_empty() { Err(Self::not_found(dir)) } else { Ok(entries) };
+        Box::pin(async move { result })
+    }
+
+    fn write<'a>(&'a self, path: &'a Path, bytes: &'a [u8]) -> BoxFuture<'a, io::Result<()>> {
+        self.insert(path, bytes);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn metadata<'a>(&'a self, path: &'a Path) -> BoxFuture<'a, io::Result<FileMetadata>> {
+        let files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        let result = match files.get(path) {
+            Some(contents) => Ok(FileMetadata { len: contents.len() as u64, is_dir: false, is_symlink: false, modified: None }),
+            None if files.keys().any(|file| file.starts_with(path)) => {
+                Ok(FileMetadata { len: 0, is_dir: true, is_symlink: false, modified: None })
+            }
+            None => Err(Self::not_found(path)),
+        };
+        Box::pin(async move { result })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn in_memory_store_lists_files_and_implicit_directories() {
+        let store = InMemoryFileStore::new();
+        store.insert("/data/navigation.yaml", "- id: home\n");
+        store.insert("/data/network/bgp.yaml", "asn: 65001\n");
+        store.insert("/data/network/ospf.yaml", "area: 0\n");
+
+        assert_eq!(
+            store.list(Path::new("/data")).await.unwrap(),
+            vec![PathBuf::from("/data/navigation.yaml"), PathBuf::from("/data/network")]
+        );
+        assert_eq!(store.list(Path::new("/data/network")).await.unwrap().len(), 2);
+
+        assert!(store.metadata(Path::new("/data/network")).await.unwrap().is_dir);
+        assert_eq!(
+            store.metadata(Path::new("/data/navigation.yaml")).await.unwrap(),
+            FileMetadata { len: 11, is_dir: false, is_symlink: false, modified: None }
+        );
+        assert_eq!(store.read_to_string(Path::new("/data/network/ospf.yaml")).await.unwrap(), "area: 0\n");
+
+        let missing = store.read(Path::new("/data/missing.yaml")).await.unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+        assert!(!store.exists(Path::new("/data/missing.yaml")).await);
+    }
+}
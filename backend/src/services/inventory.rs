@@ -0,0 +1,149 @@
+// File Path: backend/src/services/inventory.rs
+
+//! # Device Inventories
+//!
+//! Resolves the inventory file named by `BackupRequest.inventory_file` under the
+//! inventory directory (`INVENTORY_DIR`, relative to `data_dir`, default `inventories`),
+//! validates it against the `inventory` schema and expands it into target devices.
+//!
+//! Two layouts are accepted, matching the files in `shared/data/inventories`:
+//! `{locations: {<LOCATION>: {<group>: [devices]}}}` and
+//! `[{location: <LOCATION>, <group>: [devices]}]`.
+
+use std::env;
+use serde_json::{Map, Value};
+
+use crate::{
+    models::{ApiError, ApiResult, InventoryDevice},
+    services::yaml_service::YamlService,
+};
+
+/// Schema every inventory file is validated against.
+pub const INVENTORY_SCHEMA_NAME: &str = "inventory";
+
+/// Default inventory directory, relative to `data_dir`.
+const DEFAULT_INVENTORY_DIR: &str = "inventories";
+
+/// Reads the inventory directory (relative to `data_dir`) from `INVENTORY_DIR`.
+pub fn inventory_dir() -> String {
+    env::var("INVENTORY_DIR")
+        .ok()
+        .map(|dir| dir.trim().trim_matches('/').to_string())
+        .filter(|dir| !dir.is_empty())
+        .unwrap_or_else(|| DEFAULT_INVENTORY_DIR.to_string())
+}
+
+/// Loads `file` from the inventory directory, validates it and returns its devices.
+///
+/// A missing file is `FileNotFound`; a path escaping the data directories is
+/// `BadRequest`. Schema violations are always rejected (`ValidationError`), whatever
+/// `VALIDATION_MODE` says, since a half-valid inventory would back up the wrong devices.
+pub async fn load_inventory(yaml_service: &YamlService, file: &str) -> ApiResult<Vec<InventoryDevice>> {
+    let path = format!("{}/{}", inventory_dir(), file.trim_start_matches('/'));
+    let data = yaml_service.get_unvalidated_yaml_data(INVENTORY_SCHEMA_NAME, Some(&path)).await?;
+    yaml_service.validate_value(INVENTORY_SCHEMA_NAME, &data)?;
+
+    let devices = expand_inventory(&data)?;
+    if devices.is_empty() {
+        return Err(ApiError::BadRequest(format!("Inventory '{}' lists no devices", file)));
+    }
+    Ok(devices)
+}
+
+/// Flattens an inventory document into its devices, in file order.
+pub fn expand_inventory(data: &Value) -> ApiResult<Vec<InventoryDevice>> {
+    let mut devices = Vec::new();
+    match data {
+        Value::Object(root) => {
+            let locations = root.get("locations").and_then(Value::as_object).ok_or_else(|| {
+                ApiError::BadRequest("Inventory object must contain a 'locations' map".to_string())
+            })?;
+            for (location, groups) in locations {
+                if let Some(groups) = groups.as_object() {
+                    expand_groups(Some(location), groups, &mut devices)?;
+                }
+            }
+        }
+        Value::Array(entries) => {
+            for groups in entries.iter().filter_map(Value::as_object) {
+                let location = groups.get("location").and_then(Value::as_str);
+                expand_groups(location, groups, &mut devices)?;
+            }
+        }
+        _ => return Err(ApiError::BadRequest("Inventory must be a map or a list".to_string())),
+    }
+    Ok(devices)
+}
+
+/// Appends the devices of every group (`routers`, `switches`, ...) of one location.
+fn expand_groups(
+    location: Option<&str>,
+    groups: &Map<String, Value>,
+    devices: &mut Vec<InventoryDevice>,
+) -> ApiResult<()> {
+    for (role, members) in groups {
+        let Some(members) = members.as_array() else {
+            continue;
+        };
+        for member in members {
+            let mut device: InventoryDevice = serde_json::from_value(member.clone()).map_err(|e| {
+                ApiError::DeserializationError(format!("Invalid inventory device in '{}': {}", role, e))
+            })?;
+            device.location = location.map(str::to_string);
+            device.role = Some(role.clone());
+            devices.push(device);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::services::file_store::InMemoryFileStore;
+
+    const INVENTORY_SCHEMA: &str = include_str!("../../../shared/schemas/inventory.schema.json");
+
+    #[test]
+    fn both_inventory_layouts_expand_to_devices() {
+        let by_location = serde_json::json!({
+            "locations": {
+                "BASEMENT": {
+                    "routers": [{ "host_name": "r1", "ip_address": "10.0.0.1", "vendor": "JUNIPER" }],
+                    "switches": [{ "host_name": "s1", "ip_address": "10.0.0.2" }]
+                }
+            }
+        });
+        let listed = serde_json::json!([
+            { "location": "BASEMENT", "routers": [{ "host_name": "r1", "ip_address": "10.0.0.1", "vendor": "JUNIPER" }] }
+        ]);
+
+        let devices = expand_inventory(&by_location).unwrap();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].host_name, "r1");
+        assert_eq!(devices[0].location.as_deref(), Some("BASEMENT"));
+        assert_eq!(devices[0].role.as_deref(), Some("routers"));
+        assert_eq!(devices[1].role.as_deref(), Some("switches"));
+
+        assert_eq!(expand_inventory(&listed).unwrap(), devices[..1].to_vec());
+    }
+
+    #[tokio::test]
+    async fn missing_and_invalid_inventories_are_rejected() {
+        let store = Arc::new(InMemoryFileStore::new());
+        store.insert("/mem/schemas/inventory.schema.json", INVENTORY_SCHEMA);
+        store.insert("/mem/data/inventories/lab.yaml", "- location: LAB\n  routers:\n    - host_name: r1\n      ip_address: 10.0.0.1\n");
+        store.insert("/mem/data/inventories/broken.yaml", "- location: LAB\n  routers:\n    - host_name: r1\n");
+        let service = YamlService::new_with_store("/mem/schemas", "/mem/data", None, store)
+            .await
+            .expect("service should initialize");
+
+        let devices = load_inventory(&service, "lab.yaml").await.unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].ip_address, "10.0.0.1");
+
+        assert!(matches!(load_inventory(&service, "missing.yaml").await, Err(ApiError::FileNotFound(_))));
+        assert!(matches!(load_inventory(&service, "broken.yaml").await, Err(ApiError::ValidationError(_))));
+    }
+}
@@ -0,0 +1,170 @@
+// File Path: backend/src/services/job_monitor.rs
+
+//! # Job Monitor (Stalled Job Watchdog)
+//!
+//! Tracks the last time a message was seen on each active job channel. If a job's
+//! publisher goes silent for longer than the staleness timeout (e.g. the Python
+//! orchestrator crashed), a synthetic `JobEvent` with `status: "stalled"` is pushed
+//! to that channel's subscribers so the UI can stop waiting.
+
+use std::{
+    collections::HashMap,
+    env,
+    time::{Duration, Instant},
+};
+use serde_json::Value;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::{
+    models::JobEvent,
+    services::{
+        redis_service::{self, ClientCount, RedisMessage},
+        task_supervisor::{Heartbeat, HEARTBEAT_INTERVAL},
+    },
+};
+
+/// Default number of seconds without a message before a job is considered stalled.
+const DEFAULT_STALE_TIMEOUT_SECS: u64 = 120;
+
+/// Statuses after which a job channel is no longer tracked.
+const TERMINAL_STATUSES: [&str; 3] = ["completed", "failed", "stalled"];
+
+/// Per-channel tracking information.
+struct TrackedJob {
+    last_seen: Instant,
+    job_id: String,
+    device: String,
+    job_type: String,
+}
+
+/// Reads the staleness timeout from `JOB_STALE_TIMEOUT_SECS`. A value of `0` disables the watchdog.
+pub fn stale_timeout_from_env() -> Option<Duration> {
+    let secs = env::var("JOB_STALE_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_STALE_TIMEOUT_SECS);
+
+    (secs > 0).then_some(Duration::from_secs(secs))
+}
+
+/// Runs the watchdog loop until `cancel` is triggered.
+///
+/// Listens on the global broadcast channel (the same one WebSocket workers use) and
+/// re-publishes synthetic "stalled" events onto it for silent jobs.
+pub async fn start_job_watchdog(
+    ws_tx: broadcast::Sender<RedisMessage>,
+    clients: ClientCount,
+    stale_timeout: Duration,
+    cancel: CancellationToken,
+    heartbeat: Heartbeat,
+) {
+    let mut rx = ws_tx.subscribe();
+    let mut heartbeat_ticker = tokio::time::interval(HEARTBEAT_INTERVAL);
+    let mut tracked: HashMap<String, TrackedJob> = HashMap::new();
+    // Check a few times per timeout window so detection latency stays bounded.
+    let mut ticker = tokio::time::interval((stale_timeout / 4).max(Duration::from_secs(1)));
+
+    info!("Job watchdog started (stale timeout: {:?})", stale_timeout);
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+
+            _ = heartbeat_ticker.tick() => heartbeat.beat(),
+
+            received = rx.recv() => match received {
+                Ok(msg) => record_message(&mut tracked, &msg),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Job watchdog lagged, skipped {} messages", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+
+            _ = ticker.tick() => {
+                let now = Instant::now();
+                let stalled: Vec<String> = tracked
+                    .iter()
+                    .filter(|(_, job)| now.duration_since(job.last_seen) >= stale_timeout)
+                    .map(|(channel, _)| channel.clone())
+                    .collect();
+
+                for channel in stalled {
+                    if let Some(job) = tracked.remove(&channel) {
+                        emit_stalled_event(&ws_tx, &clients, &channel, &job, stale_timeout);
+                    }
+                }
+            }
+        }
+    }
+
+    info!("Job watchdog stopped.");
+}
+
+/// Resets the timer for the message's channel, or stops tracking on a terminal status.
+fn record_message(tracked: &mut HashMap<String, TrackedJob>, msg: &RedisMessage) {
+    let Some(channel_job_id) = redis_service::parse_job_channel(&msg.channel) else {
+        return;
+    };
+
+    let payload: Value = serde_json::from_str(&msg.data).unwrap_or(Value::Null);
+    let field = |name: &str| payload.get(name).and_then(Value::as_str).map(str::to_string);
+
+    if let Some(status) = field("status") {
+        if TERMINAL_STATUSES.contains(&status.as_str()) {
+            tracked.remove(&msg.channel);
+            return;
+        }
+    }
+
+    let job_id = field("job_id")
+        .unwrap_or_else(|| channel_job_id.to_string());
+
+    let entry = tracked.entry(msg.channel.clone()).or_insert_with(|| TrackedJob {
+        last_seen: Instant::now(),
+        job_id,
+        device: String::new(),
+        job_type: String::new(),
+    });
+    entry.last_seen = Instant::now();
+    if let Some(device) = field("device") {
+        entry.device = device;
+    }
+    if let Some(job_type) = field("job_type") {
+        entry.job_type = job_type;
+    }
+}
+
+/// Pushes a synthetic "stalled" `JobEvent` to the channel's subscribers.
+fn emit_stalled_event(
+    ws_tx: &broadcast::Sender<RedisMessage>,
+    clients: &ClientCount,
+    channel: &str,
+    job: &TrackedJob,
+    stale_timeout: Duration,
+) {
+    let mut event = JobEvent::new(
+        &job.job_id,
+        &job.device,
+        &job.job_type,
+        "stalled",
+        "stalled",
+        serde_json::json!({ "stale_timeout_secs": stale_timeout.as_secs() }),
+    );
+    event.error = Some(format!(
+        "No updates received for {} seconds; the job publisher may have stopped",
+        stale_timeout.as_secs()
+    ));
+
+    let data = match serde_json::to_string(&event) {
+        Ok(d) => d,
+        Err(e) => {
+            warn!("Failed to serialize stalled event for {}: {}", channel, e);
+            return;
+        }
+    };
+
+    warn!("Job on channel {} marked as stalled", channel);
+    redis_service::broadcast_to_clients(ws_tx, clients, RedisMessage::new(channel, data));
+}
@@ -0,0 +1,515 @@
+// File Path: backend/src/services/job_store.rs
+
+//! # Job Event Store
+//!
+//! Keeps the events received on each `ws_channel:job:<id>` channel in memory so HTTP
+//! clients can query a job's state without holding a WebSocket open. Events are
+//! recorded by a background task that listens on the global broadcast channel.
+//!
+//! A job's buffer is dropped a grace period after its final (`completed`/`failed`)
+//! event, so late-joining clients can still fetch the final log but memory stays bounded.
+//! The number of jobs held is also capped (`MAX_TRACKED_CHANNELS`): a new job beyond the
+//! cap evicts the least recently active one, so a flood of channels can't grow it unbounded.
+
+use std::{
+    collections::HashMap,
+    env,
+    sync::{atomic::{AtomicUsize, Ordering}, Mutex},
+    time::{Duration, Instant},
+};
+use chrono::{DateTime, Utc};
+use metrics::{counter, gauge};
+use serde_json::Value;
+use tokio::sync::{broadcast::{self, error::RecvError}, RwLock};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::{
+    models::{DeviceSummary, JobStatus},
+    services::{
+        redis_service::{self, RedisMessage},
+        task_supervisor::{Heartbeat, HEARTBEAT_INTERVAL},
+    },
+};
+
+/// Maximum number of events retained per job; the oldest are dropped first.
+const MAX_EVENTS_PER_JOB: usize = 1000;
+
+/// Default seconds a finished job's events are kept (`JOB_EVENTS_GRACE_SECS`).
+const DEFAULT_COMPLETED_GRACE_SECS: u64 = 300;
+
+/// Default cap on jobs (job channels) held at once (`MAX_TRACKED_CHANNELS`).
+const DEFAULT_MAX_TRACKED_CHANNELS: usize = 10_000;
+
+/// How often the recorder drops the buffers of jobs whose grace period has passed.
+const PURGE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Statuses after which a job's buffer is scheduled for expiry.
+const TERMINAL_STATUSES: [&str; 2] = ["completed", "failed"];
+
+/// A single job event as received from Redis, with the time the hub saw it.
+#[derive(Debug, Clone)]
+pub struct StoredEvent {
+    pub received_at: DateTime<Utc>,
+    pub payload: Value,
+    /// Length of the raw message, used to approximate buffer memory.
+    size: usize,
+}
+
+impl StoredEvent {
+    /// The event's own RFC3339 `timestamp`, falling back to when the hub received it.
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        self.payload
+            .get("timestamp")
+            .and_then(Value::as_str)
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .map(|ts| ts.with_timezone(&Utc))
+            .unwrap_or(self.received_at)
+    }
+}
+
+/// In-memory store of job events, keyed by job id.
+pub struct JobEventStore {
+    events: RwLock<HashMap<String, Vec<StoredEvent>>>,
+    /// When each finished job's buffer is dropped.
+    expiries: Mutex<HashMap<String, Instant>>,
+    /// Approximate bytes held across all buffers (`job_event_buffer_bytes` gauge).
+    bytes: AtomicUsize,
+    completed_grace: Duration,
+    /// Most jobs held at once; the least recently active is evicted beyond it.
+    max_jobs: usize,
+}
+
+impl JobEventStore {
+    /// Creates an empty store keeping finished jobs for the default grace period.
+    pub fn new() -> Self {
+        Self::with_completed_grace(Duration::from_secs(DEFAULT_COMPLETED_GRACE_SECS))
+    }
+
+    /// Creates an empty store keeping finished jobs for `JOB_EVENTS_GRACE_SECS` (default 300)
+    /// and at most `MAX_TRACKED_CHANNELS` jobs (default 10000).
+    pub fn from_env() -> Self {
+        let secs = env::var("JOB_EVENTS_GRACE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_COMPLETED_GRACE_SECS);
+        Self::with_completed_grace(Duration::from_secs(secs)).with_max_jobs(max_tracked_channels())
+    }
+
+    /// Creates an empty store that drops a job's events `completed_grace` after its final event.
+    pub fn with_completed_grace(completed_grace: Duration) -> Self {
+        Self {
+            events: RwLock::new(HashMap::new()),
+            expiries: Mutex::new(HashMap::new()),
+            bytes: AtomicUsize::new(0),
+            completed_grace,
+            max_jobs: DEFAULT_MAX_TRACKED_CHANNELS,
+        }
+    }
+
+    /// Caps the number of jobs held at once (at least 1).
+    pub fn with_max_jobs(mut self, max_jobs: usize) -> Self {
+        self.max_jobs = max_jobs.max(1);
+        self
+    }
+
+    /// Approximate bytes of event data currently buffered.
+    pub fn buffered_bytes(&self) -> usize {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Drops the buffers of finished jobs whose grace period has passed; returns how many.
+    pub async fn purge_expired(&self) -> usize {
+        let now = Instant::now();
+        let expired: Vec<String> = {
+            let mut expiries = self.expiries.lock().unwrap_or_else(|e| e.into_inner());
+            let expired: Vec<String> = expiries
+                .iter()
+                .filter(|(_, at)| **at <= now)
+                .map(|(job_id, _)| job_id.clone())
+                .collect();
+            for job_id in &expired {
+                expiries.remove(job_id);
+            }
+            expired
+        };
+        if expired.is_empty() {
+            return 0;
+        }
+
+        let mut events = self.events.write().await;
+        for job_id in &expired {
+            if let Some(job_events) = events.remove(job_id) {
+                self.bytes.fetch_sub(job_events.iter().map(|e| e.size).sum(), Ordering::Relaxed);
+            }
+        }
+        gauge!("job_event_buffer_bytes").set(self.buffered_bytes() as f64);
+        info!("Dropped event buffers of {} finished job(s)", expired.len());
+        expired.len()
+    }
+
+    /// Records a message if it was published on a job channel.
+    pub async fn record(&self, msg: &RedisMessage) {
+        let Some(channel_job_id) = redis_service::parse_job_channel(&msg.channel) else {
+            return;
+        };
+
+        // Non-JSON payloads are kept as plain strings so nothing is lost.
+        let payload: Value = serde_json::from_str(&msg.data)
+            .unwrap_or_else(|_| Value::String(msg.data.clone()));
+        let job_id = payload
+            .get("job_id")
+            .and_then(Value::as_str)
+            .unwrap_or(channel_job_id)
+            .to_string();
+
+        let terminal = payload
+            .get("status")
+            .and_then(Value::as_str)
+            .is_some_and(|status| TERMINAL_STATUSES.contains(&status));
+
+        let size = msg.data.len();
+        let mut events = self.events.write().await;
+        if !events.contains_key(&job_id) && events.len() >= self.max_jobs {
+            self.evict_least_recent(&mut events);
+        }
+        let job_events = events.entry(job_id.clone()).or_default();
+        job_events.push(StoredEvent { received_at: Utc::now(), payload, size });
+        self.bytes.fetch_add(size, Ordering::Relaxed);
+        if job_events.len() > MAX_EVENTS_PER_JOB {
+            let overflow = job_events.len() - MAX_EVENTS_PER_JOB;
+            let dropped: usize = job_events.drain(..overflow).map(|e| e.size).sum();
+            self.bytes.fetch_sub(dropped, Ordering::Relaxed);
+        }
+        gauge!("job_event_buffer_bytes").set(self.buffered_bytes() as f64);
+
+        // A final event schedules the buffer's expiry; any later event (e.g. a retry) cancels it.
+        let mut expiries = self.expiries.lock().unwrap_or_else(|e| e.into_inner());
+        if terminal {
+            expiries.insert(job_id, Instant::now() + self.completed_grace);
+        } else {
+            expiries.remove(&job_id);
+        }
+    }
+
+    /// Drops the job whose latest event is oldest, to make room for a new one.
+    fn evict_least_recent(&self, events: &mut HashMap<String, Vec<StoredEvent>>) {
+        let oldest = events
+            .iter()
+            .min_by_key(|(_, job_events)| job_events.last().map(|e| e.received_at))
+            .map(|(job_id, _)| job_id.clone());
+        let Some(job_id) = oldest else {
+            return;
+        };
+
+        if let Some(job_events) = events.remove(&job_id) {
+            self.bytes.fetch_sub(job_events.iter().map(|e| e.size).sum(), Ordering::Relaxed);
+        }
+        self.expiries.lock().unwrap_or_else(|e| e.into_inner()).remove(&job_id);
+        counter!("tracked_channel_evictions_total", "store" => "job_events").increment(1);
+        warn!("Job store full ({} jobs); evicted least recently active job {}", self.max_jobs, job_id);
+    }
+
+    /// Computes the current aggregated status of a job, or `None` if it is unknown.
+    pub async fn job_status(&self, job_id: &str) -> Option<JobStatus> {
+        let events = self.events.read().await;
+        status_of(job_id, events.get(job_id)?)
+    }
+
+    /// Summarizes jobs per device, counting each job once by its current status.
+    /// With `since`, only jobs with an event after that time are counted.
+    /// Jobs without a `device` are left out. Sorted by device name.
+    pub async fn device_summaries(&self, since: Option<DateTime<Utc>>) -> Vec<DeviceSummary> {
+        let events = self.events.read().await;
+        let mut summaries: HashMap<String, DeviceSummary> = HashMap::new();
+
+        for (job_id, job_events) in events.iter() {
+            let Some(status) = status_of(job_id, job_events) else {
+                continue;
+            };
+            let Some(device) = status.device else {
+                continue;
+            };
+            if since.is_some_and(|since| status.last_event_at <= since) {
+                continue;
+            }
+
+            let summary = summaries.entry(device.clone()).or_insert_with(|| DeviceSummary {
+                device,
+                running: 0,
+                completed: 0,
+                failed: 0,
+                last_activity: status.last_event_at,
+            });
+            match status.status.as_str() {
+                "completed" => summary.completed += 1,
+                "failed" | "stalled" => summary.failed += 1,
+                _ => summary.running += 1,
+            }
+            summary.last_activity = summary.last_activity.max(status.last_event_at);
+        }
+
+        let mut summaries: Vec<DeviceSummary> = summaries.into_values().collect();
+        summaries.sort_by(|a, b| a.device.cmp(&b.device));
+        summaries
+    }
+}
+
+/// Reads the cap on jobs held at once from `MAX_TRACKED_CHANNELS` (default 10000).
+fn max_tracked_channels() -> usize {
+    env::var("MAX_TRACKED_CHANNELS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_MAX_TRACKED_CHANNELS)
+}
+
+/// Computes a job's aggregated status from its events (oldest first).
+fn status_of(job_id: &str, job_events: &[StoredEvent]) -> Option<JobStatus> {
+    let latest = job_events.last()?;
+
+    // Most recent non-null value of a string field across the job's events.
+    let latest_str = |field: &str| {
+        job_events
+            .iter()
+            .rev()
+            .find_map(|e| e.payload.get(field).and_then(Value::as_str))
+            .map(str::to_string)
+    };
+
+    Some(JobStatus {
+        job_id: job_id.to_string(),
+        device: latest_str("device"),
+        job_type: latest_str("job_type"),
+        status: latest_str("status").unwrap_or_else(|| "unknown".to_string()),
+        progress_percent: job_events.iter().rev().find_map(|e| progress_of(&e.payload)),
+        last_event_at: latest.timestamp(),
+        error: latest.payload.get("error").and_then(Value::as_str).map(str::to_string),
+    })
+}
+
+impl JobEventStore {
+    /// Returns a job's events with a timestamp strictly after `since` (all events when
+    /// `since` is `None`), ordered ascending. Returns `None` for unknown jobs.
+    pub async fn events_since(&self, job_id: &str, since: Option<DateTime<Utc>>) -> Option<Vec<Value>> {
+        let events = self.events.read().await;
+        let job_events = events.get(job_id)?;
+
+        let mut newer: Vec<&StoredEvent> = job_events
+            .iter()
+            .filter(|e| match since {
+                Some(since) => e.timestamp() > since,
+                None => true,
+            })
+            .collect();
+        // Stable sort keeps arrival order for events sharing a timestamp.
+        newer.sort_by_key(|e| e.timestamp());
+
+        Some(newer.into_iter().map(|e| e.payload.clone()).collect())
+    }
+
+    /// Returns the events the hub received at or after `since`, across the jobs accepted
+    /// by `include`, as `(job_id, payload)` in arrival order. Used to replay the recent
+    /// past to a new WebSocket subscriber.
+    pub async fn received_since(&self, since: DateTime<Utc>, include: impl Fn(&str) -> bool) -> Vec<(String, Value)> {
+        let events = self.events.read().await;
+        let mut recent: Vec<(&str, &StoredEvent)> = events
+            .iter()
+            .filter(|(job_id, _)| include(job_id))
+            .flat_map(|(job_id, job_events)| job_events.iter().map(move |e| (job_id.as_str(), e)))
+            .filter(|(_, e)| e.received_at >= since)
+            .collect();
+        recent.sort_by_key(|(_, e)| e.received_at);
+
+        recent.into_iter().map(|(job_id, e)| (job_id.to_string(), e.payload.clone())).collect()
+    }
+}
+
+impl Default for JobEventStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Extracts a progress percentage from an event's `data` (or top level), if present.
+fn progress_of(payload: &Value) -> Option<f64> {
+    const KEYS: [&str; 3] = ["progress_percent", "progress", "percent"];
+
+    for scope in [payload.get("data"), Some(payload)].into_iter().flatten() {
+        for key in KEYS {
+            if let Some(progress) = scope.get(key).and_then(Value::as_f64) {
+                return Some(progress.clamp(0.0, 100.0));
+            }
+        }
+    }
+    None
+}
+
+/// Records every broadcast job message into the store until `cancel` is triggered,
+/// dropping finished jobs' buffers once their grace period has passed.
+pub async fn start_event_recorder(
+    store: std::sync::Arc<JobEventStore>,
+    ws_tx: broadcast::Sender<RedisMessage>,
+    cancel: CancellationToken,
+    heartbeat: Heartbeat,
+) {
+    let mut rx = ws_tx.subscribe();
+    let mut heartbeat_ticker = tokio::time::interval(HEARTBEAT_INTERVAL);
+    let mut purge_ticker = tokio::time::interval(PURGE_INTERVAL);
+    info!("Job event recorder started.");
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = heartbeat_ticker.tick() => heartbeat.beat(),
+            _ = purge_ticker.tick() => {
+                store.purge_expired().await;
+            }
+            received = rx.recv() => match received {
+                Ok(msg) => store.record(&msg).await,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Job event recorder lagged, skipped {} messages", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+
+    info!("Job event recorder stopped.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job_message(job_id: &str, status: &str, timestamp: &str) -> RedisMessage {
+        RedisMessage::new(
+            redis_service::job_channel(job_id),
+            serde_json::json!({
+                "job_id": job_id,
+                "status": status,
+                "timestamp": timestamp,
+            })
+            .to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn exceeding_the_job_cap_evicts_the_least_recently_active_job() {
+        let store = JobEventStore::new().with_max_jobs(2);
+        store.record(&job_message("j1", "running", "2024-01-01T00:00:01Z")).await;
+        store.record(&job_message("j2", "running", "2024-01-01T00:00:02Z")).await;
+        // j1 becomes the most recently active.
+        store.record(&job_message("j1", "running", "2024-01-01T00:00:03Z")).await;
+
+        store.record(&job_message("j3", "running", "2024-01-01T00:00:04Z")).await;
+
+        assert!(store.job_status("j2").await.is_none());
+        assert!(store.job_status("j1").await.is_some());
+        assert!(store.job_status("j3").await.is_some());
+        assert_eq!(store.events_since("j1", None).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn events_since_filters_and_orders_ascending() {
+        let store = JobEventStore::new();
+        store.record(&job_message("j1", "running", "2024-01-01T00:00:02Z")).await;
+        store.record(&job_message("j1", "started", "2024-01-01T00:00:01Z")).await;
+        store.record(&job_message("j1", "completed", "2024-01-01T00:00:03Z")).await;
+
+        let since = DateTime::parse_from_rfc3339("2024-01-01T00:00:01Z").unwrap().with_timezone(&Utc);
+        let events = store.events_since("j1", Some(since)).await.unwrap();
+
+        let statuses: Vec<&str> = events.iter().map(|e| e["status"].as_str().unwrap()).collect();
+        assert_eq!(statuses, vec!["running", "completed"]);
+    }
+
+    #[tokio::test]
+    async fn received_since_returns_a_time_bounded_slice_in_arrival_order() {
+        let store = JobEventStore::new();
+        store.record(&job_message("j1", "started", "2024-01-01T00:00:01Z")).await;
+        store.record(&job_message("j1", "running", "2024-01-01T00:00:02Z")).await;
+        store.record(&job_message("j2", "running", "2024-01-01T00:00:03Z")).await;
+        store.record(&job_message("j1", "completed", "2024-01-01T00:00:04Z")).await;
+
+        // Pretend the events arrived 10s, 4s, 3s and 1s ago.
+        let now = Utc::now();
+        {
+            let mut events = store.events.write().await;
+            let j1 = events.get_mut("j1").unwrap();
+            j1[0].received_at = now - chrono::Duration::seconds(10);
+            j1[1].received_at = now - chrono::Duration::seconds(4);
+            j1[2].received_at = now - chrono::Duration::seconds(1);
+            events.get_mut("j2").unwrap()[0].received_at = now - chrono::Duration::seconds(3);
+        }
+
+        let since = now - chrono::Duration::seconds(5);
+        let replayed = store.received_since(since, |job_id| job_id == "j1").await;
+        let statuses: Vec<&str> = replayed.iter().map(|(_, e)| e["status"].as_str().unwrap()).collect();
+        assert_eq!(statuses, vec!["running", "completed"]);
+
+        let all = store.received_since(since, |_| true).await;
+        let jobs: Vec<&str> = all.iter().map(|(job_id, _)| job_id.as_str()).collect();
+        assert_eq!(jobs, vec!["j1", "j2", "j1"]);
+    }
+
+    #[tokio::test]
+    async fn device_summaries_count_jobs_by_current_status() {
+        let store = JobEventStore::new();
+        for (job_id, status, timestamp) in [
+            ("j1", "running", "2024-01-01T00:00:01Z"),
+            ("j1", "completed", "2024-01-01T00:00:02Z"),
+            ("j2", "running", "2024-01-01T00:00:03Z"),
+            ("j3", "failed", "2024-01-01T00:00:04Z"),
+        ] {
+            let mut msg = job_message(job_id, status, timestamp);
+            let mut payload: Value = serde_json::from_str(&msg.data).unwrap();
+            payload["device"] = Value::from("r1");
+            msg.data = payload.to_string();
+            store.record(&msg).await;
+        }
+
+        let all = store.device_summaries(None).await;
+        assert_eq!(all.len(), 1);
+        assert_eq!((all[0].running, all[0].completed, all[0].failed), (1, 1, 1));
+        assert_eq!(all[0].last_activity.to_rfc3339(), "2024-01-01T00:00:04+00:00");
+
+        let since = DateTime::parse_from_rfc3339("2024-01-01T00:00:02Z").unwrap().with_timezone(&Utc);
+        let recent = store.device_summaries(Some(since)).await;
+        assert_eq!((recent[0].running, recent[0].completed, recent[0].failed), (1, 0, 1));
+    }
+
+    #[tokio::test]
+    async fn finished_jobs_are_dropped_after_the_grace_period() {
+        let store = JobEventStore::with_completed_grace(Duration::ZERO);
+        let done = job_message("j1", "completed", "2024-01-01T00:00:02Z");
+        store.record(&job_message("j1", "running", "2024-01-01T00:00:01Z")).await;
+        store.record(&done).await;
+        store.record(&job_message("j2", "running", "2024-01-01T00:00:03Z")).await;
+        let j2_bytes = job_message("j2", "running", "2024-01-01T00:00:03Z").data.len();
+        assert!(store.buffered_bytes() > j2_bytes);
+
+        assert_eq!(store.purge_expired().await, 1);
+        assert!(store.events_since("j1", None).await.is_none());
+        assert_eq!(store.events_since("j2", None).await.map(|e| e.len()), Some(1));
+        assert_eq!(store.buffered_bytes(), j2_bytes);
+        assert_eq!(store.purge_expired().await, 0);
+    }
+
+    #[tokio::test]
+    async fn later_events_cancel_a_scheduled_expiry() {
+        let store = JobEventStore::with_completed_grace(Duration::ZERO);
+        store.record(&job_message("j1", "failed", "2024-01-01T00:00:01Z")).await;
+        store.record(&job_message("j1", "running", "2024-01-01T00:00:02Z")).await;
+
+        assert_eq!(store.purge_expired().await, 0);
+        assert!(store.job_status("j1").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn unknown_job_has_no_events_or_status() {
+        let store = JobEventStore::new();
+        assert!(store.events_since("missing", None).await.is_none());
+        assert!(store.job_status("missing").await.is_none());
+    }
+}
@@ -0,0 +1,209 @@
+// File Path: backend/src/services/latest_event_cache.rs
+
+//! # Latest Event Cache
+//!
+//! Keeps only the most recent `JobEvent` per job id, giving a cheap "current status"
+//! lookup (`GET /api/jobs/:job_id/latest`) without the full event history. The map is
+//! bounded by an LRU capacity, and finished jobs expire after a TTL.
+
+use std::{
+    collections::HashMap,
+    env,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use metrics::counter;
+use tokio::sync::{broadcast::{self, error::RecvError}, Mutex};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::{
+    models::JobEvent,
+    services::{
+        redis_service::{self, RedisMessage},
+        task_supervisor::{Heartbeat, HEARTBEAT_INTERVAL},
+    },
+};
+
+/// Default maximum number of jobs tracked before the least recently used is evicted.
+const DEFAULT_CAPACITY: usize = 1000;
+
+/// Default seconds a finished job's latest event is kept.
+const DEFAULT_COMPLETED_TTL_SECS: u64 = 300;
+
+/// Statuses after which a job receives no further events.
+const TERMINAL_STATUSES: [&str; 3] = ["completed", "failed", "stalled"];
+
+/// A cached event and its bookkeeping.
+#[derive(Debug, Clone)]
+struct CachedEvent {
+    event: JobEvent,
+    /// When the event was recorded (TTL reference for finished jobs).
+    updated_at: Instant,
+    /// Last time the entry was written or read (LRU order).
+    last_used: Instant,
+}
+
+impl CachedEvent {
+    fn is_expired(&self, completed_ttl: Duration) -> bool {
+        TERMINAL_STATUSES.contains(&self.event.status.as_str())
+            && self.updated_at.elapsed() >= completed_ttl
+    }
+}
+
+/// Latest event per job id, bounded by capacity (LRU) and a TTL for finished jobs.
+pub struct LatestEventCache {
+    entries: Mutex<HashMap<String, CachedEvent>>,
+    capacity: usize,
+    completed_ttl: Duration,
+}
+
+impl LatestEventCache {
+    /// Creates a cache sized from `LATEST_EVENT_CACHE_SIZE` (default 1000) and
+    /// `LATEST_EVENT_TTL_SECS` (default 300).
+    pub fn from_env() -> Self {
+        let parse = |name: &str| env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+
+        Self::new(
+            parse("LATEST_EVENT_CACHE_SIZE")
+                .map(|n| n as usize)
+                .filter(|n| *n > 0)
+                .unwrap_or(DEFAULT_CAPACITY),
+            Duration::from_secs(parse("LATEST_EVENT_TTL_SECS").unwrap_or(DEFAULT_COMPLETED_TTL_SECS)),
+        )
+    }
+
+    /// Creates an empty cache holding at most `capacity` jobs.
+    pub fn new(capacity: usize, completed_ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            capacity: capacity.max(1),
+            completed_ttl,
+        }
+    }
+
+    /// Stores `event` as the latest for its job, evicting the least recently used job if full.
+    pub async fn update(&self, event: JobEvent) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().await;
+        entries.retain(|_, cached| !cached.is_expired(self.completed_ttl));
+
+        if !entries.contains_key(&event.job_id) && entries.len() >= self.capacity {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(job_id, _)| job_id.clone());
+            if let Some(job_id) = oldest {
+                entries.remove(&job_id);
+                counter!("tracked_channel_evictions_total", "store" => "latest_events").increment(1);
+            }
+        }
+
+        entries.insert(event.job_id.clone(), CachedEvent { event, updated_at: now, last_used: now });
+    }
+
+    /// Returns the latest event for `job_id`, or `None` if unknown or expired.
+    pub async fn get(&self, job_id: &str) -> Option<JobEvent> {
+        let mut entries = self.entries.lock().await;
+        if entries.get(job_id)?.is_expired(self.completed_ttl) {
+            entries.remove(job_id);
+            return None;
+        }
+
+        let cached = entries.get_mut(job_id)?;
+        cached.last_used = Instant::now();
+        Some(cached.event.clone())
+    }
+
+    /// Returns the latest event for `job_id` if it is final (`completed`, `failed`,
+    /// `stalled`): the job will publish nothing more.
+    pub async fn terminal(&self, job_id: &str) -> Option<JobEvent> {
+        self.get(job_id)
+            .await
+            .filter(|event| TERMINAL_STATUSES.contains(&event.status.as_str()))
+    }
+
+    /// Records a broadcast message if it is a well-formed job event.
+    pub async fn record(&self, msg: &RedisMessage) {
+        if redis_service::parse_job_channel(&msg.channel).is_none() {
+            return;
+        }
+        if let Ok(event) = serde_json::from_str::<JobEvent>(&msg.data) {
+            self.update(event).await;
+        }
+    }
+}
+
+/// Feeds every broadcast job event into the cache until `cancel` is triggered.
+pub async fn start_latest_event_tracker(
+    cache: Arc<LatestEventCache>,
+    ws_tx: broadcast::Sender<RedisMessage>,
+    cancel: CancellationToken,
+    heartbeat: Heartbeat,
+) {
+    let mut rx = ws_tx.subscribe();
+    let mut heartbeat_ticker = tokio::time::interval(HEARTBEAT_INTERVAL);
+    info!("Latest event tracker started.");
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = heartbeat_ticker.tick() => heartbeat.beat(),
+            received = rx.recv() => match received {
+                Ok(msg) => cache.record(&msg).await,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Latest event tracker lagged, skipped {} messages", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+
+    info!("Latest event tracker stopped.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(job_id: &str, status: &str) -> JobEvent {
+        JobEvent::new(job_id, "r1", "backup", "progress", status, serde_json::json!({}))
+    }
+
+    #[tokio::test]
+    async fn keeps_only_latest_event_and_evicts_least_recently_used() {
+        let cache = LatestEventCache::new(2, Duration::from_secs(60));
+        cache.update(event("j1", "started")).await;
+        cache.update(event("j1", "running")).await;
+        cache.update(event("j2", "running")).await;
+
+        // Reading j1 makes j2 the least recently used.
+        assert_eq!(cache.get("j1").await.unwrap().status, "running");
+        cache.update(event("j3", "running")).await;
+
+        assert!(cache.get("j1").await.is_some());
+        assert!(cache.get("j2").await.is_none());
+        assert!(cache.get("j3").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn only_final_events_count_as_terminal() {
+        let cache = LatestEventCache::new(10, Duration::from_secs(60));
+        cache.update(event("done", "completed")).await;
+        cache.update(event("busy", "running")).await;
+
+        assert_eq!(cache.terminal("done").await.unwrap().status, "completed");
+        assert!(cache.terminal("busy").await.is_none());
+        assert!(cache.terminal("unknown").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn finished_jobs_expire_after_ttl() {
+        let cache = LatestEventCache::new(10, Duration::ZERO);
+        cache.update(event("done", "completed")).await;
+        cache.update(event("busy", "running")).await;
+
+        assert!(cache.get("done").await.is_none());
+        assert!(cache.get("busy").await.is_some());
+    }
+}
@@ -9,3 +9,29 @@
 pub mod yaml_service;
 // 2. 🚀 NEW: Declare the new Redis service module
 pub mod redis_service; 
+// Watchdog that flags jobs whose publisher has gone silent
+pub mod job_monitor;
+// In-memory store of job events for HTTP status queries
+pub mod job_store;
+// Single-use WebSocket connection tickets issued over HTTP
+pub mod ticket_store;
+// JoinSet-based owner of background tasks for deterministic shutdown
+pub mod task_supervisor;
+// Typed backup file listings built from a backup directory scan
+pub mod backup_listing;
+// Latest event per job for cheap current-status lookups
+pub mod latest_event_cache;
+// Resumable WebSocket sessions (subscriptions kept for a grace window after disconnect)
+pub mod session_store;
+// Cached, time-bounded readiness check behind GET /ready
+pub mod readiness;
+// Opt-in JSON-lines capture of relayed Redis messages (REDIS_MIRROR_FILE)
+pub mod redis_mirror;
+// Retrying data file reads with a per-file circuit breaker (flaky network mounts)
+pub mod file_reader;
+// Pluggable storage for schema/data files (local filesystem or in-memory)
+pub mod file_store;
+// Optional periodic liveness message on ws_channel:system:heartbeat (SYSTEM_HEARTBEAT_SECS)
+pub mod system_heartbeat;
+// Inventory files expanded into backup target devices (validated against `inventory`)
+pub mod inventory;
@@ -0,0 +1,128 @@
+// File Path: backend/src/services/readiness.rs
+
+//! # Readiness Probe
+//!
+//! Backs `GET /ready`. Orchestrators probe readiness often (Kubernetes defaults to every
+//! few seconds, many setups every second), so the Redis check result is cached for a
+//! short TTL and each check is bounded by a timeout: a slow Redis makes the probe report
+//! "not ready" instead of hanging it.
+
+use std::{
+    env,
+    future::Future,
+    time::{Duration, Instant},
+};
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+/// Default milliseconds a readiness result is reused.
+const DEFAULT_READY_CACHE_TTL_MS: u64 = 2000;
+
+/// Default milliseconds a single dependency check may take.
+const DEFAULT_READY_CHECK_TIMEOUT_MS: u64 = 1000;
+
+/// Outcome of one readiness check, as returned by `GET /ready`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    /// `"ok"`, `"timeout"` or the Redis error message.
+    pub redis: String,
+    /// Milliseconds since the check ran (0 for a fresh check).
+    pub age_ms: u64,
+}
+
+/// Last readiness result and when it was taken.
+struct CachedReport {
+    report: ReadinessReport,
+    checked_at: Instant,
+}
+
+/// Caches the readiness result for `ttl` and bounds every check by `check_timeout`.
+pub struct ReadinessCache {
+    last: Mutex<Option<CachedReport>>,
+    ttl: Duration,
+    check_timeout: Duration,
+}
+
+impl ReadinessCache {
+    /// Creates a cache configured by `READY_CACHE_TTL_MS` (default 2000) and
+    /// `READY_CHECK_TIMEOUT_MS` (default 1000).
+    pub fn from_env() -> Self {
+        let parse = |name: &str| env::var(name).ok().and_then(|v| v.parse::<u64>().ok());
+        Self::new(
+            Duration::from_millis(parse("READY_CACHE_TTL_MS").unwrap_or(DEFAULT_READY_CACHE_TTL_MS)),
+            Duration::from_millis(parse("READY_CHECK_TIMEOUT_MS").unwrap_or(DEFAULT_READY_CHECK_TIMEOUT_MS)),
+        )
+    }
+
+    pub fn new(ttl: Duration, check_timeout: Duration) -> Self {
+        Self {
+            last: Mutex::new(None),
+            ttl,
+            check_timeout,
+        }
+    }
+
+    /// Returns the cached report while it is younger than the TTL, otherwise runs
+    /// `redis_check` (bounded by the check timeout) and caches its outcome.
+    ///
+    /// The lock is held across the check, so concurrent probes share one check.
+    pub async fn status<F, Fut, E>(&self, redis_check: F) -> ReadinessReport
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+        E: std::fmt::Display,
+    {
+        let mut last = self.last.lock().await;
+        if let Some(cached) = last.as_ref().filter(|cached| cached.checked_at.elapsed() < self.ttl) {
+            return ReadinessReport {
+                age_ms: cached.checked_at.elapsed().as_millis() as u64,
+                ..cached.report.clone()
+            };
+        }
+
+        let redis = match tokio::time::timeout(self.check_timeout, redis_check()).await {
+            Ok(Ok(())) => "ok".to_string(),
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => "timeout".to_string(),
+        };
+        let report = ReadinessReport { ready: redis == "ok", redis, age_ms: 0 };
+        *last = Some(CachedReport { report: report.clone(), checked_at: Instant::now() });
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn result_is_reused_within_ttl() {
+        let cache = ReadinessCache::new(Duration::from_secs(60), Duration::from_secs(1));
+        let checks = AtomicUsize::new(0);
+        let counter = &checks;
+        let check = move || async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok::<(), String>(())
+        };
+
+        assert!(cache.status(check).await.ready);
+        assert!(cache.status(check).await.ready);
+        assert_eq!(checks.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn slow_check_times_out_as_not_ready() {
+        let cache = ReadinessCache::new(Duration::ZERO, Duration::from_millis(10));
+        let report = cache
+            .status(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok::<(), String>(())
+            })
+            .await;
+
+        assert!(!report.ready);
+        assert_eq!(report.redis, "timeout");
+    }
+}
@@ -0,0 +1,177 @@
+// File Path: backend/src/services/redis_mirror.rs
+
+//! # Redis Message Mirror
+//!
+//! Opt-in capture (`REDIS_MIRROR_FILE=/path`) of every `RedisMessage` the hub relays,
+//! appended as one JSON line per message. The capture rotates to `<file>.1` once it
+//! reaches `REDIS_MIRROR_MAX_BYTES`, and can be copied into `<data_dir>/fixtures` and
+//! fed to `POST /api/debug/replay` to re-run an incident.
+//!
+//! The mirror reads from the broadcast channel rather than sitting inside the listener,
+//! so a full disk or unwritable file only costs the capture, never message delivery.
+
+use std::{env, path::PathBuf, time::Duration};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::{AsyncWriteExt, BufWriter},
+    sync::broadcast::{self, error::RecvError},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::services::{
+    redis_service::RedisMessage,
+    task_supervisor::{Heartbeat, HEARTBEAT_INTERVAL},
+};
+
+/// Default capture size before rotation (10 MiB).
+const DEFAULT_MIRROR_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// How often buffered lines are flushed to disk.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Appends relayed messages to a JSON-lines file, rotating at a size threshold.
+pub struct RedisMirror {
+    path: PathBuf,
+    max_bytes: u64,
+    /// Open capture file; `None` until first use or after a write error.
+    writer: Option<BufWriter<File>>,
+    /// Bytes in the current capture file.
+    written: u64,
+}
+
+impl RedisMirror {
+    /// Returns a mirror for `REDIS_MIRROR_FILE`, sized by `REDIS_MIRROR_MAX_BYTES`
+    /// (default 10 MiB), or `None` when mirroring is off.
+    pub fn from_env() -> Option<Self> {
+        let path = env::var("REDIS_MIRROR_FILE").ok().filter(|p| !p.is_empty())?;
+        let max_bytes = env::var("REDIS_MIRROR_MAX_BYTES")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|n| *n > 0)
+            .unwrap_or(DEFAULT_MIRROR_MAX_BYTES);
+        Some(Self::new(PathBuf::from(path), max_bytes))
+    }
+
+    pub fn new(path: PathBuf, max_bytes: u64) -> Self {
+        Self { path, max_bytes, writer: None, written: 0 }
+    }
+
+    /// Appends one message as a JSON line, rotating first if it would exceed the limit.
+    pub async fn append(&mut self, msg: &RedisMessage) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(msg)?;
+        line.push(b'\n');
+
+        if self.writer.is_some() && self.written + line.len() as u64 > self.max_bytes {
+            self.rotate().await?;
+        }
+        if self.writer.is_none() {
+            self.open().await?;
+        }
+
+        let writer = self.writer.as_mut().expect("capture file opened above");
+        if let Err(e) = writer.write_all(&line).await {
+            // Reopen on the next message rather than writing into a broken handle.
+            self.writer = None;
+            return Err(e);
+        }
+        self.written += line.len() as u64;
+        Ok(())
+    }
+
+    /// Flushes buffered lines to disk.
+    pub async fn flush(&mut self) -> std::io::Result<()> {
+        match self.writer.as_mut() {
+            Some(writer) => writer.flush().await,
+            None => Ok(()),
+        }
+    }
+
+    async fn open(&mut self) -> std::io::Result<()> {
+        let file = OpenOptions::new().create(true).append(true).open(&self.path).await?;
+        self.written = file.metadata().await?.len();
+        self.writer = Some(BufWriter::new(file));
+        Ok(())
+    }
+
+    /// Moves the current capture to `<file>.1` (replacing an older one) and starts afresh.
+    async fn rotate(&mut self) -> std::io::Result<()> {
+        self.flush().await?;
+        self.writer = None;
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(".1");
+        fs::rename(&self.path, PathBuf::from(rotated)).await?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+/// Mirrors every broadcast message to the capture file until `cancel` is triggered.
+/// Write errors are logged and the capture continues with the next message.
+pub async fn start_redis_mirror(
+    mut mirror: RedisMirror,
+    ws_tx: broadcast::Sender<RedisMessage>,
+    cancel: CancellationToken,
+    heartbeat: Heartbeat,
+) {
+    let mut rx = ws_tx.subscribe();
+    let mut heartbeat_ticker = tokio::time::interval(HEARTBEAT_INTERVAL);
+    let mut flush_ticker = tokio::time::interval(FLUSH_INTERVAL);
+    info!("Mirroring Redis messages to {}", mirror.path.display());
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = heartbeat_ticker.tick() => heartbeat.beat(),
+            _ = flush_ticker.tick() => {
+                if let Err(e) = mirror.flush().await {
+                    warn!("Failed to flush Redis mirror {}: {}", mirror.path.display(), e);
+                }
+            }
+            received = rx.recv() => match received {
+                Ok(msg) => {
+                    if let Err(e) = mirror.append(&msg).await {
+                        warn!("Failed to mirror Redis message to {}: {}", mirror.path.display(), e);
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Redis mirror lagged, {} messages missing from the capture", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+
+    if let Err(e) = mirror.flush().await {
+        warn!("Failed to flush Redis mirror {}: {}", mirror.path.display(), e);
+    }
+    info!("Redis mirror stopped.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn appends_json_lines_and_rotates_at_size_limit() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = dir.path().join("capture.jsonl");
+        let msg = RedisMessage::new("ws_channel:job:j1", r#"{"status":"running"}"#);
+        let line_len = serde_json::to_vec(&msg).unwrap().len() as u64 + 1;
+
+        let mut mirror = RedisMirror::new(path.clone(), line_len * 2);
+        for _ in 0..3 {
+            mirror.append(&msg).await.unwrap();
+        }
+        mirror.flush().await.unwrap();
+
+        let current = std::fs::read_to_string(&path).unwrap();
+        let rotated = std::fs::read_to_string(dir.path().join("capture.jsonl.1")).unwrap();
+        assert_eq!(rotated.lines().count(), 2);
+        assert_eq!(current.lines().count(), 1);
+
+        let line: serde_json::Value = serde_json::from_str(current.lines().next().unwrap()).unwrap();
+        assert_eq!(line["channel"], "ws_channel:job:j1");
+        assert_eq!(line["data"], r#"{"status":"running"}"#);
+    }
+}
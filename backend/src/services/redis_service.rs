@@ -1,88 +1,837 @@
 // File Path: backend/src/services/redis_service.rs
 
 use tokio::sync::broadcast;
-use std::env;
-use tracing::{info, error, instrument};
+use std::{
+    collections::HashMap,
+    env,
+    sync::{atomic::{AtomicUsize, Ordering}, Arc, OnceLock},
+    time::{Duration, Instant},
+};
+use tracing::{info, error, trace, warn, instrument};
+use metrics::counter;
 use futures::StreamExt;
-use serde::Serialize; 
+use serde::{Deserialize, Serialize};
+use redis::{
+    streams::{StreamReadOptions, StreamReadReply},
+    AsyncCommands,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    models::ApiError,
+    services::{
+        task_supervisor::{Heartbeat, HEARTBEAT_INTERVAL},
+        yaml_service::YamlService,
+    },
+};
 
 // The pattern the Rust Hub will subscribe to, catching all job updates.
 const REDIS_CHANNEL_PATTERN: &str = "ws_channel:job:*";
 
+// Prefix shared by every job channel published by the orchestrator.
+const JOB_CHANNEL_PREFIX: &str = "ws_channel:job:";
+
+// Match pattern used to discover job streams in `REDIS_MODE=streams`.
+const REDIS_STREAM_PATTERN: &str = "ws_channel:job:*";
+
+// Field holding the payload in each stream entry (`XADD <channel> * data <payload>`).
+const STREAM_DATA_FIELD: &str = "data";
+
+// Approximate cap on entries kept per job stream when publishing.
+const STREAM_MAX_LEN: usize = 1000;
+
+// How long a single XREAD blocks before re-checking for new streams.
+const STREAM_BLOCK_MS: usize = 5000;
+
+// How often the set of job streams is re-scanned.
+const STREAM_RESCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+// Default largest payload relayed as-is (512 KiB); overridable with MAX_PAYLOAD_BYTES.
+const DEFAULT_MAX_PAYLOAD_BYTES: usize = 512 * 1024;
+
+/// Transport used between the orchestrator and the hub (`REDIS_MODE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedisMode {
+    /// Fire-and-forget Pub/Sub (default). Messages published while disconnected are lost.
+    PubSub,
+    /// Redis Streams. The listener resumes from the last-seen entry id after a reconnect.
+    Streams,
+}
+
+impl RedisMode {
+    /// Reads `REDIS_MODE` (`pubsub` or `streams`), defaulting to Pub/Sub.
+    pub fn from_env() -> Self {
+        match env::var("REDIS_MODE").map(|v| v.to_lowercase()) {
+            Ok(mode) if mode == "streams" => Self::Streams,
+            Ok(mode) if mode != "pubsub" => {
+                warn!("Unknown REDIS_MODE '{}', falling back to pubsub", mode);
+                Self::PubSub
+            }
+            _ => Self::PubSub,
+        }
+    }
+}
+
 /// Struct to wrap the message received from Redis, including the channel name.
 /// This is the data structure sent to WebSocket clients, allowing them to filter.
 #[derive(Debug, Clone, Serialize)]
 pub struct RedisMessage {
     pub channel: String, // The Redis channel the message came from (e.g., ws_channel:job:UUID)
     pub data: String,    // The actual JSON payload from the Python script
+    /// True when the payload wasn't valid UTF-8 and invalid bytes were replaced (U+FFFD).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub lossy: bool,
+    /// True when event validation is in `flag` mode and the payload violated its event schema.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub invalid: bool,
 }
 
-/// Starts a continuous background task to listen for messages on Redis Pub/Sub using a pattern.
-#[instrument(skip(ws_tx))]
-pub async fn start_redis_listener(
-    // The ws_tx is the Sender for the global broadcast channel in ConnectionManager
-    ws_tx: broadcast::Sender<RedisMessage> 
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+impl RedisMessage {
+    /// A well-formed message (valid UTF-8, not flagged by validation).
+    pub fn new(channel: impl Into<String>, data: impl Into<String>) -> Self {
+        Self { channel: channel.into(), data: data.into(), lossy: false, invalid: false }
+    }
+}
+
+/// What the listener does with job payloads that violate their event schema
+/// (`REDIS_EVENT_VALIDATION`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventValidationMode {
+    /// Events are not validated (default).
+    Off,
+    /// Invalid events are forwarded with `invalid: true`.
+    Flag,
+    /// Invalid events are dropped.
+    Drop,
+}
+
+impl EventValidationMode {
+    /// Reads `REDIS_EVENT_VALIDATION` (`off`, `flag`, or `drop`), defaulting to off.
+    pub fn from_env() -> Self {
+        match env::var("REDIS_EVENT_VALIDATION").map(|v| v.to_lowercase()).as_deref() {
+            Ok("flag") => Self::Flag,
+            Ok("drop") => Self::Drop,
+            _ => Self::Off,
+        }
+    }
+}
+
+/// Checks received job payloads against their job type's event schema (falling back to
+/// `job_event`) before broadcast.
+struct EventValidator {
+    mode: EventValidationMode,
+    yaml_service: Arc<YamlService>,
+}
+
+impl EventValidator {
+    /// Returns `None` if the event should be dropped, otherwise whether to flag it invalid.
+    fn check(&self, channel: &str, payload: &str) -> Option<bool> {
+        if self.mode == EventValidationMode::Off || parse_job_channel(channel).is_none() {
+            return Some(false);
+        }
+
+        let result = serde_json::from_str::<serde_json::Value>(payload)
+            .map_err(|e| format!("payload is not JSON: {}", e))
+            .and_then(|event| {
+                self.yaml_service
+                    .validate_job_event(&event)
+                    .map_err(|e| e.to_string())
+            });
+
+        match (result, self.mode) {
+            (Ok(()), _) => Some(false),
+            (Err(e), EventValidationMode::Drop) => {
+                warn!("Dropping invalid job event on channel {}: {}", channel, e);
+                None
+            }
+            (Err(e), _) => {
+                warn!("Invalid job event on channel {}: {}", channel, e);
+                Some(true)
+            }
+        }
+    }
+}
+
+/// Whether non-UTF-8 payloads are forwarded after lossy conversion (`REDIS_LOSSY_UTF8`,
+/// default true) instead of being dropped.
+fn forward_lossy_payloads() -> bool {
+    env::var("REDIS_LOSSY_UTF8")
+        .map(|v| !matches!(v.to_lowercase().as_str(), "0" | "false" | "no"))
+        .unwrap_or(true)
+}
+
+/// Number of WebSocket clients connected to the `ConnectionManager`, shared with every
+/// task that broadcasts to them.
+///
+/// The broadcast channel's own receiver count can't answer "is anyone listening": the
+/// job recorder, latest-event tracker and watchdog stay subscribed even with no clients.
+#[derive(Debug, Clone, Default)]
+pub struct ClientCount(Arc<AtomicUsize>);
+
+impl ClientCount {
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub fn set(&self, count: usize) {
+        self.0.store(count, Ordering::Relaxed);
+    }
+}
+
+/// Sends a message to every WebSocket client via the global broadcast channel.
+///
+/// The message is sent even when `clients` is zero, since internal receivers (job
+/// recorder, latest-event tracker, ...) still consume it. No connected clients is the
+/// normal idle case, so it is only traced and counted in `broadcast_no_receivers_total`
+/// rather than logged as a warning.
+pub fn broadcast_to_clients(ws_tx: &broadcast::Sender<RedisMessage>, clients: &ClientCount, msg: RedisMessage) {
+    let channel = (clients.get() == 0).then(|| msg.channel.clone());
+    // A `SendError` only means no receivers at all, internal ones included.
+    let unsent = ws_tx.send(msg).err().map(|broadcast::error::SendError(msg)| msg.channel);
+    if let Some(channel) = channel.or(unsent) {
+        counter!("broadcast_no_receivers_total").increment(1);
+        trace!("No clients for message on channel {}", channel);
+    }
+}
+
+/// The broadcast channel is permanently closed: the `ConnectionManager` owning the last
+/// `Sender` was dropped, so no client can ever receive a message again.
+#[derive(Debug, thiserror::Error)]
+#[error("broadcast channel closed: every sender was dropped")]
+pub struct BroadcastClosed;
+
+/// Failure of a Redis operation, classified so callers can tell a transient failure
+/// (retry) from one that retrying can't fix.
+#[derive(Debug, thiserror::Error)]
+pub enum RedisError {
+    /// Connecting failed or the connection dropped mid-command.
+    #[error("Redis connection failed: {0}")]
+    Connection(#[source] redis::RedisError),
+    /// `SUBSCRIBE`/`PSUBSCRIBE` was refused.
+    #[error("Redis subscription failed: {0}")]
+    Subscription(#[source] redis::RedisError),
+    /// Redis rejected the credentials (`NOAUTH`, `WRONGPASS`).
+    #[error("Redis authentication failed: {0}")]
+    Auth(#[source] redis::RedisError),
+    /// The connection URL is invalid.
+    #[error("Invalid Redis configuration: {0}")]
+    Config(#[source] redis::RedisError),
+    /// A payload or reply couldn't be encoded or decoded.
+    #[error("Redis payload decode failed: {0}")]
+    PayloadDecode(String),
+    /// The local broadcast channel is gone, so nothing can be relayed anymore.
+    #[error(transparent)]
+    BroadcastClosed(#[from] BroadcastClosed),
+}
+
+impl RedisError {
+    /// Classifies an error raised while connecting or running a command.
+    fn connection(e: redis::RedisError) -> Self {
+        Self::classify(e, Self::Connection)
+    }
+
+    /// Classifies an error raised while subscribing.
+    fn subscription(e: redis::RedisError) -> Self {
+        Self::classify(e, Self::Subscription)
+    }
+
+    fn classify(e: redis::RedisError, otherwise: fn(redis::RedisError) -> Self) -> Self {
+        if e.kind() == redis::ErrorKind::AuthenticationFailed || matches!(e.code(), Some("NOAUTH" | "WRONGPASS")) {
+            Self::Auth(e)
+        } else if e.kind() == redis::ErrorKind::InvalidClientConfig {
+            Self::Config(e)
+        } else if e.kind() == redis::ErrorKind::TypeError {
+            Self::PayloadDecode(e.to_string())
+        } else {
+            otherwise(e)
+        }
+    }
+
+    /// Whether reconnecting may succeed. Auth and configuration errors need an operator,
+    /// and a closed broadcast channel never reopens.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Connection(_) | Self::Subscription(_) | Self::PayloadDecode(_))
+    }
+}
+
+impl From<RedisError> for ApiError {
+    fn from(e: RedisError) -> Self {
+        match e {
+            RedisError::PayloadDecode(detail) => ApiError::SerializationError(detail),
+            other => ApiError::InternalError(other.to_string()),
+        }
+    }
+}
+
+/// Forwards a message from the listener, which only holds a weak handle on the channel.
+///
+/// Unlike `broadcast_to_clients`, this can tell a closed channel (the weak handle no
+/// longer upgrades) from one that merely has no receivers.
+fn forward(ws_tx: &broadcast::WeakSender<RedisMessage>, clients: &ClientCount, msg: RedisMessage) -> Result<(), BroadcastClosed> {
+    let sender = ws_tx.upgrade().ok_or(BroadcastClosed)?;
+    broadcast_to_clients(&sender, clients, msg);
+    Ok(())
+}
+
+// Prefix of the channels the orchestrator listens on for job control messages.
+const CONTROL_CHANNEL_PREFIX: &str = "ws_channel:control:";
+
+/// Builds the control channel name for a job (e.g., "ws_channel:control:UUID").
+pub fn control_channel(job_id: &str) -> String {
+    format!("{}{}", CONTROL_CHANNEL_PREFIX, job_id)
+}
+
+// Pub/Sub channel relaying `ConnectionManager::broadcast` messages between backend instances.
+const BROADCAST_CHANNEL: &str = "ws_channel:broadcast";
+
+/// Channel name local clients see on broadcast messages.
+pub const LOCAL_BROADCAST_CHANNEL: &str = "broadcast";
+
+/// A broadcast as published on `ws_channel:broadcast`, tagged with the sending instance
+/// so it isn't delivered twice to that instance's own clients.
+#[derive(Debug, Serialize, Deserialize)]
+struct BroadcastEnvelope {
+    origin: String,
+    data: String,
+}
+
+/// Identifies this backend process on `ws_channel:broadcast` (`INSTANCE_ID`, default a
+/// random UUID per process).
+pub fn instance_id() -> &'static str {
+    static INSTANCE_ID: OnceLock<String> = OnceLock::new();
+    INSTANCE_ID.get_or_init(|| {
+        env::var("INSTANCE_ID")
+            .ok()
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+    })
+}
+
+/// Publishes a broadcast for the other backend instances to relay to their clients.
+///
+/// Always uses Pub/Sub, also in `REDIS_MODE=streams`.
+pub async fn publish_broadcast(message: &str) -> Result<(), RedisError> {
+    let envelope = BroadcastEnvelope { origin: instance_id().to_string(), data: message.to_string() };
+    let envelope = serde_json::to_string(&envelope).map_err(|e| RedisError::PayloadDecode(e.to_string()))?;
+    let mut conn = connect(&redis_url()).await?;
+    conn.publish::<_, _, ()>(BROADCAST_CHANNEL, envelope).await.map_err(RedisError::connection)?;
+    Ok(())
+}
+
+/// Unwraps a broadcast received from Redis into the message local clients get.
+///
+/// Returns `None` for broadcasts sent by `origin` itself (already delivered locally)
+/// and for malformed envelopes.
+fn remote_broadcast(payload: &str, origin: &str) -> Option<RedisMessage> {
+    match serde_json::from_str::<BroadcastEnvelope>(payload) {
+        Ok(envelope) if envelope.origin == origin => None,
+        Ok(envelope) => Some(RedisMessage::new(LOCAL_BROADCAST_CHANNEL, envelope.data)),
+        Err(e) => {
+            warn!("Ignoring malformed broadcast envelope: {}", e);
+            None
+        }
+    }
+}
+
+// Pattern of the throwaway channels used by `POST /api/debug/ping-roundtrip` probes.
+const ROUNDTRIP_CHANNEL_PATTERN: &str = "ws_channel:roundtrip:*";
+
+/// Builds the channel a round-trip probe with `tag` is published on.
+pub fn roundtrip_channel(tag: &str) -> String {
+    format!("{}{}", ROUNDTRIP_CHANNEL_PATTERN.trim_end_matches('*'), tag)
+}
+
+/// True for the throwaway channels of round-trip probes, which only the probe itself reads.
+pub fn is_roundtrip_channel(channel: &str) -> bool {
+    channel.starts_with(ROUNDTRIP_CHANNEL_PATTERN.trim_end_matches('*'))
+}
+
+/// Publishes a round-trip probe over Pub/Sub; the listener relays it to the broadcast
+/// channel like any other message.
+pub async fn publish_roundtrip_probe(channel: &str, payload: &str) -> Result<(), RedisError> {
+    let mut conn = connect(&redis_url()).await?;
+    conn.publish::<_, _, ()>(channel, payload).await.map_err(RedisError::connection)?;
+    Ok(())
+}
+
+/// Builds the full Redis channel name for a job (e.g., "ws_channel:job:UUID").
+pub fn job_channel(job_id: &str) -> String {
+    format!("{}{}", JOB_CHANNEL_PREFIX, job_id)
+}
+
+/// Extracts the job id from a job channel name (`ws_channel:job:<id>` -> `<id>`).
+///
+/// Returns `None` for every other channel (control, broadcast, ...) and for malformed
+/// job channels: an empty id, a wildcard pattern, or an id containing `:`.
+pub fn parse_job_channel(channel: &str) -> Option<&str> {
+    channel
+        .strip_prefix(JOB_CHANNEL_PREFIX)
+        .filter(|id| !id.is_empty() && !id.contains([':', '*']))
+}
+
+/// Resolves the Redis connection URL from the REDIS_HOST/REDIS_PORT environment variables.
+pub fn redis_url() -> String {
     let redis_host = env::var("REDIS_HOST").unwrap_or_else(|_| "redis_broker".to_string());
     let redis_port = env::var("REDIS_PORT").unwrap_or_else(|_| "6379".to_string());
-    let redis_url = format!("redis://{}:{}", redis_host, redis_port);
-    info!("Starting Redis listener, attempting connection to: {}", redis_url);
+    format!("redis://{}:{}", redis_host, redis_port)
+}
+
+/// Opens a connection, classifying failures (bad URL, refused, rejected credentials).
+async fn connect(url: &str) -> Result<redis::aio::Connection, RedisError> {
+    let client = redis::Client::open(url).map_err(RedisError::connection)?;
+    client.get_tokio_connection().await.map_err(RedisError::connection)
+}
+
+/// Sends a PING over a short-lived connection (readiness probe).
+pub async fn ping() -> redis::RedisResult<()> {
+    let client = redis::Client::open(redis_url())?;
+    let mut conn = client.get_tokio_connection().await?;
+    redis::cmd("PING").query_async::<_, ()>(&mut conn).await
+}
+
+/// Publishes a single payload to a Redis channel using a short-lived connection.
+/// Messages published on job channels flow back through the listener to subscribed clients.
+///
+/// In `REDIS_MODE=streams` the payload is appended to the stream named after the channel.
+pub async fn publish_message(
+    channel: &str,
+    payload: &str,
+) -> Result<(), RedisError> {
+    let mut conn = connect(&redis_url()).await?;
+    match RedisMode::from_env() {
+        RedisMode::PubSub => conn.publish::<_, _, ()>(channel, payload).await.map_err(RedisError::connection)?,
+        RedisMode::Streams => {
+            redis::cmd("XADD")
+                .arg(channel)
+                .arg("MAXLEN")
+                .arg("~")
+                .arg(STREAM_MAX_LEN)
+                .arg("*")
+                .arg(STREAM_DATA_FIELD)
+                .arg(payload)
+                .query_async::<_, ()>(&mut conn)
+                .await
+                .map_err(RedisError::connection)?
+        }
+    }
+    Ok(())
+}
+
+/// Reads the largest relayed payload from `MAX_PAYLOAD_BYTES` (default 512 KiB).
+fn max_payload_bytes() -> usize {
+    env::var("MAX_PAYLOAD_BYTES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_MAX_PAYLOAD_BYTES)
+}
+
+/// Returns a small placeholder for payloads over `max_bytes`, so an oversized blob isn't
+/// cloned to every subscriber: `{"type":"payload_too_large","size":N,"channel":"..."}`.
+fn oversized_placeholder(channel: &str, size: usize, max_bytes: usize) -> Option<RedisMessage> {
+    if size <= max_bytes {
+        return None;
+    }
+    counter!("redis_oversized_payloads_total").increment(1);
+    warn!("Payload of {} bytes on channel {} exceeds {} bytes; relaying a placeholder", size, channel, max_bytes);
+    let placeholder = serde_json::json!({ "type": "payload_too_large", "size": size, "channel": channel });
+    Some(RedisMessage::new(channel, placeholder.to_string()))
+}
+
+/// Converts a raw payload to a string, applying the lossy UTF-8 policy.
+/// Returns `None` when the payload should be dropped.
+fn decode_payload(raw: Vec<u8>, channel: &str, lossy_utf8: bool) -> Option<(String, bool)> {
+    match String::from_utf8(raw) {
+        Ok(p) => Some((p, false)),
+        Err(e) => {
+            counter!("redis_payload_decode_errors_total").increment(1);
+            if !lossy_utf8 {
+                error!("Dropping non-UTF-8 payload on channel {}: {}", channel, e);
+                return None;
+            }
+            warn!("Non-UTF-8 payload on channel {}, forwarding with lossy conversion", channel);
+            Some((String::from_utf8_lossy(e.as_bytes()).into_owned(), true))
+        }
+    }
+}
+
+/// Starts a continuous background task to listen for messages on Redis Pub/Sub using a pattern.
+///
+/// Runs until `cancel` is triggered, at which point it returns `Ok(())`. `heartbeat`
+/// is updated while the listener is connected or actively retrying. Job payloads are
+/// checked against their event schema per `REDIS_EVENT_VALIDATION`.
+///
+/// `ws_tx` is a weak handle on the `ConnectionManager`'s broadcast channel, so the
+/// listener doesn't keep the channel alive by itself. If the channel closes the
+/// listener can never deliver anything again: it logs an error and returns
+/// `BroadcastClosed` instead of reconnecting, leaving the supervisor to react. The same
+/// goes for any other error that isn't `RedisError::is_retryable` (bad credentials or URL).
+#[instrument(skip(ws_tx, clients, yaml_service, cancel, heartbeat))]
+pub async fn start_redis_listener(
+    // Weak handle on the global broadcast channel owned by ConnectionManager
+    ws_tx: broadcast::WeakSender<RedisMessage>,
+    clients: ClientCount,
+    yaml_service: Arc<YamlService>,
+    cancel: CancellationToken,
+    heartbeat: Heartbeat,
+) -> Result<(), RedisError> {
+    let redis_url = redis_url();
+    let mode = RedisMode::from_env();
+    let validator = EventValidator { mode: EventValidationMode::from_env(), yaml_service };
+    info!("Starting Redis listener ({:?} mode), attempting connection to: {}", mode, redis_url);
+
+    // Last-seen entry id per stream; kept across reconnects so no entries are skipped.
+    let mut stream_offsets: HashMap<String, String> = HashMap::new();
     
-    loop {
-        match try_connect_and_subscribe(&redis_url, ws_tx.clone()).await {
+    while !cancel.is_cancelled() {
+        heartbeat.beat();
+        let result = match ws_tx.upgrade() {
+            None => Err(BroadcastClosed.into()),
+            Some(_) => match mode {
+                RedisMode::PubSub => {
+                    try_connect_and_subscribe(&redis_url, &ws_tx, &clients, &validator, &cancel, &heartbeat).await
+                }
+                RedisMode::Streams => {
+                    try_connect_and_read_streams(&redis_url, &ws_tx, &clients, &validator, &cancel, &heartbeat, &mut stream_offsets).await
+                }
+            },
+        };
+        match result {
+            Ok(_) if cancel.is_cancelled() => break,
             Ok(_) => info!("Redis subscription cleanly stopped (unexpected). Restarting..."),
+            Err(e) if !e.is_retryable() => {
+                error!("Redis listener stopping: {}", e);
+                return Err(e);
+            }
             Err(e) => {
                 error!("Redis connection or subscription failed: {}. Retrying in 5 seconds...", e);
-                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = tokio::time::sleep(std::time::Duration::from_secs(5)) => {}
+                }
             }
         }
     }
+
+    info!("Redis listener received shutdown signal.");
+    Ok(())
 }
 
 /// Connects to Redis, subscribes to the channel pattern, and runs the message consumption loop.
 async fn try_connect_and_subscribe(
     url: &str,
-    ws_tx: broadcast::Sender<RedisMessage>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let client = redis::Client::open(url)?;
+    ws_tx: &broadcast::WeakSender<RedisMessage>,
+    clients: &ClientCount,
+    validator: &EventValidator,
+    cancel: &CancellationToken,
+    heartbeat: &Heartbeat,
+) -> Result<(), RedisError> {
     // Use the tokio connection for async operations
-    let conn = client.get_tokio_connection().await?; 
+    let conn = connect(url).await?;
     
     let mut pubsub = conn.into_pubsub();
     
     // Subscribing to a PATTERN
-    pubsub.psubscribe(REDIS_CHANNEL_PATTERN).await?;
+    pubsub.psubscribe(REDIS_CHANNEL_PATTERN).await.map_err(RedisError::subscription)?;
     info!("Successfully subscribed to Redis pattern: {}", REDIS_CHANNEL_PATTERN);
+    pubsub.subscribe(BROADCAST_CHANNEL).await.map_err(RedisError::subscription)?;
+    pubsub.psubscribe(ROUNDTRIP_CHANNEL_PATTERN).await.map_err(RedisError::subscription)?;
     
+    let lossy_utf8 = forward_lossy_payloads();
+    let max_payload = max_payload_bytes();
     let mut message_stream = pubsub.on_message();
+    let mut heartbeat_ticker = tokio::time::interval(HEARTBEAT_INTERVAL);
     
-    while let Some(msg) = message_stream.next().await {
-        
+    loop {
+        let msg = tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = heartbeat_ticker.tick() => {
+                heartbeat.beat();
+                continue;
+            }
+            next = message_stream.next() => match next {
+                Some(msg) => msg,
+                None => break,
+            },
+        };
+
         // --- 1. Handle Payload Extraction ---
-        let payload: String = match msg.get_payload() {
+        // Fetch raw bytes so a mis-encoded payload isn't silently dropped.
+        let raw: Vec<u8> = match msg.get_payload() {
             Ok(p) => p,
             Err(e) => {
                 error!("Failed to get payload from Redis message: {}", e);
                 continue;
             }
         };
+        if let Some(placeholder) = oversized_placeholder(msg.get_channel_name(), raw.len(), max_payload) {
+            forward(ws_tx, clients, placeholder)?;
+            continue;
+        }
+
+        let Some((payload, lossy)) = decode_payload(raw, msg.get_channel_name(), lossy_utf8) else {
+            continue;
+        };
+        if msg.get_channel_name() == BROADCAST_CHANNEL {
+            if let Some(message) = remote_broadcast(&payload, instance_id()) {
+                forward(ws_tx, clients, message)?;
+            }
+            continue;
+        }
         
         // --- 2. Create the RedisMessage struct ---
         // Get the channel name the message was received on
         let redis_channel = msg.get_channel_name().to_string();
+        let Some(invalid) = validator.check(&redis_channel, &payload) else {
+            continue;
+        };
         let wrapped_message = RedisMessage {
             channel: redis_channel,
             data: payload,
+            lossy,
+            invalid,
         };
         
         info!("Redis message received on channel {}: {}", wrapped_message.channel, wrapped_message.data);
         
         // --- 3. Broadcast the WRAPPED message to WebSocket Clients ---
         // The clients' workers will check the 'channel' field to filter the message.
-        if ws_tx.send(wrapped_message).is_err() {
-            // Non-fatal: means no WebSocket clients are listening currently.
-        }
+        forward(ws_tx, clients, wrapped_message)?;
     }
     
     Ok(())
 }
+
+/// Connects to Redis and consumes every job stream with `XREAD BLOCK`, resuming from
+/// `offsets` (stream key -> last-seen entry id) so a reconnect doesn't lose entries.
+async fn try_connect_and_read_streams(
+    url: &str,
+    ws_tx: &broadcast::WeakSender<RedisMessage>,
+    clients: &ClientCount,
+    validator: &EventValidator,
+    cancel: &CancellationToken,
+    heartbeat: &Heartbeat,
+    offsets: &mut HashMap<String, String>,
+) -> Result<(), RedisError> {
+    let mut conn = connect(url).await?;
+    info!("Reading Redis streams matching: {}", REDIS_STREAM_PATTERN);
+
+    let lossy_utf8 = forward_lossy_payloads();
+    let max_payload = max_payload_bytes();
+    let options = StreamReadOptions::default().block(STREAM_BLOCK_MS);
+    let mut last_scan: Option<Instant> = None;
+
+    loop {
+        // XREAD blocks for at most STREAM_BLOCK_MS, so every pass doubles as a heartbeat.
+        heartbeat.beat();
+        let rescan_due = match last_scan {
+            Some(t) => t.elapsed() >= STREAM_RESCAN_INTERVAL,
+            None => true,
+        };
+        if rescan_due {
+            let initial = last_scan.is_none() && offsets.is_empty();
+            discover_streams(&mut conn, offsets, initial).await.map_err(RedisError::connection)?;
+            last_scan = Some(Instant::now());
+        }
+
+        if offsets.is_empty() {
+            // Nothing to read yet: wait for the orchestrator to create a job stream.
+            tokio::select! {
+                _ = cancel.cancelled() => break,
+                _ = tokio::time::sleep(STREAM_RESCAN_INTERVAL) => continue,
+            }
+        }
+
+        let (keys, ids): (Vec<String>, Vec<String>) =
+            offsets.iter().map(|(k, id)| (k.clone(), id.clone())).unzip();
+
+        // XREAD returns nil when the block timeout expires without new entries.
+        let reply: Option<StreamReadReply> = tokio::select! {
+            _ = cancel.cancelled() => break,
+            reply = conn.xread_options(&keys, &ids, &options) => reply.map_err(RedisError::connection)?,
+        };
+
+        for stream in reply.map(|r| r.keys).unwrap_or_default() {
+            for entry in stream.ids {
+                offsets.insert(stream.key.clone(), entry.id.clone());
+
+                let Some(raw) = entry.get::<Vec<u8>>(STREAM_DATA_FIELD) else {
+                    warn!("Stream entry {} on {} has no '{}' field", entry.id, stream.key, STREAM_DATA_FIELD);
+                    continue;
+                };
+                if let Some(placeholder) = oversized_placeholder(&stream.key, raw.len(), max_payload) {
+                    forward(ws_tx, clients, placeholder)?;
+                    continue;
+                }
+                let Some((payload, lossy)) = decode_payload(raw, &stream.key, lossy_utf8) else {
+                    continue;
+                };
+
+                info!("Redis stream entry received on {}: {}", stream.key, payload);
+                let Some(invalid) = validator.check(&stream.key, &payload) else {
+                    continue;
+                };
+                forward(ws_tx, clients, RedisMessage {
+                    channel: stream.key.clone(),
+                    data: payload,
+                    lossy,
+                    invalid,
+                })?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds newly created job streams to `offsets`.
+///
+/// On the `initial` scan, existing streams start at their latest entry (history isn't
+/// replayed); streams that appear later start at the beginning, since every entry
+/// in them was published after the listener started watching.
+async fn discover_streams(
+    conn: &mut redis::aio::Connection,
+    offsets: &mut HashMap<String, String>,
+    initial: bool,
+) -> redis::RedisResult<()> {
+    let mut cursor: u64 = 0;
+
+    loop {
+        let (next, keys): (u64, Vec<String>) = redis::cmd("SCAN")
+            .arg(cursor)
+            .arg("MATCH")
+            .arg(REDIS_STREAM_PATTERN)
+            .arg("TYPE")
+            .arg("stream")
+            .query_async(conn)
+            .await?;
+
+        for key in keys {
+            if offsets.contains_key(&key) {
+                continue;
+            }
+            let start = if initial {
+                latest_entry_id(conn, &key).await?
+            } else {
+                "0-0".to_string()
+            };
+            offsets.insert(key, start);
+        }
+
+        if next == 0 {
+            return Ok(());
+        }
+        cursor = next;
+    }
+}
+
+/// Returns the id of a stream's newest entry, or "0-0" for an empty stream.
+async fn latest_entry_id(conn: &mut redis::aio::Connection, key: &str) -> redis::RedisResult<String> {
+    let newest: Vec<(String, HashMap<String, redis::Value>)> = redis::cmd("XREVRANGE")
+        .arg(key)
+        .arg("+")
+        .arg("-")
+        .arg("COUNT")
+        .arg(1)
+        .query_async(conn)
+        .await?;
+    Ok(newest.into_iter().next().map(|(id, _)| id).unwrap_or_else(|| "0-0".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auth_and_config_failures_are_not_retried() {
+        let refused = std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "refused");
+        assert!(matches!(RedisError::connection(refused.into()), RedisError::Connection(_)));
+
+        let wrong_pass = redis::parse_redis_value(b"-WRONGPASS invalid password\r\n").unwrap_err();
+        let auth = RedisError::connection(wrong_pass);
+        assert!(matches!(auth, RedisError::Auth(_)));
+        assert!(!auth.is_retryable());
+
+        let bad_url = RedisError::connection((redis::ErrorKind::InvalidClientConfig, "bad url").into());
+        assert!(!bad_url.is_retryable());
+        assert!(RedisError::subscription((redis::ErrorKind::ResponseError, "denied").into()).is_retryable());
+        assert!(!RedisError::from(BroadcastClosed).is_retryable());
+    }
+
+    #[test]
+    fn forwarding_tells_a_closed_channel_from_an_idle_one() {
+        let (tx, rx) = broadcast::channel(4);
+        let weak = tx.downgrade();
+
+        let clients = ClientCount::default();
+
+        drop(rx);
+        assert!(forward(&weak, &clients, RedisMessage::new("ws_channel:job:j1", "idle")).is_ok(), "no receivers is not fatal");
+
+        drop(tx);
+        assert!(matches!(forward(&weak, &clients, RedisMessage::new("ws_channel:job:j1", "gone")), Err(BroadcastClosed)));
+    }
+
+    #[test]
+    fn broadcast_without_clients_is_counted_even_with_internal_receivers() {
+        let recorder = metrics_exporter_prometheus::PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        let (tx, mut internal) = broadcast::channel(4);
+        let clients = ClientCount::default();
+
+        metrics::with_local_recorder(&recorder, || {
+            broadcast_to_clients(&tx, &clients, RedisMessage::new("ws_channel:job:j1", "idle"));
+        });
+        assert!(handle.render().contains("broadcast_no_receivers_total 1"), "{}", handle.render());
+        assert_eq!(internal.try_recv().unwrap().data, "idle", "internal receivers still get the message");
+
+        clients.set(1);
+        metrics::with_local_recorder(&recorder, || {
+            broadcast_to_clients(&tx, &clients, RedisMessage::new("ws_channel:job:j1", "seen"));
+        });
+        assert!(handle.render().contains("broadcast_no_receivers_total 1"), "{}", handle.render());
+    }
+
+    #[test]
+    fn oversized_payload_is_replaced_by_placeholder() {
+        let channel = "ws_channel:job:j1";
+        let payload = "x".repeat(2048);
+
+        assert!(oversized_placeholder(channel, payload.len(), 4096).is_none());
+
+        let placeholder = oversized_placeholder(channel, payload.len(), 1024).expect("payload is over the limit");
+        assert_eq!(placeholder.channel, channel);
+        let data: serde_json::Value = serde_json::from_str(&placeholder.data).unwrap();
+        assert_eq!(
+            data,
+            serde_json::json!({ "type": "payload_too_large", "size": 2048, "channel": channel })
+        );
+    }
+
+    #[test]
+    fn job_ids_are_parsed_from_job_channels_only() {
+        assert_eq!(parse_job_channel("ws_channel:job:3f2a-91c0"), Some("3f2a-91c0"));
+        assert_eq!(parse_job_channel(&job_channel("j1")), Some("j1"));
+
+        assert_eq!(parse_job_channel(BROADCAST_CHANNEL), None);
+        assert_eq!(parse_job_channel(&control_channel("j1")), None);
+        assert_eq!(parse_job_channel("ws_channel:job:"), None);
+        assert_eq!(parse_job_channel("ws_channel:job:*"), None);
+        assert_eq!(parse_job_channel("ws_channel:job:a:b"), None);
+        assert_eq!(parse_job_channel("job:j1"), None);
+        assert_eq!(parse_job_channel(""), None);
+    }
+
+    #[test]
+    fn broadcasts_from_this_instance_are_not_relayed_again() {
+        let payload = serde_json::json!({ "origin": "a", "data": "maintenance at 5pm" }).to_string();
+
+        assert!(remote_broadcast(&payload, "a").is_none());
+
+        let relayed = remote_broadcast(&payload, "b").expect("broadcast from another instance");
+        assert_eq!(relayed.channel, LOCAL_BROADCAST_CHANNEL);
+        assert_eq!(relayed.data, "maintenance at 5pm");
+
+        assert!(remote_broadcast("not json", "b").is_none());
+    }
+}
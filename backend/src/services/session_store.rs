@@ -0,0 +1,290 @@
+// File Path: backend/src/services/session_store.rs
+
+//! # WebSocket Session Store
+//!
+//! Remembers the subscriptions of recently disconnected WebSocket clients so a client
+//! reconnecting with `/ws?session=<id>` within the grace window picks up where it left
+//! off instead of starting fresh (e.g. flaky mobile connections).
+//!
+//! With `PERSIST_SUBSCRIPTIONS=true` sessions are also kept in Redis (`ws_session:<id>`),
+//! so a client can resume even after the server restarted (rolling deploys). A live
+//! session's entry is refreshed on every SUBSCRIBE/UNSUBSCRIBE and expires after
+//! `PERSIST_SUBSCRIPTIONS_TTL_SECS`; on disconnect it shrinks to the grace window.
+
+use std::{
+    collections::HashMap,
+    env,
+    time::{Duration, Instant},
+};
+use redis::AsyncCommands;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::services::redis_service;
+
+/// Default seconds a disconnected session stays resumable.
+const DEFAULT_SESSION_GRACE_SECS: u64 = 60;
+
+/// Default seconds a live session's persisted subscriptions survive without an update.
+const DEFAULT_PERSIST_TTL_SECS: u64 = 86_400;
+
+/// Prefix of the Redis keys holding persisted sessions.
+const SESSION_KEY_PREFIX: &str = "ws_session:";
+
+/// A disconnected session awaiting resume.
+#[derive(Debug, Clone)]
+struct SavedSession {
+    subscriptions: Vec<String>,
+    expires_at: Instant,
+}
+
+/// TTL map of disconnected sessions, keyed by session id.
+pub struct SessionStore {
+    sessions: Mutex<HashMap<String, SavedSession>>,
+    grace: Duration,
+    /// Redis copy of the sessions (`PERSIST_SUBSCRIPTIONS=true`), surviving restarts.
+    persistence: Option<PersistedSessions>,
+}
+
+/// Sessions stored in Redis, one expiring key per session.
+struct PersistedSessions {
+    client: redis::Client,
+    /// Expiry of a connected session's entry, refreshed on every change.
+    live_ttl: Duration,
+}
+
+impl PersistedSessions {
+    /// Enabled by `PERSIST_SUBSCRIPTIONS=true`; live entries expire after
+    /// `PERSIST_SUBSCRIPTIONS_TTL_SECS` (default 86400).
+    fn from_env() -> Option<Self> {
+        let enabled = env::var("PERSIST_SUBSCRIPTIONS")
+            .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+
+        let client = match redis::Client::open(redis_service::redis_url()) {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("PERSIST_SUBSCRIPTIONS disabled, invalid Redis URL: {}", e);
+                return None;
+            }
+        };
+        let live_ttl = env::var("PERSIST_SUBSCRIPTIONS_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_PERSIST_TTL_SECS);
+        info!("Persisting WebSocket subscriptions to Redis (live TTL {}s)", live_ttl);
+        Some(Self { client, live_ttl: Duration::from_secs(live_ttl) })
+    }
+
+    async fn store(&self, session_id: &str, subscriptions: &[String], ttl: Duration) -> redis::RedisResult<()> {
+        let mut conn = self.client.get_tokio_connection().await?;
+        let value = serde_json::to_string(subscriptions).unwrap_or_else(|_| "[]".to_string());
+        conn.set_ex(session_key(session_id), value, ttl.as_secs().max(1) as usize).await
+    }
+
+    /// Removes and returns a persisted session. `GETDEL` (Redis 6.2+) is atomic, so two
+    /// instances racing to resume the same session can't both get it.
+    async fn take(&self, session_id: &str) -> redis::RedisResult<Option<Vec<String>>> {
+        let mut conn = self.client.get_tokio_connection().await?;
+        let value: Option<String> = conn.get_del(session_key(session_id)).await?;
+        Ok(value.and_then(|v| serde_json::from_str(&v).ok()))
+    }
+}
+
+fn session_key(session_id: &str) -> String {
+    format!("{}{}", SESSION_KEY_PREFIX, session_id)
+}
+
+impl SessionStore {
+    /// Creates an empty store with the grace window from `WS_SESSION_GRACE_SECS` (default 60),
+    /// persisted to Redis when `PERSIST_SUBSCRIPTIONS=true`.
+    pub fn new() -> Self {
+        let secs = env::var("WS_SESSION_GRACE_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_SESSION_GRACE_SECS);
+        Self {
+            persistence: PersistedSessions::from_env(),
+            ..Self::with_grace(Duration::from_secs(secs))
+        }
+    }
+
+    /// Creates an empty in-memory store with a custom grace window.
+    pub fn with_grace(grace: Duration) -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            grace,
+            persistence: None,
+        }
+    }
+
+    /// Records a connected session's current subscriptions in Redis, so they can be
+    /// resumed even if the server dies before the client disconnects. No-op in memory mode.
+    pub async fn checkpoint(&self, session_id: &str, subscriptions: &[String]) {
+        if let Some(persistence) = &self.persistence {
+            if let Err(e) = persistence.store(session_id, subscriptions, persistence.live_ttl).await {
+                warn!("Failed to persist subscriptions of session {}: {}", session_id, e);
+            }
+        }
+    }
+
+    /// Saves a disconnected session's subscriptions. Expired sessions are pruned on the way.
+    pub async fn save(&self, session_id: &str, subscriptions: Vec<String>) {
+        if let Some(persistence) = &self.persistence {
+            if let Err(e) = persistence.store(session_id, &subscriptions, self.grace).await {
+                warn!("Failed to persist session {}: {}", session_id, e);
+            }
+        }
+
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().await;
+        sessions.retain(|_, session| session.expires_at > now);
+        sessions.insert(
+            session_id.to_string(),
+            SavedSession { subscriptions, expires_at: now + self.grace },
+        );
+    }
+
+    /// Takes a saved session's subscriptions. Returns `None` if the session is unknown,
+    /// already resumed, or past its grace window.
+    ///
+    /// With persistence, sessions this process doesn't know (saved before a restart or
+    /// by another instance) are looked up in Redis.
+    pub async fn resume(&self, session_id: &str) -> Option<Vec<String>> {
+        let local = self.sessions.lock().await.remove(session_id);
+        let Some(persistence) = &self.persistence else {
+            let session = local?;
+            return (session.expires_at > Instant::now()).then_some(session.subscriptions);
+        };
+
+        // Redis is the source of truth here (it also expires entries); taking the key
+        // keeps the resume single-use across instances.
+        match persistence.take(session_id).await {
+            Ok(persisted) => persisted,
+            Err(e) => {
+                warn!("Failed to load persisted session {}: {}", session_id, e);
+                local
+                    .filter(|session| session.expires_at > Instant::now())
+                    .map(|session| session.subscriptions)
+            }
+        }
+    }
+}
+
+impl Default for SessionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    /// Minimal in-process Redis speaking just the commands `PersistedSessions` uses
+    /// (`SETEX`, `GETDEL`); anything else, a non-atomic `GET` + `DEL` included, is refused.
+    async fn fake_redis() -> redis::Client {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("redis://{}", listener.local_addr().unwrap());
+        let keys = Arc::new(Mutex::new(HashMap::<Vec<u8>, Vec<u8>>::new()));
+
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let keys = keys.clone();
+                tokio::spawn(async move {
+                    let (reader, mut writer) = socket.into_split();
+                    let mut reader = BufReader::new(reader);
+                    while let Some(args) = read_command(&mut reader).await {
+                        let reply = match (args[0].to_ascii_uppercase().as_slice(), &args[1..]) {
+                            (b"SETEX", [key, _ttl, value]) => {
+                                keys.lock().await.insert(key.clone(), value.clone());
+                                b"+OK\r\n".to_vec()
+                            }
+                            (b"GETDEL", [key]) => match keys.lock().await.remove(key) {
+                                Some(value) => [format!("${}\r\n", value.len()).into_bytes(), value, b"\r\n".to_vec()].concat(),
+                                None => b"$-1\r\n".to_vec(),
+                            },
+                            _ => b"-ERR unsupported command\r\n".to_vec(),
+                        };
+                        if writer.write_all(&reply).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        redis::Client::open(url).unwrap()
+    }
+
+    /// Reads one RESP command (an array of bulk strings).
+    async fn read_command(reader: &mut (impl AsyncBufReadExt + Unpin)) -> Option<Vec<Vec<u8>>> {
+        let mut line = String::new();
+        reader.read_line(&mut line).await.ok().filter(|n| *n > 0)?;
+        let count: usize = line.trim_end().strip_prefix('*')?.parse().ok()?;
+        let mut args = Vec::with_capacity(count);
+        for _ in 0..count {
+            line.clear();
+            reader.read_line(&mut line).await.ok()?;
+            let len: usize = line.trim_end().strip_prefix('$')?.parse().ok()?;
+            let mut arg = vec![0; len + 2];
+            reader.read_exact(&mut arg).await.ok()?;
+            arg.truncate(len);
+            args.push(arg);
+        }
+        Some(args)
+    }
+
+    fn persisted_store(client: redis::Client) -> SessionStore {
+        SessionStore {
+            persistence: Some(PersistedSessions { client, live_ttl: Duration::from_secs(60) }),
+            ..SessionStore::with_grace(Duration::from_secs(60))
+        }
+    }
+
+    #[tokio::test]
+    async fn session_resumes_once_with_its_subscriptions() {
+        let store = SessionStore::with_grace(Duration::from_secs(60));
+        store.save("s1", vec!["ws_channel:job:a".to_string()]).await;
+
+        assert_eq!(store.resume("s1").await, Some(vec!["ws_channel:job:a".to_string()]));
+        assert!(store.resume("s1").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn session_past_grace_window_is_not_resumed() {
+        let store = SessionStore::with_grace(Duration::ZERO);
+        store.save("s1", vec!["ws_channel:job:a".to_string()]).await;
+
+        assert!(store.resume("s1").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn persisted_session_round_trips_through_redis() {
+        let client = fake_redis().await;
+        let subscriptions = vec!["ws_channel:job:a".to_string(), "ws_channel:job:*".to_string()];
+        persisted_store(client.clone()).save("s1", subscriptions.clone()).await;
+
+        // A fresh store stands in for a restarted server: only Redis knows the session.
+        assert_eq!(persisted_store(client).resume("s1").await, Some(subscriptions));
+    }
+
+    #[tokio::test]
+    async fn persisted_session_is_taken_only_once() {
+        let client = fake_redis().await;
+        let persisted = PersistedSessions { client, live_ttl: Duration::from_secs(60) };
+        persisted.store("s1", &["ws_channel:job:a".to_string()], Duration::from_secs(60)).await.unwrap();
+
+        assert_eq!(persisted.take("s1").await.unwrap(), Some(vec!["ws_channel:job:a".to_string()]));
+        assert_eq!(persisted.take("s1").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,101 @@
+// File Path: backend/src/services/system_heartbeat.rs
+
+//! # System Heartbeat
+//!
+//! Optional application-level liveness signal: every `SYSTEM_HEARTBEAT_SECS` a
+//! `{"type":"HEARTBEAT","ts":...}` message goes to subscribers of
+//! `ws_channel:system:heartbeat` (clients send `SUBSCRIBE system:heartbeat`). Clients
+//! can treat a missed heartbeat as a dead backend well before a TCP timeout, even when
+//! no jobs are running. This complements the per-connection ping/pong.
+
+use std::{env, time::Duration};
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::services::{
+    redis_service::{self, ClientCount, RedisMessage},
+    task_supervisor::{Heartbeat, HEARTBEAT_INTERVAL},
+};
+
+/// Channel the heartbeat is delivered on.
+pub const SYSTEM_HEARTBEAT_CHANNEL: &str = "ws_channel:system:heartbeat";
+
+/// Reads the heartbeat interval from `SYSTEM_HEARTBEAT_SECS`. Unset or `0` disables it.
+pub fn interval_from_env() -> Option<Duration> {
+    env::var("SYSTEM_HEARTBEAT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+}
+
+/// Builds the heartbeat message sent at `now`.
+fn heartbeat_message(now: DateTime<Utc>) -> RedisMessage {
+    let payload = serde_json::json!({
+        "type": "HEARTBEAT",
+        "ts": now.to_rfc3339(),
+        "instance": redis_service::instance_id(),
+    });
+    RedisMessage::new(SYSTEM_HEARTBEAT_CHANNEL, payload.to_string())
+}
+
+/// Sends a heartbeat to local WebSocket clients every `interval` until `cancel` is triggered.
+pub async fn start_system_heartbeat(
+    ws_tx: broadcast::Sender<RedisMessage>,
+    clients: ClientCount,
+    interval: Duration,
+    cancel: CancellationToken,
+    heartbeat: Heartbeat,
+) {
+    let mut ticker = tokio::time::interval(interval);
+    let mut heartbeat_ticker = tokio::time::interval(HEARTBEAT_INTERVAL);
+
+    info!("System heartbeat started (every {:?} on {})", interval, SYSTEM_HEARTBEAT_CHANNEL);
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            _ = heartbeat_ticker.tick() => heartbeat.beat(),
+            _ = ticker.tick() => redis_service::broadcast_to_clients(&ws_tx, &clients, heartbeat_message(Utc::now())),
+        }
+    }
+
+    info!("System heartbeat stopped.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heartbeat_message_is_typed_and_timestamped() {
+        let now = Utc::now();
+        let message = heartbeat_message(now);
+        assert_eq!(message.channel, SYSTEM_HEARTBEAT_CHANNEL);
+
+        let payload: serde_json::Value = serde_json::from_str(&message.data).unwrap();
+        assert_eq!(payload["type"], "HEARTBEAT");
+        assert_eq!(payload["ts"], now.to_rfc3339());
+    }
+
+    #[tokio::test]
+    async fn heartbeats_are_sent_until_cancelled() {
+        let (tx, mut rx) = broadcast::channel(8);
+        let cancel = CancellationToken::new();
+        let task = tokio::spawn(start_system_heartbeat(
+            tx,
+            ClientCount::default(),
+            Duration::from_millis(10),
+            cancel.clone(),
+            Heartbeat::new(),
+        ));
+
+        let first = rx.recv().await.unwrap();
+        assert_eq!(first.channel, SYSTEM_HEARTBEAT_CHANNEL);
+
+        cancel.cancel();
+        task.await.unwrap();
+    }
+}
@@ -0,0 +1,247 @@
+// File Path: backend/src/services/task_supervisor.rs
+
+//! # Background Task Supervisor
+//!
+//! Owns every long-running background task (Redis listener, job watchdog, event
+//! recorder, subscription sweep, ...) in a single `JoinSet` sharing one
+//! `CancellationToken`, so shutdown cancels them together and waits for them with
+//! a deadline instead of leaving them detached.
+//!
+//! Tasks also report liveness through a `Heartbeat`, so a task that is wedged
+//! (alive but no longer making progress) shows up in `GET /api/health/tasks`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use serde::Serialize;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// Default time background tasks get to stop after cancellation.
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 10;
+
+/// How often background tasks record a heartbeat while idle.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Default seconds without a heartbeat before a task is reported as stale.
+const DEFAULT_TASK_STALE_AFTER_SECS: u64 = 30;
+
+/// Reads the shutdown deadline from `SHUTDOWN_TIMEOUT_SECS` (default 10).
+pub fn shutdown_timeout_from_env() -> Duration {
+    let secs = env::var("SHUTDOWN_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Reads the heartbeat staleness threshold from `TASK_STALE_AFTER_SECS` (default 30).
+pub fn task_stale_after_from_env() -> Duration {
+    let secs = env::var("TASK_STALE_AFTER_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_TASK_STALE_AFTER_SECS);
+    Duration::from_secs(secs)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A task's "last heartbeat" timestamp (Unix seconds), updated by the task itself.
+#[derive(Debug, Clone)]
+pub struct Heartbeat(Arc<AtomicU64>);
+
+impl Heartbeat {
+    /// Creates a heartbeat that has just beaten.
+    pub fn new() -> Self {
+        Self(Arc::new(AtomicU64::new(unix_now())))
+    }
+
+    /// Records that the task is still making progress.
+    pub fn beat(&self) {
+        self.0.store(unix_now(), Ordering::Relaxed);
+    }
+
+    /// Seconds since the last beat.
+    pub fn seconds_since(&self) -> u64 {
+        unix_now().saturating_sub(self.0.load(Ordering::Relaxed))
+    }
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Liveness of one background task, as reported by `GET /api/health/tasks`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskLiveness {
+    pub seconds_since_heartbeat: u64,
+    pub stale: bool,
+}
+
+/// Heartbeats of every registered background task, keyed by task name.
+#[derive(Default)]
+pub struct HeartbeatRegistry {
+    heartbeats: RwLock<HashMap<&'static str, Heartbeat>>,
+}
+
+impl HeartbeatRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a task and returns the heartbeat it should update.
+    pub fn register(&self, name: &'static str) -> Heartbeat {
+        let heartbeat = Heartbeat::new();
+        if let Ok(mut heartbeats) = self.heartbeats.write() {
+            heartbeats.insert(name, heartbeat.clone());
+        }
+        heartbeat
+    }
+
+    /// Reports every task's liveness; tasks silent for `stale_after` or longer are stale.
+    pub fn liveness(&self, stale_after: Duration) -> HashMap<&'static str, TaskLiveness> {
+        let Ok(heartbeats) = self.heartbeats.read() else {
+            return HashMap::new();
+        };
+        heartbeats
+            .iter()
+            .map(|(name, heartbeat)| {
+                let seconds = heartbeat.seconds_since();
+                (*name, TaskLiveness {
+                    seconds_since_heartbeat: seconds,
+                    stale: seconds >= stale_after.as_secs(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// A named set of background tasks stopped together on shutdown.
+pub struct TaskSupervisor {
+    tasks: JoinSet<&'static str>,
+    running: HashSet<&'static str>,
+    token: CancellationToken,
+}
+
+impl TaskSupervisor {
+    /// Creates an empty supervisor with a fresh cancellation token.
+    pub fn new() -> Self {
+        Self {
+            tasks: JoinSet::new(),
+            running: HashSet::new(),
+            token: CancellationToken::new(),
+        }
+    }
+
+    /// The token tasks should watch to know when to stop.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Spawns a task under `name`. Names identify tasks in shutdown logs and must be unique.
+    pub fn spawn<F>(&mut self, name: &'static str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.running.insert(name);
+        self.tasks.spawn(async move {
+            task.await;
+            name
+        });
+    }
+
+    /// Cancels every task and waits up to `timeout` for them to finish. Tasks still
+    /// running at the deadline are logged and aborted.
+    pub async fn shutdown(mut self, timeout: Duration) {
+        info!("Stopping {} background task(s)...", self.running.len());
+        self.token.cancel();
+
+        let deadline = tokio::time::sleep(timeout);
+        tokio::pin!(deadline);
+
+        while !self.running.is_empty() {
+            tokio::select! {
+                _ = &mut deadline => {
+                    let mut stuck: Vec<&str> = self.running.iter().copied().collect();
+                    stuck.sort_unstable();
+                    warn!("Background tasks did not stop within {:?}, aborting: {:?}", timeout, stuck);
+                    self.tasks.shutdown().await;
+                    return;
+                }
+                joined = self.tasks.join_next() => match joined {
+                    Some(Ok(name)) => {
+                        self.running.remove(name);
+                        info!("Background task '{}' stopped.", name);
+                    }
+                    // A panicked task can't report its name; the deadline log covers it.
+                    Some(Err(e)) => error!("Background task failed to join: {}", e),
+                    None => break,
+                },
+            }
+        }
+
+        info!("All background tasks stopped.");
+    }
+}
+
+impl Default for TaskSupervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn shutdown_cancels_and_awaits_tasks() {
+        let mut supervisor = TaskSupervisor::new();
+        let token = supervisor.token();
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel();
+        supervisor.spawn("waits-for-cancel", async move {
+            token.cancelled().await;
+            let _ = done_tx.send(());
+        });
+
+        supervisor.shutdown(Duration::from_secs(1)).await;
+        assert!(done_rx.await.is_ok());
+    }
+
+    #[test]
+    fn fresh_heartbeat_is_not_stale() {
+        let registry = HeartbeatRegistry::new();
+        registry.register("fresh").beat();
+
+        let liveness = registry.liveness(Duration::from_secs(30));
+        assert!(!liveness["fresh"].stale);
+        assert!(registry.liveness(Duration::ZERO)["fresh"].stale);
+    }
+
+    #[tokio::test]
+    async fn shutdown_gives_up_on_stuck_tasks() {
+        let mut supervisor = TaskSupervisor::new();
+        supervisor.spawn("ignores-cancel", std::future::pending());
+
+        let started = std::time::Instant::now();
+        supervisor.shutdown(Duration::from_millis(50)).await;
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
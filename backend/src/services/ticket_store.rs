@@ -0,0 +1,127 @@
+// File Path: backend/src/services/ticket_store.rs
+
+//! # WebSocket Ticket Store
+//!
+//! Short-lived, single-use tickets that clients obtain from `POST /api/ws-ticket`
+//! and present as `/ws?ticket=...`, so long-lived credentials never appear in a
+//! WebSocket URL (and therefore never in access logs).
+
+use std::{
+    collections::HashMap,
+    env,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// How long an issued ticket stays valid.
+pub const TICKET_TTL: Duration = Duration::from_secs(30);
+
+/// An issued, not yet consumed ticket.
+#[derive(Debug, Clone)]
+pub struct Ticket {
+    pub expires_at: Instant,
+    /// Whether the connection opened with this ticket gets admin privileges.
+    pub is_admin: bool,
+}
+
+/// In-memory map of outstanding tickets, keyed by ticket id.
+pub struct TicketStore {
+    tickets: Mutex<HashMap<String, Ticket>>,
+    ttl: Duration,
+}
+
+impl TicketStore {
+    /// Creates an empty store using the default 30 second TTL.
+    pub fn new() -> Self {
+        Self::with_ttl(TICKET_TTL)
+    }
+
+    /// Creates an empty store with a custom TTL.
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            tickets: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// Issues a new ticket and returns its id. Expired tickets are pruned on the way.
+    pub async fn issue(&self, is_admin: bool) -> String {
+        let now = Instant::now();
+        let id = Uuid::new_v4().to_string();
+
+        let mut tickets = self.tickets.lock().await;
+        tickets.retain(|_, ticket| ticket.expires_at > now);
+        tickets.insert(id.clone(), Ticket { expires_at: now + self.ttl, is_admin });
+        id
+    }
+
+    /// Consumes a ticket. Returns `None` if it is unknown, already used, or expired.
+    pub async fn consume(&self, id: &str) -> Option<Ticket> {
+        let ticket = self.tickets.lock().await.remove(id)?;
+        (ticket.expires_at > Instant::now()).then_some(ticket)
+    }
+
+    /// The TTL applied to newly issued tickets.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+}
+
+impl Default for TicketStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `/ws` rejects upgrades without a valid ticket (`WS_REQUIRE_TICKET`, default false).
+pub fn tickets_required() -> bool {
+    env::var("WS_REQUIRE_TICKET")
+        .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+        .unwrap_or(false)
+}
+
+/// Returns true if `token` equals the (non-empty) value of environment variable `var`.
+pub fn env_token_matches(var: &str, token: &str) -> bool {
+    match env::var(var) {
+        Ok(expected) => !expected.is_empty() && constant_time_eq(expected.as_bytes(), token.as_bytes()),
+        Err(_) => false,
+    }
+}
+
+/// Compares two secrets in time that depends only on their length, so a caller can't
+/// recover a token byte by byte from response timings.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_time_eq_compares_whole_tokens() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secre"));
+        assert!(constant_time_eq(b"", b""));
+    }
+
+    #[tokio::test]
+    async fn ticket_is_single_use() {
+        let store = TicketStore::new();
+        let id = store.issue(true).await;
+
+        let ticket = store.consume(&id).await.expect("fresh ticket is valid");
+        assert!(ticket.is_admin);
+        assert!(store.consume(&id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn expired_ticket_is_rejected() {
+        let store = TicketStore::with_ttl(Duration::ZERO);
+        let id = store.issue(false).await;
+
+        assert!(store.consume(&id).await.is_none());
+    }
+}
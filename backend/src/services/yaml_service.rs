@@ -7,23 +7,200 @@
 // SECTION: Imports and Struct Definition
 // ====================================================
 
-use crate::models::{ApiError, ApiResult};
+use crate::services::{
+    file_reader::ResilientReader,
+    file_store::{FileStore, LocalFileStore},
+};
+use crate::util::merge::{deep_merge, ArrayStrategy};
+use crate::models::{
+    ApiError, ApiResult, FileValidationReport, FileValidationResult, SchemaCollision, SchemaDataFile,
+    SchemaHealth, SchemaLoadFailure, ValidationIssue, ValidationSummary,
+};
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::{
     collections::HashMap,
-    path::{Path, PathBuf},
-    // Added for serde_yaml
-    borrow::Borrow,
+    env,
+    path::{Component, Path, PathBuf},
+    io,
+    sync::{Arc, OnceLock, RwLock},
+    time::Instant,
 };
-use tokio::fs;
 use tracing::{info, warn};
-use jsonschema::{Draft, JSONSchema};
+use jsonschema::{error::ValidationErrorKind, Draft, JSONSchema, ValidationError};
+use metrics::{counter, histogram};
 use serde_yaml; // Explicitly included for serde_yaml::from_str
 
 pub struct YamlService {
     pub schema_dir: PathBuf, // Made public for potential testing/debugging
     pub data_dir: PathBuf,   // Made public
+    /// Compiled schemas, swapped wholesale by `reload_schemas` (see `schema_set`).
+    schema_set: RwLock<Arc<SchemaSet>>,
+    /// Canonical directories data files may be read from (data_dir + DATA_DIR_ALLOWLIST).
+    pub allowed_dirs: Vec<PathBuf>,
+    /// Optional bundle document whose `$defs` entries are loaded as `<bundle>:<def>` schemas.
+    pub schema_bundle: Option<PathBuf>,
+    /// How schema violations in served data are handled (`VALIDATION_MODE`).
+    pub validation_mode: ValidationMode,
+    /// Compile schemas on first use instead of at load (`LAZY_SCHEMAS`).
+    pub lazy_schemas: bool,
+    /// Backend every schema and data file is read from (local filesystem by default).
+    store: Arc<dyn FileStore>,
+    /// Reads data files from `store`, retrying transient IO errors (see `file_reader`).
+    pub file_reader: ResilientReader,
+    /// Serializes `save_yaml_data`'s check-then-write so two writers can't both pass the ETag check.
+    write_lock: tokio::sync::Mutex<()>,
+}
+
+/// One generation of compiled schemas and the health report of the load that built it.
+#[derive(Default)]
+pub struct SchemaSet {
     pub schemas: HashMap<String, JSONSchema>,
+    /// Source document of every compiled schema, as read (bundle entries: their `$defs` value).
+    pub sources: HashMap<String, Value>,
+    /// Schema files that failed to load or collided with another file's schema name.
+    pub health: SchemaHealth,
+    /// Schemas recorded but not yet compiled (`LAZY_SCHEMAS=true`).
+    lazy: HashMap<String, LazySchema>,
+    /// Every schema document by URI, kept so lazy schemas can resolve `$ref`s when compiled.
+    documents: Vec<(String, String, Value)>,
+}
+
+/// A schema compiled on first use; a compile error is kept and reported on every use.
+struct LazySchema {
+    uri: String,
+    compiled: OnceLock<Result<JSONSchema, String>>,
+}
+
+impl SchemaSet {
+    /// The compiled schema, compiling a lazy one on first use. A lazy schema that fails to
+    /// compile is a `ValidationError`.
+    pub fn try_get(&self, schema_name: &str) -> ApiResult<Option<&JSONSchema>> {
+        if let Some(schema) = self.schemas.get(schema_name) {
+            return Ok(Some(schema));
+        }
+        let Some(lazy) = self.lazy.get(schema_name) else {
+            return Ok(None);
+        };
+
+        let compiled = lazy.compiled.get_or_init(|| {
+            let document = self.sources.get(schema_name).cloned().unwrap_or(Value::Null);
+            let result = compile_schema(&lazy.uri, &document, &self.documents).map_err(|e| e.to_string());
+            match &result {
+                Ok(_) => info!("Compiled schema on first use: {} from {}", schema_name, lazy.uri),
+                Err(e) => warn!("Failed to compile schema {} on first use: {}", schema_name, e),
+            }
+            result
+        });
+        compiled.as_ref().map(Some).map_err(|e| {
+            ApiError::ValidationError(format!("Schema '{}' failed to compile: {}", schema_name, e))
+        })
+    }
+
+    pub fn contains(&self, schema_name: &str) -> bool {
+        self.schemas.contains_key(schema_name) || self.lazy.contains_key(schema_name)
+    }
+
+    /// Whether `schema_name` has been compiled (always true outside `LAZY_SCHEMAS` mode).
+    #[cfg(test)]
+    pub fn is_compiled(&self, schema_name: &str) -> bool {
+        self.schemas.contains_key(schema_name)
+            || self
+                .lazy
+                .get(schema_name)
+                .is_some_and(|lazy| matches!(lazy.compiled.get(), Some(Ok(_))))
+    }
+
+    /// Names of every available schema, compiled or not.
+    pub fn names(&self) -> Vec<String> {
+        self.schemas.keys().chain(self.lazy.keys()).cloned().collect()
+    }
+}
+
+/// Handling of schema violations when serving data (`VALIDATION_MODE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValidationMode {
+    /// Violations are a 400 `ValidationError` (default).
+    #[default]
+    Reject,
+    /// Violations are logged and served alongside the data (a `_validation_warnings`
+    /// field on object data; `"valid": false` plus `warnings` from the validate endpoint).
+    Warn,
+    /// Data is served without validation.
+    Off,
+}
+
+impl ValidationMode {
+    /// Reads `VALIDATION_MODE` (`reject`, `warn`, or `off`); unknown values fall back to `reject`.
+    pub fn from_env() -> Self {
+        match env::var("VALIDATION_MODE").map(|v| v.to_ascii_lowercase()).as_deref() {
+            Ok("warn") => Self::Warn,
+            Ok("off") => Self::Off,
+            Ok("reject") | Err(_) => Self::Reject,
+            Ok(other) => {
+                warn!("Unknown VALIDATION_MODE '{}', using 'reject'", other);
+                Self::Reject
+            }
+        }
+    }
+}
+
+/// Field added to object data served with tolerated violations (`VALIDATION_MODE=warn`).
+pub const VALIDATION_WARNINGS_FIELD: &str = "_validation_warnings";
+
+/// A validated data file: the parsed data, the violations tolerated under
+/// `VALIDATION_MODE=warn`, and the ETag of the bytes that were parsed.
+#[derive(Debug)]
+pub struct LoadedData {
+    pub data: Value,
+    pub warnings: Vec<String>,
+    pub etag: String,
+}
+
+/// Supported data file formats. All parse into a unified `serde_json::Value`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Yaml,
+    Json,
+    Toml,
+}
+
+impl DataFormat {
+    /// Extensions tried, in order, when resolving the default file for a schema.
+    const DEFAULT_EXTENSIONS: [&'static str; 4] = ["yaml", "yml", "json", "toml"];
+
+    /// Parses a format name (as used by the `format` query parameter).
+    pub fn from_name(name: &str) -> ApiResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "json" => Ok(Self::Json),
+            "toml" => Ok(Self::Toml),
+            other => Err(ApiError::BadRequest(format!(
+                "Unsupported data format '{}' (expected yaml, json, or toml)",
+                other
+            ))),
+        }
+    }
+
+    /// Detects the format from a file extension, if it is a recognized one.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        Self::from_name(ext).ok()
+    }
+
+    /// Parses file content in this format.
+    pub fn parse(self, content: &str) -> ApiResult<Value> {
+        match self {
+            Self::Yaml => serde_yaml::from_str(content)
+                .map_err(|e| ApiError::YamlParseError(e.to_string())),
+            Self::Json => serde_json::from_str(content)
+                .map_err(|e| ApiError::DeserializationError(format!("Invalid JSON data: {}", e))),
+            Self::Toml => toml::from_str(content)
+                .map_err(|e| ApiError::DeserializationError(format!("Invalid TOML data: {}", e))),
+        }
+    }
 }
 
 // ====================================================
@@ -32,176 +209,2181 @@ pub struct YamlService {
 
 impl YamlService {
     pub async fn new(schema_dir: &str, data_dir: &str) -> ApiResult<Self> {
-        let schema_path = PathBuf::from(schema_dir);
-        let data_path = PathBuf::from(data_dir);
+        Self::new_with_dirs(schema_dir, data_dir).await
+    }
+
+    /// Creates a service from arbitrary schema/data directories (e.g. temp dirs in tests).
+    /// The schema bundle, if any, comes from `SCHEMA_BUNDLE`.
+    pub async fn new_with_dirs(
+        schema_dir: impl AsRef<Path>,
+        data_dir: impl AsRef<Path>,
+    ) -> ApiResult<Self> {
+        let bundle = schema_bundle_from_env(schema_dir.as_ref());
+        Self::new_with_bundle(schema_dir, data_dir, bundle).await
+    }
+
+    /// Like `new_with_dirs`, with an explicit schema bundle path.
+    pub async fn new_with_bundle(
+        schema_dir: impl AsRef<Path>,
+        data_dir: impl AsRef<Path>,
+        schema_bundle: Option<PathBuf>,
+    ) -> ApiResult<Self> {
+        Self::new_with_store(schema_dir, data_dir, schema_bundle, Arc::new(LocalFileStore)).await
+    }
+
+    /// Creates a service whose files all come from `store` (e.g. an `InMemoryFileStore`).
+    pub async fn new_with_store(
+        schema_dir: impl AsRef<Path>,
+        data_dir: impl AsRef<Path>,
+        schema_bundle: Option<PathBuf>,
+        store: Arc<dyn FileStore>,
+    ) -> ApiResult<Self> {
+        let schema_path = schema_dir.as_ref().to_path_buf();
+        let data_path = data_dir.as_ref().to_path_buf();
         
-        if !schema_path.exists() {
+        if !store.exists(&schema_path).await {
             return Err(ApiError::FileNotFound(format!(
                 "Schema directory not found: {}",
                 schema_path.display()
             )));
         }
 
-        if !data_path.exists() {
+        if !store.exists(&data_path).await {
             return Err(ApiError::FileNotFound(format!(
                 "Data directory not found: {}",
                 data_path.display()
             )));
         }
 
-        let mut service = Self {
+        let allowed_dirs = Self::build_allowlist(&data_path);
+
+        let service = Self {
             schema_dir: schema_path,
             data_dir: data_path,
-            schemas: HashMap::new(),
+            schema_set: RwLock::new(Arc::new(SchemaSet::default())),
+            allowed_dirs,
+            schema_bundle,
+            validation_mode: ValidationMode::from_env(),
+            lazy_schemas: lazy_schemas(),
+            file_reader: ResilientReader::from_env(store.clone()),
+            store,
+            write_lock: tokio::sync::Mutex::new(()),
         };
 
-        service.load_schemas().await?;
+        service.reload_schemas().await?;
         Ok(service)
     }
 
-    async fn load_schemas(&mut self) -> ApiResult<()> {
-        info!("Loading schemas from: {}", self.schema_dir.display());
-        
-        let mut entries = fs::read_dir(&self.schema_dir)
-            .await
-            .map_err(ApiError::IoError)?;
-
-        while let Some(entry) = entries.next_entry().await.map_err(ApiError::IoError)? {
-            let path = entry.path();
-            if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                    // Extract base name by removing ".schema" suffix if present
-                    let schema_name = if stem.ends_with(".schema") {
-                        stem.trim_end_matches(".schema").to_string()
-                    } else {
-                        stem.to_string()
-                    };
-                    
-                    match self.load_schema(&path).await {
-                        Ok(schema) => {
-                            // Clone schema_name to avoid borrow after move
-                            let schema_name_clone = schema_name.clone();
-                            self.schemas.insert(schema_name, schema);
-                            info!("Loaded schema: {} from {}", schema_name_clone, path.display());
-                        }
-                        Err(e) => {
-                            warn!("Failed to load schema {}: {}", schema_name, e);
-                        }
+    /// The current schema generation. Callers hold the `Arc` for as long as they need
+    /// it; a concurrent reload swaps in a new generation without waiting for them.
+    pub fn schema_set(&self) -> Arc<SchemaSet> {
+        self.schema_set.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Reloads every schema from disk. The new set is built entirely off to the side and
+    /// swapped in with one brief write lock, so validations never wait on a reload. On
+    /// failure (e.g. a collision with `STRICT_SCHEMAS=true`) the current set stays active.
+    pub async fn reload_schemas(&self) -> ApiResult<SchemaHealth> {
+        let set = self.load_schemas().await?;
+        let health = set.health.clone();
+        *self.schema_set.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(set);
+        Ok(health)
+    }
+
+    /// Builds the list of canonical directories data files may be read from: `data_dir`
+    /// itself plus any comma-separated extra roots in the `DATA_DIR_ALLOWLIST` env var.
+    fn build_allowlist(data_path: &Path) -> Vec<PathBuf> {
+        let mut allowed = vec![canonical_or_lexical(data_path)];
+
+        if let Ok(extra) = env::var("DATA_DIR_ALLOWLIST") {
+            for dir in extra.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+                match std::fs::canonicalize(dir) {
+                    Ok(canonical) => {
+                        info!("Allowing data reads from: {}", canonical.display());
+                        allowed.push(canonical);
                     }
+                    Err(e) => warn!("Ignoring DATA_DIR_ALLOWLIST entry {}: {}", dir, e),
                 }
             }
         }
 
-        Ok(())
+        allowed
     }
 
-    async fn load_schema(&self, schema_path: &Path) -> ApiResult<JSONSchema> {
-        let content = fs::read_to_string(schema_path)
-            .await
-            .map_err(ApiError::IoError)?;
+    /// Loads every schema under `schema_dir`.
+    ///
+    /// All documents are read first and registered with the `$ref` resolver under
+    /// their `file://` URI, so a ref such as `common.schema.json#/definitions/Ipv4`
+    /// resolves against a sibling file, and `#/...` refs inside that file against it.
+    ///
+    /// When several files map to the same schema name (e.g. `nav.json` and
+    /// `nav.schema.json`), the `.schema.json` file (else the first by path) is used,
+    /// a warning names every file, and the collision is recorded in the set's `health`.
+    /// With `STRICT_SCHEMAS=true` a collision fails the load (startup or reload) instead.
+    ///
+    /// A configured schema bundle is loaded alongside the per-file schemas.
+    ///
+    /// With `LAZY_SCHEMAS=true` the per-file schemas are only read and recorded here and
+    /// compiled on first use (see `SchemaSet::try_get`); their compile errors then
+    /// surface at that point instead of in `health`. Bundle entries are always compiled.
+    async fn load_schemas(&self) -> ApiResult<SchemaSet> {
+        info!("Loading schemas from: {}", self.schema_dir.display());
+
+        let mut paths = self.discover_schema_files().await?;
+        paths.sort();
+
+        let mut set = SchemaSet::default();
+        let mut documents: Vec<(String, String, Value)> = Vec::new();
+        for path in paths {
+            let (Some(schema_name), Some(uri)) = (self.schema_name_for(&path), self.schema_uri_for(&path)) else {
+                continue;
+            };
+
+            match read_schema_document(self.store.as_ref(), &path).await {
+                Ok(document) => documents.push((schema_name, uri, document)),
+                Err(e) => {
+                    warn!("Failed to load schema {}: {}", schema_name, e);
+                    set.health.failed.push(SchemaLoadFailure {
+                        name: schema_name,
+                        file: uri,
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        set.health.collisions = find_schema_collisions(&documents);
+        for collision in &set.health.collisions {
+            warn!(
+                "Schema name '{}' is defined by multiple files {:?}; using {}",
+                collision.name, collision.files, collision.used
+            );
+        }
+        if strict_schemas() && !set.health.collisions.is_empty() {
+            let names: Vec<&str> = set.health.collisions.iter().map(|c| c.name.as_str()).collect();
+            return Err(ApiError::ValidationError(format!(
+                "Duplicate schema names with STRICT_SCHEMAS=true: {}",
+                names.join(", ")
+            )));
+        }
+
+        // Built-in schemas are available unless schema_dir provides its own version.
+        if !documents.iter().any(|(name, _, _)| name == JOB_EVENT_SCHEMA_NAME) {
+            match serde_json::from_str(JOB_EVENT_SCHEMA) {
+                Ok(document) => documents.push((
+                    JOB_EVENT_SCHEMA_NAME.to_string(),
+                    file_uri(&self.schema_dir.join(format!("{}.schema.json", JOB_EVENT_SCHEMA_NAME)))
+                        .unwrap_or_default(),
+                    document,
+                )),
+                Err(e) => warn!("Built-in {} schema is invalid: {}", JOB_EVENT_SCHEMA_NAME, e),
+            }
+        }
+
+        let lazy = self.lazy_schemas;
+        for (schema_name, uri, document) in &documents {
+            // Shadowed files stay registered for `$ref` resolution but aren't compiled.
+            let shadowed = set
+                .health
+                .collisions
+                .iter()
+                .any(|c| &c.name == schema_name && &c.used != uri);
+            if shadowed {
+                continue;
+            }
+
+            if lazy {
+                set.sources.insert(schema_name.clone(), document.clone());
+                set.lazy.insert(schema_name.clone(), LazySchema { uri: uri.clone(), compiled: OnceLock::new() });
+                continue;
+            }
+
+            match compile_schema(uri, document, &documents) {
+                Ok(schema) => {
+                    info!("Loaded schema: {} from {}", schema_name, uri);
+                    set.schemas.insert(schema_name.clone(), schema);
+                    set.sources.insert(schema_name.clone(), document.clone());
+                }
+                Err(e) => {
+                    warn!("Failed to load schema {}: {}", schema_name, e);
+                    set.health.failed.push(SchemaLoadFailure {
+                        name: schema_name.clone(),
+                        file: uri.clone(),
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        if let Some(bundle_path) = self.schema_bundle.clone() {
+            self.load_schema_bundle(&bundle_path, &mut documents, &mut set).await;
+        }
+
+        set.health.loaded = set.names();
+        set.health.loaded.sort();
+        if lazy {
+            info!("Recorded {} schema(s) for compilation on first use (LAZY_SCHEMAS=true)", set.lazy.len());
+            set.documents = documents;
+        }
+        Ok(set)
+    }
+
+    /// Registers each top-level `$defs` entry of a bundle document as its own schema,
+    /// named `<bundle>:<def>` (e.g. `bundle:navigation` for `bundle.schema.json`).
+    ///
+    /// Each entry is compiled as a `$ref` into the registered bundle document, so refs
+    /// between entries (`#/$defs/...`) resolve within the bundle. Failures land in `set.health`.
+    async fn load_schema_bundle(
+        &self,
+        bundle_path: &Path,
+        documents: &mut Vec<(String, String, Value)>,
+        set: &mut SchemaSet,
+    ) {
+        let file_name = bundle_path.file_name().and_then(|n| n.to_str()).unwrap_or("bundle.json");
+        let bundle_name = file_name
+            .trim_end_matches(".json")
+            .trim_end_matches(".schema")
+            .to_string();
+        // Inside schema_dir the bundle is already registered under its relative path.
+        let uri = file_uri(bundle_path).unwrap_or_else(|| file_name.to_string());
+
+        let document = match read_schema_document(self.store.as_ref(), bundle_path).await {
+            Ok(document) => document,
+            Err(e) => {
+                warn!("Failed to load schema bundle {}: {}", bundle_path.display(), e);
+                set.health.failed.push(SchemaLoadFailure {
+                    name: bundle_name,
+                    file: bundle_path.display().to_string(),
+                    error: e.to_string(),
+                });
+                return;
+            }
+        };
+
+        let defs: Vec<String> = document
+            .get("$defs")
+            .and_then(Value::as_object)
+            .map(|defs| defs.keys().cloned().collect())
+            .unwrap_or_default();
+        if defs.is_empty() {
+            warn!("Schema bundle {} has no $defs entries", bundle_path.display());
+        }
+        if !documents.iter().any(|(_, existing, _)| *existing == uri) {
+            documents.push((bundle_name.clone(), uri.clone(), document.clone()));
+        }
+
+        for def in defs {
+            let schema_name = format!("{}:{}", bundle_name, def);
+            let pointer = def.replace('~', "~0").replace('/', "~1");
+            let entry = serde_json::json!({ "$ref": format!("{}#/$defs/{}", uri, pointer) });
+            // The stub needs its own URI; under the bundle's, the ref would point back at it.
+            let entry_uri = format!("{}?def={}", uri, pointer);
+
+            match compile_schema(&entry_uri, &entry, documents) {
+                Ok(schema) => {
+                    info!("Loaded schema: {} from bundle {}", schema_name, uri);
+                    let source = documents
+                        .iter()
+                        .find(|(_, existing, _)| *existing == uri)
+                        .and_then(|(_, _, bundle)| bundle.get("$defs")?.get(&def).cloned())
+                        .unwrap_or(Value::Null);
+                    set.sources.insert(schema_name.clone(), source);
+                    set.schemas.insert(schema_name, schema);
+                }
+                Err(e) => {
+                    warn!("Failed to load schema {}: {}", schema_name, e);
+                    set.health.failed.push(SchemaLoadFailure {
+                        name: schema_name,
+                        file: uri.clone(),
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Walks `schema_dir` (including nested domain folders) and returns every `*.json` file.
+    async fn discover_schema_files(&self) -> ApiResult<Vec<PathBuf>> {
+        collect_files(self.store.as_ref(), &self.schema_dir, &["json"]).await
+    }
+
+    /// Derives the schema name from a schema file's path relative to `schema_dir`.
+    ///
+    /// `navigation.schema.json` -> `navigation`, `network/bgp.schema.json` -> `network/bgp`.
+    fn schema_name_for(&self, path: &Path) -> Option<String> {
+        let name = relative_stem(&self.schema_dir, path)?;
+        // Extract base name by removing ".schema" suffix if present
+        Some(name.strip_suffix(".schema").map(str::to_string).unwrap_or(name))
+    }
+
+    /// Base URI a file under `schema_dir` is registered under for `$ref` resolution.
+    fn schema_uri_for(&self, path: &Path) -> Option<String> {
+        path.strip_prefix(&self.schema_dir).ok()?;
+        file_uri(path)
+    }
+}
+
+/// Name of the built-in schema describing events published on job channels.
+pub const JOB_EVENT_SCHEMA_NAME: &str = "job_event";
+
+/// Prefix of per-job-type event schemas: events with `"job_type": "backup"` validate
+/// against `event_backup` when such a schema is loaded, otherwise against `job_event`.
+pub const JOB_EVENT_TYPE_SCHEMA_PREFIX: &str = "event_";
+
+/// The built-in `job_event` schema (overridable by `job_event.schema.json` in schema_dir).
+const JOB_EVENT_SCHEMA: &str = include_str!("schemas/job_event.schema.json");
+
+/// The `file://` URI of `path` (made absolute first), under which a schema document is
+/// registered for `$ref` resolution. jsonschema ignores `json-schema:` base URIs, which
+/// would make `#/...` refs inside a referenced document resolve against the wrong one.
+fn file_uri(path: &Path) -> Option<String> {
+    let absolute = std::path::absolute(path).ok()?;
+    url::Url::from_file_path(absolute).ok().map(String::from)
+}
+
+/// Resolves `SCHEMA_BUNDLE` (relative paths are taken from `schema_dir`), if set.
+fn schema_bundle_from_env(schema_dir: &Path) -> Option<PathBuf> {
+    let raw = env::var("SCHEMA_BUNDLE").ok().filter(|v| !v.trim().is_empty())?;
+    Some(schema_dir.join(raw.trim()))
+}
+
+/// How overlays combine arrays (`OVERLAY_ARRAY_STRATEGY`): `merge-by-key` (default;
+/// arrays of objects with an `id` merge by id, other arrays are replaced), `concat`,
+/// or `replace`.
+fn overlay_array_strategy() -> ArrayStrategy {
+    match env::var("OVERLAY_ARRAY_STRATEGY").map(|v| v.to_ascii_lowercase()).as_deref() {
+        Ok("concat") => ArrayStrategy::Concat,
+        Ok("replace") => ArrayStrategy::Replace,
+        _ => ArrayStrategy::MergeByKey("id".to_string()),
+    }
+}
+
+/// Whether schemas are compiled on first use instead of at load (`LAZY_SCHEMAS`, default false).
+fn lazy_schemas() -> bool {
+    env::var("LAZY_SCHEMAS")
+        .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+        .unwrap_or(false)
+}
+
+/// Whether duplicate schema names fail startup (`STRICT_SCHEMAS`, default false).
+fn strict_schemas() -> bool {
+    env::var("STRICT_SCHEMAS")
+        .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+        .unwrap_or(false)
+}
+
+/// Finds schema names defined by more than one document (given sorted by path) and
+/// picks the one to use: the `.schema.json` file if there is one, else the first.
+fn find_schema_collisions(documents: &[(String, String, Value)]) -> Vec<SchemaCollision> {
+    let mut by_name: Vec<(&str, Vec<&str>)> = Vec::new();
+    for (name, uri, _) in documents {
+        match by_name.iter_mut().find(|(n, _)| *n == name.as_str()) {
+            Some((_, uris)) => uris.push(uri.as_str()),
+            None => by_name.push((name.as_str(), vec![uri.as_str()])),
+        }
+    }
 
-        let schema_value: Value = serde_json::from_str(&content)
-            .map_err(|e| ApiError::ValidationError(format!("Invalid JSON schema: {}", e)))?;
+    by_name
+        .into_iter()
+        .filter(|(_, uris)| uris.len() > 1)
+        .map(|(name, uris)| {
+            let used = uris
+                .iter()
+                .find(|uri| uri.ends_with(".schema.json"))
+                .unwrap_or(&uris[0])
+                .to_string();
+            SchemaCollision {
+                name: name.to_string(),
+                files: uris.iter().map(|uri| uri.to_string()).collect(),
+                used,
+            }
+        })
+        .collect()
+}
+
+/// Reads and parses a schema file.
+async fn read_schema_document(store: &dyn FileStore, schema_path: &Path) -> ApiResult<Value> {
+    let content = store
+        .read_to_string(schema_path)
+        .await
+        .map_err(ApiError::IoError)?;
+
+    serde_json::from_str(&content)
+        .map_err(|e| ApiError::ValidationError(format!("Invalid JSON schema: {}", e)))
+}
+
+/// Compiles the schema registered at `uri`, with every loaded document available
+/// to the resolver. A schema without its own `$id` gets `uri` as its base, so
+/// relative refs resolve against its location in `schema_dir`.
+fn compile_schema(uri: &str, document: &Value, documents: &[(String, String, Value)]) -> ApiResult<JSONSchema> {
+    let mut root = document.clone();
+    if let Value::Object(map) = &mut root {
+        map.entry("$id").or_insert_with(|| Value::String(uri.to_string()));
+    }
+
+    let mut options = JSONSchema::options();
+    options
+        .with_draft(Draft::Draft7)
+        .should_validate_formats(true)
+        .with_format("cidr", is_cidr)
+        .with_format("asn", is_asn)
+        .with_format("interface-name", is_interface_name);
+    for (_, other_uri, other) in documents {
+        options.with_document(other_uri.clone(), other.clone());
+    }
 
-        let schema = JSONSchema::options()
-            .with_draft(Draft::Draft7)
-            .compile(&schema_value)
-            .map_err(|e| ApiError::ValidationError(format!("Schema compilation failed: {}", e)))?;
+    options
+        .compile(&root)
+        .map_err(|e| ApiError::ValidationError(format!("Schema compilation failed: {}", e)))
+}
 
-        Ok(schema)
+/// `format: cidr` — an IPv4 or IPv6 prefix such as `10.0.0.0/24` or `2001:db8::/32`.
+fn is_cidr(value: &str) -> bool {
+    let Some((address, prefix)) = value.split_once('/') else {
+        return false;
+    };
+    let Ok(prefix) = prefix.parse::<u8>() else {
+        return false;
+    };
+    match address.parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V4(_)) => prefix <= 32,
+        Ok(std::net::IpAddr::V6(_)) => prefix <= 128,
+        Err(_) => false,
     }
 }
 
+/// `format: asn` — a 32-bit autonomous system number, optionally prefixed with `AS`
+/// (`65001`, `AS65001`). Zero is reserved and rejected.
+fn is_asn(value: &str) -> bool {
+    let digits = value
+        .strip_prefix("AS")
+        .or_else(|| value.strip_prefix("as"))
+        .unwrap_or(value);
+    digits.chars().all(|c| c.is_ascii_digit())
+        && matches!(digits.parse::<u32>(), Ok(asn) if asn > 0)
+}
+
+/// `format: interface-name` — a device interface name such as `ge-0/0/0`, `xe-1/0/2.100`,
+/// `et-0/0/1:2` or `ae0`: starts with a letter, at most 64 characters of letters, digits
+/// and `-` `/` `.` `:` `_`, and does not end with a separator.
+fn is_interface_name(value: &str) -> bool {
+    const SEPARATORS: [char; 5] = ['-', '/', '.', ':', '_'];
+
+    value.len() <= 64
+        && value.starts_with(|c: char| c.is_ascii_alphabetic())
+        && !value.ends_with(SEPARATORS)
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || SEPARATORS.contains(&c))
+}
+
 // ====================================================
 // SECTION: YAML Data Handling (Content as provided)
 // ====================================================
 
 impl YamlService {
+    /// Loads a data file and validates it against `schema_name`.
+    ///
+    /// Returns `ApiError::NotFound` if `schema_name` isn't a loaded schema; use
+    /// `get_unvalidated_yaml_data` to read data deliberately without a schema.
     pub async fn get_yaml_data(
         &self,
         schema_name: &str,
         file_path: Option<&str>,
     ) -> ApiResult<Value> {
-        let yaml_path = self.resolve_yaml_path(schema_name, file_path)?;
+        self.get_yaml_data_with_override(schema_name, file_path, None, None, false).await
+    }
+
+    /// Like `get_yaml_data`, but validates against `schema_override` (when given) instead
+    /// of `schema_name`. The data file is still resolved from `schema_name`/`file_path`,
+    /// which lets a candidate schema be tested against the live data.
+    ///
+    /// `format` forces the parser when the file extension is ambiguous. With
+    /// `allow_additional`, `additionalProperties` violations are ignored and the unknown
+    /// fields are served as-is (for data that is ahead of its schema).
+    pub async fn get_yaml_data_with_override(
+        &self,
+        schema_name: &str,
+        file_path: Option<&str>,
+        schema_override: Option<&str>,
+        format: Option<DataFormat>,
+        allow_additional: bool,
+    ) -> ApiResult<Value> {
+        let LoadedData { mut data, warnings, .. } = self
+            .load_yaml_data(schema_name, file_path, schema_override, format, allow_additional)
+            .await?;
+        attach_validation_warnings(&mut data, warnings);
+        Ok(data)
+    }
+
+    /// Like `get_yaml_data_with_override`, but returns the violations tolerated under
+    /// `VALIDATION_MODE=warn` alongside the data instead of inside it, whatever its root
+    /// type, together with the ETag of the file content that was parsed.
+    pub async fn load_yaml_data(
+        &self,
+        schema_name: &str,
+        file_path: Option<&str>,
+        schema_override: Option<&str>,
+        format: Option<DataFormat>,
+        allow_additional: bool,
+    ) -> ApiResult<LoadedData> {
+        let validation_name = schema_override.unwrap_or(schema_name);
+        let schemas = self.schema_set();
+        let schema = schemas.try_get(validation_name)?.ok_or_else(|| match schema_override {
+            Some(name) => ApiError::NotFound(format!("Override schema '{}' not found", name)),
+            None => ApiError::NotFound(format!("Schema '{}' not found", schema_name)),
+        })?;
+
+        let (data, etag) = self.read_yaml_tagged(schema_name, file_path, format).await?;
+
+        // Validate against schema (or tolerate/skip per VALIDATION_MODE)
+        let warnings = self.enforce_validation(validation_name, schema, &data, allow_additional)?;
+
+        Ok(LoadedData { data, warnings, etag })
+    }
+
+    /// Loads a data file without schema validation (explicit `?validate=false`).
+    pub async fn get_unvalidated_yaml_data(
+        &self,
+        schema_name: &str,
+        file_path: Option<&str>,
+    ) -> ApiResult<Value> {
+        self.read_yaml(schema_name, file_path, None).await
+    }
+
+    /// Loads the base data for `schema_name`, deep-merges `overlay_file` on top of it
+    /// (arrays combine per `OVERLAY_ARRAY_STRATEGY`), and validates the merged result
+    /// unless `validate` is false.
+    ///
+    /// `format` forces the parser for the base file, as in `get_yaml_data_with_override`.
+    /// A missing overlay file is not an error: the base data is returned.
+    pub async fn get_merged_yaml_data(
+        &self,
+        schema_name: &str,
+        overlay_file: &str,
+        schema_override: Option<&str>,
+        format: Option<DataFormat>,
+        allow_additional: bool,
+        validate: bool,
+    ) -> ApiResult<Value> {
+        let overlay_path = self.resolve_yaml_path(schema_name, Some(overlay_file)).await?;
+        if !self.store.exists(&overlay_path).await {
+            info!("No overlay {} found, serving base {} data", overlay_file, schema_name);
+            if !validate {
+                return self.read_yaml(schema_name, None, format).await;
+            }
+            return self
+                .get_yaml_data_with_override(schema_name, None, schema_override, format, allow_additional)
+                .await;
+        }
+
+        let mut merged = self.read_yaml(schema_name, None, format).await?;
+        let overlay = self.read_yaml(schema_name, Some(overlay_file), None).await?;
+        deep_merge(&mut merged, &overlay, &overlay_array_strategy());
+        if !validate {
+            return Ok(merged);
+        }
+
+        let validation_name = schema_override.unwrap_or(schema_name);
+        let schemas = self.schema_set();
+        let schema = schemas.try_get(validation_name)?.ok_or_else(|| {
+            ApiError::NotFound(format!("Schema '{}' not found", validation_name))
+        })?;
+        let warnings = self.enforce_validation(validation_name, schema, &merged, allow_additional)?;
+        attach_validation_warnings(&mut merged, warnings);
+        Ok(merged)
+    }
+
+    /// Validates served data according to `validation_mode`: rejects violations, or logs
+    /// and returns them, or skips. The returned violations are empty unless in warn mode.
+    fn enforce_validation(
+        &self,
+        schema_name: &str,
+        schema: &JSONSchema,
+        data: &Value,
+        allow_additional: bool,
+    ) -> ApiResult<Vec<String>> {
+        match self.validation_mode {
+            ValidationMode::Reject => validate_with_metrics(schema_name, schema, data, allow_additional).map(|()| Vec::new()),
+            ValidationMode::Off => Ok(Vec::new()),
+            ValidationMode::Warn => {
+                let violations = validation_errors_with_metrics(schema_name, schema, data, allow_additional);
+                if !violations.is_empty() {
+                    warn!(
+                        "Serving data with {} violation(s) of schema '{}' (VALIDATION_MODE=warn): {:?}",
+                        violations.len(), schema_name, violations
+                    );
+                }
+                Ok(violations)
+            }
+        }
+    }
+
+    /// Name of the schema a job event validates against: `event_<job_type>` if loaded,
+    /// otherwise the generic `job_event` schema.
+    pub fn job_event_schema_name(&self, event: &Value) -> String {
+        event
+            .get("job_type")
+            .and_then(Value::as_str)
+            .map(|job_type| format!("{}{}", JOB_EVENT_TYPE_SCHEMA_PREFIX, job_type))
+            .filter(|name| self.schema_set().contains(name))
+            .unwrap_or_else(|| JOB_EVENT_SCHEMA_NAME.to_string())
+    }
+
+    /// Validates a job event against the schema for its `job_type` (see `job_event_schema_name`).
+    pub fn validate_job_event(&self, event: &Value) -> ApiResult<()> {
+        self.validate_value(&self.job_event_schema_name(event), event)
+    }
+
+    /// Validates an in-memory value against a loaded schema.
+    pub fn validate_value(&self, schema_name: &str, data: &Value) -> ApiResult<()> {
+        let schemas = self.schema_set();
+        let schema = schemas.try_get(schema_name)?.ok_or_else(|| {
+            ApiError::NotFound(format!("Schema '{}' not found", schema_name))
+        })?;
+        validate_with_metrics(schema_name, schema, data, false)
+    }
+
+    /// Returns a copy of `data` with the `default` values declared by `schema_name` filled
+    /// in for missing properties (see `fill_defaults`).
+    pub fn apply_defaults(&self, schema_name: &str, data: &Value) -> ApiResult<Value> {
+        let schemas = self.schema_set();
+        let source = schemas.sources.get(schema_name).ok_or_else(|| {
+            ApiError::NotFound(format!("Schema '{}' not found", schema_name))
+        })?;
+        let mut repaired = data.clone();
+        fill_defaults(source, source, &mut repaired, 0);
+        Ok(repaired)
+    }
+
+    /// Reads and parses a data file (YAML, JSON, or TOML) without validating it.
+    ///
+    /// The parser is chosen from `format` when given, otherwise from the file extension
+    /// (unknown extensions are parsed as YAML).
+    async fn read_yaml(
+        &self,
+        schema_name: &str,
+        file_path: Option<&str>,
+        format: Option<DataFormat>,
+    ) -> ApiResult<Value> {
+        Ok(self.read_yaml_tagged(schema_name, file_path, format).await?.0)
+    }
+
+    /// Like `read_yaml`, but also returns the ETag of the bytes that were parsed, so it
+    /// always describes the data returned (see `content_etag`). Only the file itself is
+    /// hashed, not the files it `$include`s.
+    async fn read_yaml_tagged(
+        &self,
+        schema_name: &str,
+        file_path: Option<&str>,
+        format: Option<DataFormat>,
+    ) -> ApiResult<(Value, String)> {
+        let yaml_path = self.resolve_yaml_path(schema_name, file_path).await?;
         
-        if !yaml_path.exists() {
+        if !self.store.exists(&yaml_path).await {
             return Err(ApiError::FileNotFound(format!(
                 "YAML file not found: {}",
                 yaml_path.display()
             )));
         }
 
-        let content = fs::read_to_string(&yaml_path)
-            .await
-            .map_err(ApiError::IoError)?;
+        let content = self.read_data_content(&yaml_path).await?;
+        let etag = content_etag(content.as_bytes());
+        let data = parse_as(&yaml_path, format, &content)?;
+        let root = canonical_or_lexical(&yaml_path);
+        let data = self.resolve_includes(data, &yaml_path, &mut vec![root]).await?;
+        Ok((data, etag))
+    }
+
+    /// Replaces every `{"$include": "relative/path.yaml"}` node with the parsed content
+    /// of that file, recursively. Include paths resolve from the including file's folder
+    /// and must stay inside the allowed data directories.
+    ///
+    /// `chain` holds the canonical paths of the files currently being expanded; meeting
+    /// one of them again is an include cycle and fails instead of recursing forever.
+    fn resolve_includes<'a>(
+        &'a self,
+        value: Value,
+        current_file: &'a Path,
+        chain: &'a mut Vec<PathBuf>,
+    ) -> BoxFuture<'a, ApiResult<Value>> {
+        Box::pin(async move {
+            match value {
+                Value::Object(fields) => {
+                    if let Some(target) = include_target(&fields) {
+                        return self.expand_include(target, current_file, chain).await;
+                    }
+                    let mut resolved = serde_json::Map::with_capacity(fields.len());
+                    for (key, child) in fields {
+                        resolved.insert(key, self.resolve_includes(child, current_file, chain).await?);
+                    }
+                    Ok(Value::Object(resolved))
+                }
+                Value::Array(items) => {
+                    let mut resolved = Vec::with_capacity(items.len());
+                    for item in items {
+                        resolved.push(self.resolve_includes(item, current_file, chain).await?);
+                    }
+                    Ok(Value::Array(resolved))
+                }
+                scalar => Ok(scalar),
+            }
+        })
+    }
+
+    /// Loads one include target and resolves its own includes.
+    async fn expand_include(
+        &self,
+        target: &str,
+        current_file: &Path,
+        chain: &mut Vec<PathBuf>,
+    ) -> ApiResult<Value> {
+        if Path::new(target).is_absolute() {
+            return Err(ApiError::BadRequest(format!(
+                "Include path must be relative: {} (in {})",
+                target,
+                current_file.display()
+            )));
+        }
 
-        let yaml_data: Value = serde_yaml::from_str(&content)
-            .map_err(|e| ApiError::YamlParseError(e.to_string()))?;
+        let include_path = current_file.parent().unwrap_or(&self.data_dir).join(target);
+        self.ensure_allowed(&include_path)?;
+        if !self.store.exists(&include_path).await {
+            return Err(ApiError::FileNotFound(format!(
+                "Included file not found: {} (in {})",
+                include_path.display(),
+                current_file.display()
+            )));
+        }
 
-        // Validate against schema
-        if let Some(schema) = self.schemas.get(schema_name) {
-            schema
-                .validate(yaml_data.borrow()) // Use .borrow() for validation 
-                .map_err(|errors| {
-                    let error_messages: Vec<String> = errors
-                        .map(|e| e.to_string())
-                        .collect();
-                    ApiError::ValidationError(format!("Schema validation failed: {:?}", error_messages))
-                })?;
+        let canonical = canonical_or_lexical(&include_path);
+        if chain.contains(&canonical) {
+            let cycle: Vec<String> = chain
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|path| path.display().to_string())
+                .collect();
+            return Err(ApiError::YamlParseError(format!("Include cycle: {}", cycle.join(" -> "))));
         }
 
-        Ok(yaml_data)
+        let included = self.parse_data_file(&include_path, None).await?;
+        chain.push(canonical);
+        let resolved = self.resolve_includes(included, &include_path, chain).await;
+        chain.pop();
+        resolved
     }
 
+    /// Validates the data file against the schema named `schema_name`.
+    #[cfg(test)]
     pub async fn validate_yaml_data(
         &self,
         schema_name: &str,
         file_path: Option<&str>,
     ) -> ApiResult<Value> {
-        let schema = self.schemas.get(schema_name).ok_or_else(|| {
-            ApiError::NotFound(format!("Schema '{}' not found", schema_name))
-        })?;
+        Ok(self.validate_yaml_data_with_override(schema_name, file_path, None, None, false).await?.0)
+    }
 
-        let yaml_data = self.get_yaml_data(schema_name, file_path).await?;
-        
-        // Perform validation (already done in get_yaml_data, but re-validate for clarity)
-        schema
-            .validate(&yaml_data)
-            .map_err(|errors| {
-                let error_messages: Vec<String> = errors
-                    .map(|e| e.to_string())
-                    .collect();
-                ApiError::ValidationError(format!("Schema validation failed: {:?}", error_messages))
-            })?;
+    /// Validates the data file against `schema_override` when given, otherwise against
+    /// the schema named `schema_name`. Parses with `format` when the extension is ambiguous
+    /// and optionally tolerates unknown fields (see `get_yaml_data_with_override`).
+    ///
+    /// Returns the report and the ETag of the file content it was computed from.
+    pub async fn validate_yaml_data_with_override(
+        &self,
+        schema_name: &str,
+        file_path: Option<&str>,
+        schema_override: Option<&str>,
+        format: Option<DataFormat>,
+        allow_additional: bool,
+    ) -> ApiResult<(Value, String)> {
+        // load_yaml_data returns NotFound for an unknown schema and has already
+        // validated the data (a second pass would double-count validation metrics).
+        let LoadedData { data, warnings, etag } = self
+            .load_yaml_data(schema_name, file_path, schema_override, format, allow_additional)
+            .await?;
         
-        Ok(serde_json::json!({
-            "valid": true,
-            "data": yaml_data
-        }))
+        // In VALIDATION_MODE=warn tolerated violations make the data invalid, not an error.
+        let mut result = serde_json::json!({
+            "valid": warnings.is_empty(),
+            "data": data
+        });
+        if !warnings.is_empty() {
+            result["warnings"] = Value::from(warnings);
+        }
+        Ok((result, etag))
     }
 }
 
-// ====================================================
-// SECTION: Utility Methods (Content as provided)
-// ====================================================
+/// Lists tolerated violations under `_validation_warnings` when `data` is an object.
+/// Other roots have nowhere to carry them; callers needing them use the returned list.
+fn attach_validation_warnings(data: &mut Value, warnings: Vec<String>) {
+    if let (Value::Object(fields), false) = (data, warnings.is_empty()) {
+        fields.insert(VALIDATION_WARNINGS_FIELD.to_string(), Value::from(warnings));
+    }
+}
 
-impl YamlService {
-    pub async fn list_available_schemas(&self) -> ApiResult<Vec<String>> {
-        Ok(self.schemas.keys().cloned().collect())
+/// Deepest schema nesting `fill_defaults` follows (guards against recursive `$ref`s).
+const MAX_DEFAULTS_DEPTH: usize = 32;
+
+/// Inserts each property's schema `default` where `data` lacks that property, then
+/// recurses into nested `properties` and array `items`. Local `$ref`s (`#/...`) are
+/// resolved against `root`; refs to other documents are not followed.
+fn fill_defaults(schema: &Value, root: &Value, data: &mut Value, depth: usize) {
+    if depth > MAX_DEFAULTS_DEPTH {
+        return;
     }
+    let schema = match schema.get("$ref").and_then(Value::as_str) {
+        Some(reference) => match reference.strip_prefix('#').and_then(|pointer| root.pointer(pointer)) {
+            Some(target) => target,
+            None => return,
+        },
+        None => schema,
+    };
 
-    fn resolve_yaml_path(&self, schema_name: &str, file_path: Option<&str>) -> ApiResult<PathBuf> {
-        match file_path {
-            Some(path) => {
-                // If a specific file path is provided, use it relative to data_dir
-                let full_path = self.data_dir.join(path);
-                Ok(full_path)
+    match data {
+        Value::Object(fields) => {
+            let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+                return;
+            };
+            for (name, property) in properties {
+                if let Some(default) = property.get("default") {
+                    fields.entry(name.clone()).or_insert_with(|| default.clone());
+                }
+                if let Some(child) = fields.get_mut(name) {
+                    fill_defaults(property, root, child, depth + 1);
+                }
             }
-            None => {
-                // Default to schema_name.yaml in the data directory
-                let default_file = format!("{}.yaml", schema_name);
-                Ok(self.data_dir.join(default_file))
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items").filter(|s| s.is_object()) {
+                for item in items {
+                    fill_defaults(item_schema, root, item, depth + 1);
+                }
             }
         }
+        _ => {}
+    }
+}
+
+// ====================================================
+// SECTION: Data File Writes
+// ====================================================
+
+/// Strong ETag of a data file's raw content: the quoted hex SHA-256.
+pub fn content_etag(bytes: &[u8]) -> String {
+    format!("\"{:x}\"", Sha256::digest(bytes))
+}
+
+/// Whether an `If-Match` value (`*` or a comma-separated list of ETags) matches `current`.
+fn if_match_satisfied(if_match: &str, current: &str) -> bool {
+    if_match
+        .split(',')
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == current)
+}
+
+impl YamlService {
+    /// Replaces an existing data file with `data`, validated against `schema_name` and
+    /// written in the file's own format. Returns the new ETag.
+    ///
+    /// Optimistic concurrency: `if_match` must carry the ETag the caller last read (see
+    /// `load_yaml_data`). Without one the write is `PreconditionRequired`; if the file
+    /// changed since, it is a `Conflict` and the file is left untouched.
+    pub async fn save_yaml_data(
+        &self,
+        schema_name: &str,
+        file_path: Option<&str>,
+        data: &Value,
+        if_match: Option<&str>,
+    ) -> ApiResult<String> {
+        let if_match = if_match.filter(|tag| !tag.trim().is_empty()).ok_or_else(|| {
+            ApiError::PreconditionRequired("Writes require an If-Match header with the file's ETag".to_string())
+        })?;
+        self.validate_value(schema_name, data)?;
+
+        let path = self.resolve_yaml_path(schema_name, file_path).await?;
+        let content = match DataFormat::from_path(&path).unwrap_or(DataFormat::Yaml) {
+            DataFormat::Yaml => serde_yaml::to_string(data)
+                .map_err(|e| ApiError::SerializationError(format!("YAML serialization failed: {}", e)))?,
+            DataFormat::Json => serde_json::to_string_pretty(data)
+                .map_err(|e| ApiError::SerializationError(format!("JSON serialization failed: {}", e)))?,
+            DataFormat::Toml => toml::to_string(data)
+                .map_err(|e| ApiError::SerializationError(format!("TOML serialization failed: {}", e)))?,
+        };
+
+        let _guard = self.write_lock.lock().await;
+        let current = self.store.read(&path).await.map_err(|e| file_error(&path, e))?;
+        let current_etag = content_etag(&current);
+        if !if_match_satisfied(if_match, &current_etag) {
+            return Err(ApiError::Conflict(format!(
+                "{} was modified since it was read (current ETag {})",
+                path.display(),
+                current_etag
+            )));
+        }
+
+        self.store.write(&path, content.as_bytes()).await?;
+        info!("Saved {} ({} bytes)", path.display(), content.len());
+        Ok(content_etag(content.as_bytes()))
+    }
+}
+
+/// Maps a missing data file to `FileNotFound`, other IO errors to `IoError`.
+fn file_error(path: &Path, error: io::Error) -> ApiError {
+    match error.kind() {
+        io::ErrorKind::NotFound => ApiError::FileNotFound(format!("YAML file not found: {}", path.display())),
+        _ => ApiError::IoError(error),
+    }
+}
+
+// ====================================================
+// SECTION: Bulk Validation
+// ====================================================
+
+impl YamlService {
+    /// Validates every data file (`data_files`) against the schema matching its path
+    /// stem (e.g. `network/bgp.yaml` or `network/bgp.json` -> schema `network/bgp`).
+    ///
+    /// Files with no matching schema are reported as skipped rather than failed.
+    pub async fn validate_all(&self) -> ApiResult<ValidationSummary> {
+        let mut summary = ValidationSummary::default();
+
+        for path in self.data_files().await? {
+            if let Some(result) = self.validate_data_file(&path).await {
+                summary.record(result);
+            }
+        }
+
+        info!(
+            "Validated data files: {} total, {} passed, {} failed, {} skipped",
+            summary.total, summary.passed, summary.failed, summary.skipped
+        );
+        Ok(summary)
+    }
+
+    /// Every data file under `data_dir` in a supported format (`.yaml`, `.yml`, `.json`,
+    /// `.toml`), sorted by path.
+    pub async fn data_files(&self) -> ApiResult<Vec<PathBuf>> {
+        let mut files = collect_files(self.store.as_ref(), &self.data_dir, &DataFormat::DEFAULT_EXTENSIONS).await?;
+        files.sort();
+        Ok(files)
+    }
+
+    /// Data files belonging to `schema_name`: those whose file stem is the schema name (or
+    /// its last segment, e.g. `bgp` for `network/bgp`). With `validate`, every data file is
+    /// parsed: files matched by name get their current validation status and errors, and
+    /// any other file whose content validates is included too. Without it no file is read,
+    /// so listing stays cheap however many data files there are. Unknown schemas are `NotFound`.
+    pub async fn schema_data_files(&self, schema_name: &str, validate: bool) -> ApiResult<Vec<SchemaDataFile>> {
+        let schemas = self.schema_set();
+        let schema = schemas.try_get(schema_name)?.ok_or_else(|| {
+            ApiError::NotFound(format!("Schema '{}' not found", schema_name))
+        })?;
+        let short_name = schema_name.rsplit('/').next().unwrap_or(schema_name);
+
+        let mut files = Vec::new();
+        for path in self.data_files().await? {
+            let Some(relative) = path.strip_prefix(&self.data_dir).ok().and_then(Path::to_str) else {
+                continue;
+            };
+            let relative = relative.replace('\\', "/");
+            let stem_matches = relative_stem(&self.data_dir, &path).as_deref() == Some(schema_name)
+                || path.file_stem().and_then(|s| s.to_str()) == Some(short_name);
+            if !validate && !stem_matches {
+                continue;
+            }
+
+            // Parse errors count as invalid; files matched only by content are valid by definition.
+            let errors: Vec<String> = if !validate {
+                Vec::new()
+            } else {
+                match self.read_yaml(schema_name, Some(&relative), None).await {
+                    Ok(data) => match schema.validate(&data) {
+                        Ok(()) => Vec::new(),
+                        Err(errors) => errors.map(|e| format!("{}: {}", e.instance_path, e)).collect(),
+                    },
+                    Err(e) => vec![e.to_string()],
+                }
+            };
+            if !stem_matches && !errors.is_empty() {
+                continue;
+            }
+
+            let metadata = self.store.metadata(&path).await?;
+            files.push(SchemaDataFile {
+                path: relative,
+                size_bytes: metadata.len,
+                modified: metadata.modified.map(DateTime::<Utc>::from),
+                valid: validate.then_some(errors.is_empty()),
+                errors,
+            });
+        }
+        Ok(files)
+    }
+
+    /// Validates one data file (from `data_files`) against the schema matching its stem.
+    /// Returns `None` for paths outside `data_dir`.
+    pub async fn validate_data_file(&self, path: &Path) -> Option<FileValidationResult> {
+        let name = relative_stem(&self.data_dir, path)?;
+        let file = path.strip_prefix(&self.data_dir).ok()?.to_str()?.replace('\\', "/");
+
+        if !self.schema_set().contains(&name) {
+            return Some(FileValidationResult {
+                file,
+                schema: None,
+                valid: true,
+                errors: Vec::new(),
+            });
+        }
+
+        let errors = match self.get_yaml_data(&name, Some(&file)).await {
+            Ok(_) => Vec::new(),
+            Err(e) => vec![e.to_string()],
+        };
+        if !errors.is_empty() {
+            warn!("Validation failed for {}: {:?}", file, errors);
+        }
+
+        Some(FileValidationResult {
+            file,
+            schema: Some(name),
+            valid: errors.is_empty(),
+            errors,
+        })
+    }
+}
+
+impl YamlService {
+    /// Validates one data file and reports every violation with a fix suggestion,
+    /// instead of failing on the first error. Unknown schemas are `NotFound`.
+    pub async fn validate_file_report(
+        &self,
+        schema_name: &str,
+        file_path: &str,
+    ) -> ApiResult<FileValidationReport> {
+        let schemas = self.schema_set();
+        let schema = schemas.try_get(schema_name)?.ok_or_else(|| {
+            ApiError::NotFound(format!("Schema '{}' not found", schema_name))
+        })?;
+
+        let data = self.read_yaml(schema_name, Some(file_path), None).await?;
+        let issues: Vec<ValidationIssue> = match schema.validate(&data) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.map(|e| validation_issue(&e)).collect(),
+        };
+
+        Ok(FileValidationReport { valid: issues.is_empty(), issues })
+    }
+}
+
+// ====================================================
+// SECTION: Utility Methods (Content as provided)
+// ====================================================
+
+impl YamlService {
+    pub async fn list_available_schemas(&self) -> ApiResult<Vec<String>> {
+        Ok(self.schema_set().names())
+    }
+
+    async fn resolve_yaml_path(&self, schema_name: &str, file_path: Option<&str>) -> ApiResult<PathBuf> {
+        let full_path = match file_path {
+            Some(path) => {
+                // If a specific file path is provided, use it relative to data_dir
+                self.data_dir.join(path)
+            }
+            None => {
+                // Default to schema_name.yaml in the data directory. Nested schema
+                // names (e.g. "network/bgp") map onto the same sub-folders in data_dir.
+                // If no YAML file exists, a JSON or TOML file with the same stem is used.
+                let mut found = None;
+                for ext in DataFormat::DEFAULT_EXTENSIONS {
+                    let candidate = self.data_dir.join(format!("{}.{}", schema_name, ext));
+                    if self.store.exists(&candidate).await {
+                        found = Some(candidate);
+                        break;
+                    }
+                }
+                found.unwrap_or_else(|| self.data_dir.join(format!("{}.yaml", schema_name)))
+            }
+        };
+
+        self.ensure_allowed(&full_path)?;
+        Ok(full_path)
+    }
+
+    /// Rejects paths that resolve (after following `..` and symlinks) outside the allowlist.
+    fn ensure_allowed(&self, path: &Path) -> ApiResult<()> {
+        if self.allowed_dirs.iter().any(|root| resolve_path(path).starts_with(root)) {
+            Ok(())
+        } else {
+            Err(ApiError::BadRequest(format!(
+                "Path is outside the allowed data directories: {}",
+                path.display()
+            )))
+        }
+    }
+}
+
+/// Resolves `path` the way the filesystem would: its deepest existing ancestor is
+/// canonicalized (following symlinks), then the missing remainder is applied lexically.
+/// A file that doesn't exist yet therefore resolves to where it would be created.
+fn resolve_path(path: &Path) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    let mut resolved = loop {
+        if let Ok(canonical) = std::fs::canonicalize(existing) {
+            break canonical;
+        }
+        match (existing.parent(), existing.components().next_back()) {
+            (Some(parent), Some(last)) => {
+                missing.push(last);
+                existing = parent;
+            }
+            // Nothing exists (e.g. a bare relative path): resolve it purely lexically.
+            _ => break PathBuf::new(),
+        }
+    };
+
+    for component in missing.into_iter().rev() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    resolved
+}
+
+/// Key of an include node: `{"$include": "relative/path.yaml"}`.
+const INCLUDE_KEY: &str = "$include";
+
+/// The include target if `fields` is exactly an include node.
+fn include_target(fields: &serde_json::Map<String, Value>) -> Option<&str> {
+    match fields.get(INCLUDE_KEY) {
+        Some(Value::String(target)) if fields.len() == 1 => Some(target),
+        _ => None,
+    }
+}
+
+/// The canonical form of `path` when it exists on the local filesystem, otherwise the
+/// path itself (files served from a non-local `FileStore`).
+fn canonical_or_lexical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+impl YamlService {
+    /// Reads a text file under `data_dir` (e.g. a replay fixture) through the file store,
+    /// with the same allowlist and size limit as data files.
+    pub async fn read_data_text(&self, relative: &Path) -> ApiResult<String> {
+        let path = self.data_dir.join(relative);
+        self.ensure_allowed(&path)?;
+        let size = self.store.metadata(&path).await.map_err(|e| file_error(&path, e))?.len;
+        if size > max_file_bytes() {
+            return Err(ApiError::BadRequest(format!(
+                "File {} is {} bytes, exceeding the {} byte limit",
+                path.display(),
+                size,
+                max_file_bytes()
+            )));
+        }
+        self.store.read_to_string(&path).await.map_err(|e| file_error(&path, e))
+    }
+
+    /// Reads and parses one data file, choosing the parser from `format` or the extension.
+    async fn parse_data_file(&self, path: &Path, format: Option<DataFormat>) -> ApiResult<Value> {
+        let content = self.read_data_content(path).await?;
+        parse_as(path, format, &content)
+    }
+
+    /// Reads a data file's raw content, within the size limit.
+    async fn read_data_content(&self, path: &Path) -> ApiResult<String> {
+        ensure_within_size(self.store.as_ref(), path, max_file_bytes()).await?;
+        self.file_reader
+            .read_to_string(path)
+            .await
+            .map_err(ApiError::IoError)
+    }
+}
+
+/// Parses `content` with `format`, or the parser for `path`'s extension (YAML when unknown).
+fn parse_as(path: &Path, format: Option<DataFormat>, content: &str) -> ApiResult<Value> {
+    format
+        .or_else(|| DataFormat::from_path(path))
+        .unwrap_or(DataFormat::Yaml)
+        .parse(content)
+}
+
+/// Default upper bound on a data file's size (10 MiB).
+const DEFAULT_MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Largest data file that will be read (`MAX_FILE_BYTES`, default 10 MiB).
+fn max_file_bytes() -> u64 {
+    env::var("MAX_FILE_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_FILE_BYTES)
+}
+
+/// Rejects files larger than `max_bytes` using their metadata, before any of the
+/// content is read into memory.
+async fn ensure_within_size(store: &dyn FileStore, path: &Path, max_bytes: u64) -> ApiResult<()> {
+    let size = store.metadata(path).await.map_err(ApiError::IoError)?.len;
+    if size > max_bytes {
+        return Err(ApiError::BadRequest(format!(
+            "File {} is {} bytes, exceeding the {} byte limit",
+            path.display(),
+            size,
+            max_bytes
+        )));
+    }
+    Ok(())
+}
+
+// ====================================================
+// SECTION: Validation Helpers
+// ====================================================
+
+/// Validates `data` against `schema`, recording latency and failure metrics
+/// labeled by schema name.
+fn validate_with_metrics(
+    schema_name: &str,
+    schema: &JSONSchema,
+    data: &Value,
+    allow_additional: bool,
+) -> ApiResult<()> {
+    let error_messages = validation_errors_with_metrics(schema_name, schema, data, allow_additional);
+    if error_messages.is_empty() {
+        Ok(())
+    } else {
+        Err(ApiError::ValidationError(format!("Schema validation failed: {:?}", error_messages)))
+    }
+}
+
+/// Validates `data` (recording validation metrics) and returns one message per violation.
+///
+/// With `allow_additional`, `additionalProperties` violations are dropped, so data
+/// carrying fields a schema doesn't know yet still passes.
+fn validation_errors_with_metrics(
+    schema_name: &str,
+    schema: &JSONSchema,
+    data: &Value,
+    allow_additional: bool,
+) -> Vec<String> {
+    let started = Instant::now();
+    let error_messages: Vec<String> = match schema.validate(data) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .filter(|e| !(allow_additional && matches!(e.kind, ValidationErrorKind::AdditionalProperties { .. })))
+            .map(|e| e.to_string())
+            .collect(),
+    };
+
+    let schema_label = schema_name.to_string();
+    histogram!("yaml_validation_duration_seconds", "schema" => schema_label.clone())
+        .record(started.elapsed().as_secs_f64());
+    counter!("yaml_validations_total", "schema" => schema_label.clone()).increment(1);
+    if !error_messages.is_empty() {
+        counter!("yaml_validation_failures_total", "schema" => schema_label).increment(1);
+    }
+
+    error_messages
+}
+
+/// Converts a validator error into a `ValidationIssue` with a keyword-specific suggestion.
+fn validation_issue(error: &ValidationError) -> ValidationIssue {
+    let (keyword, suggestion) = match &error.kind {
+        ValidationErrorKind::Required { property } => (
+            "required",
+            format!("Add the missing property {}.", property),
+        ),
+        ValidationErrorKind::Type { .. } => (
+            "type",
+            "Change the value to the type the schema expects (check quoting of numbers and booleans).".to_string(),
+        ),
+        ValidationErrorKind::Enum { options } => (
+            "enum",
+            format!("Use one of the allowed values: {}.", options),
+        ),
+        ValidationErrorKind::Constant { expected_value } => (
+            "const",
+            format!("Set the value to {}.", expected_value),
+        ),
+        ValidationErrorKind::AdditionalProperties { unexpected } => (
+            "additionalProperties",
+            format!("Remove the unknown properties: {}.", unexpected.join(", ")),
+        ),
+        ValidationErrorKind::MinLength { limit } => (
+            "minLength",
+            format!("Use a value of at least {} characters.", limit),
+        ),
+        ValidationErrorKind::MaxLength { limit } => (
+            "maxLength",
+            format!("Shorten the value to at most {} characters.", limit),
+        ),
+        ValidationErrorKind::Minimum { limit } => (
+            "minimum",
+            format!("Use a value greater than or equal to {}.", limit),
+        ),
+        ValidationErrorKind::Maximum { limit } => (
+            "maximum",
+            format!("Use a value less than or equal to {}.", limit),
+        ),
+        ValidationErrorKind::MinItems { limit } => (
+            "minItems",
+            format!("Add items so the list has at least {}.", limit),
+        ),
+        ValidationErrorKind::MaxItems { limit } => (
+            "maxItems",
+            format!("Remove items so the list has at most {}.", limit),
+        ),
+        ValidationErrorKind::UniqueItems => (
+            "uniqueItems",
+            "Remove the duplicate entries from the list.".to_string(),
+        ),
+        ValidationErrorKind::Pattern { pattern } => (
+            "pattern",
+            format!("Change the value to match the pattern {}.", pattern),
+        ),
+        ValidationErrorKind::Format { format } => (
+            "format",
+            format!("Use a valid {} value.", format),
+        ),
+        _ => (
+            "other",
+            "Adjust the value so it satisfies the schema.".to_string(),
+        ),
+    };
+
+    ValidationIssue {
+        pointer: error.instance_path.to_string(),
+        keyword: keyword.to_string(),
+        message: error.to_string(),
+        suggestion,
+    }
+}
+
+// ====================================================
+// SECTION: Filesystem Helpers
+// ====================================================
+
+/// Recursively collects every file under `root` with one of the given extensions, sorted
+/// for deterministic ordering (and log output) across filesystems.
+///
+/// Symlinked files are followed, but symlinked directories are skipped (they can form
+/// cycles) and so are dangling links, each with a warning.
+async fn collect_files(store: &dyn FileStore, root: &Path, extensions: &[&str]) -> ApiResult<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = store.list(&dir).await.map_err(ApiError::IoError)?;
+
+        for path in entries {
+            let mut metadata = store.symlink_metadata(&path).await.map_err(ApiError::IoError)?;
+            if metadata.is_symlink {
+                metadata = match store.metadata(&path).await {
+                    Ok(target) if target.is_dir => {
+                        warn!("Skipping symlinked directory {}", path.display());
+                        continue;
+                    }
+                    Ok(target) => target,
+                    Err(e) => {
+                        warn!("Skipping dangling symlink {}: {}", path.display(), e);
+                        continue;
+                    }
+                };
+            }
+
+            if metadata.is_dir {
+                pending.push(path);
+            } else if path.extension().and_then(|s| s.to_str()).is_some_and(|ext| extensions.contains(&ext)) {
+                found.push(path);
+            }
+        }
+    }
+
+    found.sort();
+    Ok(found)
+}
+
+/// Builds a name for `path` relative to `root`, without its final extension.
+/// Separators are always normalized to `/` so names are stable across platforms.
+fn relative_stem(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let stem = relative.file_stem()?.to_str()?;
+
+    let mut parts: Vec<String> = match relative.parent() {
+        Some(parent) => parent
+            .components()
+            .map(|c| c.as_os_str().to_str().map(str::to_string))
+            .collect::<Option<Vec<_>>>()?,
+        None => Vec::new(),
+    };
+    parts.push(stem.to_string());
+
+    Some(parts.join("/"))
+}
+
+// ====================================================
+// SECTION: Tests
+// ====================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const ITEMS_SCHEMA: &str = r#"{
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "array",
+        "items": {
+            "type": "object",
+            "required": ["id", "title"],
+            "properties": {
+                "id": { "type": "string" },
+                "title": { "type": "string" }
+            }
+        }
+    }"#;
+
+    /// Test fixture: temp schema/data dirs populated with the given files.
+    /// The `TempDir` must be kept alive for the duration of the test.
+    struct Fixture {
+        _root: TempDir,
+        service: YamlService,
+    }
+
+    async fn fixture(schemas: &[(&str, &str)], data: &[(&str, &str)]) -> Fixture {
+        let root = tempfile::tempdir().expect("create temp dir");
+        let schema_dir = root.path().join("schemas");
+        let data_dir = root.path().join("data");
+
+        for (dir, files) in [(&schema_dir, schemas), (&data_dir, data)] {
+            std::fs::create_dir_all(dir).expect("create dir");
+            for (name, content) in files {
+                let path = dir.join(name);
+                std::fs::create_dir_all(path.parent().unwrap()).expect("create parent dir");
+                std::fs::write(path, content).expect("write file");
+            }
+        }
+
+        let service = YamlService::new_with_dirs(&schema_dir, &data_dir)
+            .await
+            .expect("service should initialize");
+        Fixture { _root: root, service }
+    }
+
+    #[tokio::test]
+    async fn get_yaml_data_returns_parsed_valid_data() {
+        let fx = fixture(
+            &[("items.schema.json", ITEMS_SCHEMA)],
+            &[("items.yaml", "- id: a\n  title: Alpha\n")],
+        )
+        .await;
+
+        let data = fx.service.get_yaml_data("items", None).await.unwrap();
+        assert_eq!(data, serde_json::json!([{ "id": "a", "title": "Alpha" }]));
+    }
+
+    #[tokio::test]
+    async fn nested_schema_tree_derives_path_names() {
+        let fx = fixture(
+            &[
+                ("items.schema.json", ITEMS_SCHEMA),
+                ("network/ospf.schema.json", ITEMS_SCHEMA),
+                ("network/bgp/peer.schema.json", ITEMS_SCHEMA),
+            ],
+            &[("network/bgp/peer.yaml", "- id: p1\n  title: Peer\n")],
+        )
+        .await;
+
+        let names = fx.service.list_available_schemas().await.unwrap();
+        for expected in ["items", "network/ospf", "network/bgp/peer"] {
+            assert!(names.iter().any(|n| n == expected), "missing {expected} in {names:?}");
+        }
+
+        let data = fx.service.get_yaml_data("network/bgp/peer", None).await.unwrap();
+        assert_eq!(data, serde_json::json!([{ "id": "p1", "title": "Peer" }]));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn data_paths_must_resolve_inside_data_dir() {
+        let fx = fixture(
+            &[("items.schema.json", ITEMS_SCHEMA)],
+            &[("network/bgp/peer.yaml", "- id: p1\n  title: Peer\n")],
+        )
+        .await;
+        let outside = fx._root.path().join("outside");
+        std::fs::create_dir_all(&outside).unwrap();
+        std::fs::write(outside.join("secret.yaml"), "- id: s\n  title: Secret\n").unwrap();
+        std::os::unix::fs::symlink(&outside, fx._root.path().join("data/escape")).unwrap();
+
+        let nested = fx.service.get_yaml_data("items", Some("network/bgp/peer.yaml")).await;
+        assert_eq!(nested.unwrap()[0]["id"], "p1");
+        let missing = fx.service.get_yaml_data("items", Some("network/bgp/absent.yaml")).await;
+        assert!(matches!(missing, Err(ApiError::FileNotFound(_))), "got {:?}", missing);
+
+        for escape in ["../outside/secret.yaml", "network/../../outside/secret.yaml", "escape/secret.yaml", "escape/absent.yaml"] {
+            let result = fx.service.get_yaml_data("items", Some(escape)).await;
+            assert!(matches!(result, Err(ApiError::BadRequest(_))), "{} gave {:?}", escape, result);
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn file_walk_skips_symlinked_directories_and_dangling_links() {
+        let fx = fixture(
+            &[("items.schema.json", ITEMS_SCHEMA)],
+            &[("items.yaml", "- id: a\n  title: Alpha\n"), ("network/bgp.yaml", "- id: b\n  title: Beta\n")],
+        )
+        .await;
+        let root = fx._root.path();
+        for dir in ["schemas", "data"] {
+            std::os::unix::fs::symlink(root.join(dir), root.join(dir).join("loop")).unwrap();
+        }
+        std::os::unix::fs::symlink(root.join("missing.schema.json"), root.join("schemas/broken.schema.json")).unwrap();
+        std::os::unix::fs::symlink(root.join("missing.yaml"), root.join("data/broken.yaml")).unwrap();
+        std::os::unix::fs::symlink(root.join("data/items.yaml"), root.join("data/linked.yaml")).unwrap();
+
+        let files = fx.service.data_files().await.unwrap();
+        let names: Vec<_> = files.iter().map(|path| path.strip_prefix(root.join("data")).unwrap()).collect();
+        assert_eq!(names, [Path::new("items.yaml"), Path::new("linked.yaml"), Path::new("network/bgp.yaml")]);
+
+        fx.service.reload_schemas().await.unwrap();
+        assert!(fx.service.list_available_schemas().await.unwrap().contains(&"items".to_string()));
+    }
+
+    #[tokio::test]
+    async fn validate_yaml_data_wraps_result() {
+        let fx = fixture(
+            &[("items.schema.json", ITEMS_SCHEMA)],
+            &[("items.yaml", "- id: a\n  title: Alpha\n")],
+        )
+        .await;
+
+        let result = fx.service.validate_yaml_data("items", None).await.unwrap();
+        assert_eq!(result["valid"], serde_json::json!(true));
+        assert_eq!(result["data"][0]["id"], serde_json::json!("a"));
+    }
+
+    #[tokio::test]
+    async fn missing_data_file_is_file_not_found() {
+        let fx = fixture(&[("items.schema.json", ITEMS_SCHEMA)], &[]).await;
+
+        let err = fx.service.get_yaml_data("items", None).await.unwrap_err();
+        assert!(matches!(err, ApiError::FileNotFound(_)), "got {:?}", err);
+    }
+
+    #[tokio::test]
+    async fn invalid_yaml_is_parse_error() {
+        let fx = fixture(
+            &[("items.schema.json", ITEMS_SCHEMA)],
+            &[("items.yaml", "- id: [unclosed\n")],
+        )
+        .await;
+
+        let err = fx.service.get_yaml_data("items", None).await.unwrap_err();
+        assert!(matches!(err, ApiError::YamlParseError(_)), "got {:?}", err);
+    }
+
+    #[tokio::test]
+    async fn schema_violation_is_validation_error() {
+        let fx = fixture(
+            &[("items.schema.json", ITEMS_SCHEMA)],
+            &[("items.yaml", "- id: a\n")],
+        )
+        .await;
+
+        let err = fx.service.get_yaml_data("items", None).await.unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError(_)), "got {:?}", err);
+    }
+
+    #[tokio::test]
+    async fn get_yaml_data_unknown_schema_is_not_found() {
+        let fx = fixture(&[], &[("bogus.yaml", "- id: a\n")]).await;
+
+        let err = fx.service.get_yaml_data("bogus", None).await.unwrap_err();
+        assert!(matches!(err, ApiError::NotFound(_)), "got {:?}", err);
+    }
+
+    #[tokio::test]
+    async fn unvalidated_read_ignores_unknown_schema() {
+        let fx = fixture(&[], &[("bogus.yaml", "- id: a\n")]).await;
+
+        let data = fx.service.get_unvalidated_yaml_data("bogus", None).await.unwrap();
+        assert_eq!(data, serde_json::json!([{ "id": "a" }]));
+    }
+
+    #[tokio::test]
+    async fn json_data_file_is_used_when_no_yaml_exists() {
+        let fx = fixture(
+            &[("items.schema.json", ITEMS_SCHEMA)],
+            &[("items.json", r#"[{ "id": "a", "title": "Alpha" }]"#)],
+        )
+        .await;
+
+        let data = fx.service.get_yaml_data("items", None).await.unwrap();
+        assert_eq!(data, serde_json::json!([{ "id": "a", "title": "Alpha" }]));
+    }
+
+    #[test]
+    fn toml_parses_into_unified_value() {
+        let value = DataFormat::from_name("TOML")
+            .unwrap()
+            .parse("title = \"Alpha\"\n[meta]\nversion = 2\n")
+            .unwrap();
+        assert_eq!(value, serde_json::json!({ "title": "Alpha", "meta": { "version": 2 } }));
+        assert!(matches!(DataFormat::from_name("xml"), Err(ApiError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn validate_file_report_lists_issues_with_suggestions() {
+        let fx = fixture(
+            &[("items.schema.json", ITEMS_SCHEMA)],
+            &[("items.yaml", "- id: a\n- id: 7\n  title: Seven\n")],
+        )
+        .await;
+
+        let report = fx.service.validate_file_report("items", "items.yaml").await.unwrap();
+        assert!(!report.valid);
+
+        let keywords: Vec<(&str, &str)> = report
+            .issues
+            .iter()
+            .map(|i| (i.pointer.as_str(), i.keyword.as_str()))
+            .collect();
+        assert!(keywords.contains(&("/0", "required")), "got {:?}", keywords);
+        assert!(keywords.contains(&("/1/id", "type")), "got {:?}", keywords);
+        assert!(report.issues.iter().all(|i| !i.suggestion.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn ref_resolves_definition_in_sibling_schema_file() {
+        let common = r#"{
+            "definitions": {
+                "Ipv4": { "type": "string", "pattern": "^[0-9]+(\\.[0-9]+){3}$" }
+            }
+        }"#;
+        let device = r#"{
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": { "ip": { "$ref": "common.schema.json#/definitions/Ipv4" } }
+        }"#;
+        let fx = fixture(
+            &[("common.schema.json", common), ("device.schema.json", device)],
+            &[("device.yaml", "ip: 10.0.0.1\n"), ("bad.yaml", "ip: not-an-ip\n")],
+        )
+        .await;
+
+        assert!(fx.service.get_yaml_data("device", None).await.is_ok());
+        let err = fx.service.get_yaml_data("device", Some("bad.yaml")).await.unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError(_)), "got {:?}", err);
+    }
+
+    #[tokio::test]
+    async fn local_ref_inside_referenced_file_resolves_against_that_file() {
+        let b = r##"{
+            "definitions": {
+                "x": { "type": "object", "properties": { "asn": { "$ref": "#/definitions/y" } } },
+                "y": { "type": "integer" }
+            }
+        }"##;
+        let a = r#"{
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": { "peer": { "$ref": "b.schema.json#/definitions/x" } }
+        }"#;
+        let fx = fixture(
+            &[("network/a.schema.json", a), ("network/b.schema.json", b)],
+            &[("network/a.yaml", "peer:\n  asn: 65001\n"), ("bad.yaml", "peer:\n  asn: not-a-number\n")],
+        )
+        .await;
+
+        assert!(fx.service.get_yaml_data("network/a", None).await.is_ok());
+        let err = fx.service.get_yaml_data("network/a", Some("bad.yaml")).await.unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError(_)), "got {:?}", err);
+    }
+
+    #[test]
+    fn custom_formats_accept_valid_and_reject_invalid_values() {
+        for valid in ["10.0.0.0/24", "0.0.0.0/0", "2001:db8::/32"] {
+            assert!(is_cidr(valid), "{} should be a cidr", valid);
+        }
+        for invalid in ["10.0.0.0", "10.0.0.0/33", "2001:db8::/129", "10.0.0/24", "host/24"] {
+            assert!(!is_cidr(invalid), "{} should not be a cidr", invalid);
+        }
+
+        for valid in ["65001", "AS65001", "as4200000000"] {
+            assert!(is_asn(valid), "{} should be an asn", valid);
+        }
+        for invalid in ["0", "AS", "4294967296", "-1", "AS 65001", "65001a"] {
+            assert!(!is_asn(invalid), "{} should not be an asn", invalid);
+        }
+
+        for valid in ["ge-0/0/0", "xe-1/0/2.100", "et-0/0/1:2", "ae0", "lo0.0", "irb"] {
+            assert!(is_interface_name(valid), "{} should be an interface name", valid);
+        }
+        for invalid in ["", "0ge-0/0/0", "ge-0/0/0 ", "ge-0/0/", "ge 0/0/0", "x".repeat(65).as_str()] {
+            assert!(!is_interface_name(invalid), "{:?} should not be an interface name", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn invalid_formats_are_reported_as_format_issues() {
+        let schema = r#"{
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": {
+                "mgmt_ip": { "type": "string", "format": "ipv4" },
+                "hostname": { "type": "string", "format": "hostname" },
+                "prefix": { "type": "string", "format": "cidr" },
+                "asn": { "type": "string", "format": "asn" },
+                "uplink": { "type": "string", "format": "interface-name" }
+            }
+        }"#;
+        let fx = fixture(
+            &[("device.schema.json", schema)],
+            &[
+                ("good.yaml", "mgmt_ip: 10.0.0.1\nhostname: r1.example.net\nprefix: 10.0.0.0/24\nasn: AS65001\nuplink: ge-0/0/0\n"),
+                ("bad.yaml", "mgmt_ip: 10.0.0.300\nhostname: -bad-\nprefix: 10.0.0.0/40\nasn: '0'\nuplink: ge 0/0/0\n"),
+            ],
+        )
+        .await;
+
+        assert!(fx.service.validate_file_report("device", "good.yaml").await.unwrap().valid);
+
+        let report = fx.service.validate_file_report("device", "bad.yaml").await.unwrap();
+        let mut pointers: Vec<&str> = report
+            .issues
+            .iter()
+            .filter(|i| i.keyword == "format")
+            .map(|i| i.pointer.as_str())
+            .collect();
+        pointers.sort_unstable();
+        assert_eq!(pointers, vec!["/asn", "/hostname", "/mgmt_ip", "/prefix", "/uplink"]);
+    }
+
+    #[tokio::test]
+    async fn bundle_defs_are_loaded_as_individual_schemas() {
+        let bundle = r##"{
+            "$defs": {
+                "navigation": {
+                    "type": "object",
+                    "required": ["title"],
+                    "properties": { "title": { "type": "string" } }
+                },
+                "menu": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/navigation" }
+                }
+            }
+        }"##;
+        let root = tempfile::tempdir().expect("create temp dir");
+        let (schema_dir, data_dir) = (root.path().join("schemas"), root.path().join("data"));
+        std::fs::create_dir_all(&schema_dir).unwrap();
+        std::fs::create_dir_all(&data_dir).unwrap();
+        std::fs::write(schema_dir.join("items.schema.json"), ITEMS_SCHEMA).unwrap();
+        let bundle_path = root.path().join("bundle.schema.json");
+        std::fs::write(&bundle_path, bundle).unwrap();
+
+        let service = YamlService::new_with_bundle(&schema_dir, &data_dir, Some(bundle_path))
+            .await
+            .expect("service should initialize");
+
+        assert_eq!(service.schema_set().health.loaded, vec!["bundle:menu", "bundle:navigation", "items", "job_event"]);
+        let set = service.schema_set();
+        assert_eq!(set.sources["bundle:navigation"]["required"], serde_json::json!(["title"]));
+        assert_eq!(set.sources["items"], serde_json::from_str::<Value>(ITEMS_SCHEMA).unwrap());
+        assert!(service.validate_value("bundle:navigation", &serde_json::json!({ "title": "Home" })).is_ok());
+        assert!(service.validate_value("bundle:navigation", &serde_json::json!({})).is_err());
+        assert!(service.validate_value("bundle:menu", &serde_json::json!([{ "title": "Home" }])).is_ok());
+        assert!(service.validate_value("bundle:menu", &serde_json::json!([{ "title": 1 }])).is_err());
+    }
+
+    #[tokio::test]
+    async fn allow_additional_serves_unknown_fields() {
+        let schema = r#"{
+            "type": "object",
+            "required": ["title"],
+            "properties": { "title": { "type": "string" } },
+            "additionalProperties": false
+        }"#;
+        let fx = fixture(&[("page.schema.json", schema)], &[("page.yaml", "title: Home
+badge: new
+")]).await;
+
+        let err = fx.service.get_yaml_data("page", None).await.unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError(_)), "got {:?}", err);
+
+        let data = fx.service
+            .get_yaml_data_with_override("page", None, None, None, true)
+            .await
+            .unwrap();
+        assert_eq!(data, serde_json::json!({ "title": "Home", "badge": "new" }));
+    }
+
+    #[tokio::test]
+    async fn validation_mode_warn_and_off_serve_nonconforming_data() {
+        let schema = r#"{
+            "type": "object",
+            "required": ["title"],
+            "properties": { "title": { "type": "string" } }
+        }"#;
+        let mut fx = fixture(&[("page.schema.json", schema)], &[("page.yaml", "title: 7\n")]).await;
+
+        fx.service.validation_mode = ValidationMode::Warn;
+        let data = fx.service.get_yaml_data("page", None).await.unwrap();
+        assert_eq!(data["title"], serde_json::json!(7));
+        assert_eq!(data[VALIDATION_WARNINGS_FIELD].as_array().map(Vec::len), Some(1));
+        let result = fx.service.validate_yaml_data("page", None).await.unwrap();
+        assert_eq!(result["valid"], serde_json::json!(false));
+
+        // An array root has no field to carry warnings; they come back beside the data.
+        let list_schema = format!(r#"{{ "type": "array", "items": {} }}"#, schema);
+        let mut fx_list = fixture(&[("pages.schema.json", &list_schema)], &[("pages.yaml", "- title: 7\n")]).await;
+        fx_list.service.validation_mode = ValidationMode::Warn;
+        let loaded = fx_list.service
+            .load_yaml_data("pages", None, None, None, false)
+            .await
+            .unwrap();
+        assert_eq!(loaded.data[0]["title"], serde_json::json!(7));
+        assert_eq!(loaded.warnings.len(), 1);
+        let result = fx_list.service.validate_yaml_data("pages", None).await.unwrap();
+        assert_eq!(result["valid"], serde_json::json!(false));
+        assert_eq!(result["warnings"].as_array().map(Vec::len), Some(1));
+
+        fx.service.validation_mode = ValidationMode::Off;
+        let data = fx.service.get_yaml_data("page", None).await.unwrap();
+        assert!(data.get(VALIDATION_WARNINGS_FIELD).is_none());
+
+        fx.service.validation_mode = ValidationMode::Reject;
+        let err = fx.service.get_yaml_data("page", None).await.unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError(_)), "got {:?}", err);
+    }
+
+    #[tokio::test]
+    async fn nested_includes_are_inlined_before_validation() {
+        let fx = fixture(
+            &[("items.schema.json", ITEMS_SCHEMA)],
+            &[
+                ("items.yaml", "- $include: parts/alpha.yaml\n- id: b\n  title: Beta\n"),
+                ("parts/alpha.yaml", "id: a\ntitle:\n  $include: titles/alpha.yaml\n"),
+                ("parts/titles/alpha.yaml", "Alpha\n"),
+            ],
+        )
+        .await;
+
+        let data = fx.service.get_yaml_data("items", None).await.unwrap();
+        assert_eq!(
+            data,
+            serde_json::json!([{ "id": "a", "title": "Alpha" }, { "id": "b", "title": "Beta" }])
+        );
+    }
+
+    #[tokio::test]
+    async fn include_cycles_and_escapes_are_errors() {
+        let fx = fixture(
+            &[("items.schema.json", ITEMS_SCHEMA)],
+            &[
+                ("items.yaml", "- $include: a.yaml\n"),
+                ("a.yaml", "$include: b.yaml\n"),
+                ("b.yaml", "$include: a.yaml\n"),
+                ("escape.yaml", "- $include: ../schemas/items.schema.json\n"),
+            ],
+        )
+        .await;
+
+        let err = fx.service.get_unvalidated_yaml_data("items", None).await.unwrap_err();
+        assert!(
+            matches!(&err, ApiError::YamlParseError(msg) if msg.contains("Include cycle")),
+            "unexpected error: {err:?}"
+        );
+
+        let err = fx.service.get_unvalidated_yaml_data("items", Some("escape.yaml")).await.unwrap_err();
+        assert!(matches!(err, ApiError::BadRequest(_)), "unexpected error: {err:?}");
+    }
+
+    #[tokio::test]
+    async fn schema_data_files_match_by_stem_or_validated_content() {
+        let store = Arc::new(crate::services::file_store::InMemoryFileStore::new());
+        store.insert("/mem/schemas/items.schema.json", ITEMS_SCHEMA);
+        store.insert("/mem/data/items.yaml", "- id: a\n");
+        store.insert("/mem/data/lists/more.yaml", "- id: b\n  title: Beta\n");
+        store.insert("/mem/data/other.yaml", "name: unrelated\n");
+        let service = YamlService::new_with_store("/mem/schemas", "/mem/data", None, store)
+            .await
+            .expect("service should initialize");
+
+        // Without validation only names are matched; no file is parsed.
+        let files = service.schema_data_files("items", false).await.unwrap();
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["items.yaml"]);
+        assert!(files.iter().all(|f| f.valid.is_none() && f.errors.is_empty()));
+
+        // items.yaml matches by name but lacks a title; lists/more.yaml matches by content.
+        let files = service.schema_data_files("items", true).await.unwrap();
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, vec!["items.yaml", "lists/more.yaml"]);
+        assert_eq!(files[1].size_bytes, 22);
+        assert_eq!(files[0].valid, Some(false));
+        assert!(!files[0].errors.is_empty());
+        assert_eq!(files[1].valid, Some(true));
+
+        assert!(matches!(service.schema_data_files("missing", false).await, Err(ApiError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn validate_all_covers_every_data_format() {
+        let store = Arc::new(crate::services::file_store::InMemoryFileStore::new());
+        store.insert("/mem/schemas/items.schema.json", ITEMS_SCHEMA);
+        store.insert("/mem/data/items.json", r#"[{ "id": "a", "title": "Alpha" }]"#);
+        store.insert("/mem/data/network/ospf.toml", "area = 0\n");
+        store.insert("/mem/data/tags.yml", "- core\n");
+        store.insert("/mem/data/README.md", "not data\n");
+        let service = YamlService::new_with_store("/mem/schemas", "/mem/data", None, store)
+            .await
+            .expect("service should initialize");
+
+        let summary = service.validate_all().await.unwrap();
+        let files: Vec<&str> = summary.results.iter().map(|r| r.file.as_str()).collect();
+        assert_eq!(files, vec!["items.json", "network/ospf.toml", "tags.yml"]);
+        assert_eq!((summary.passed, summary.failed, summary.skipped), (1, 0, 2));
+    }
+
+    #[tokio::test]
+    async fn lazy_schemas_compile_on_first_use() {
+        let store = Arc::new(crate::services::file_store::InMemoryFileStore::new());
+        store.insert("/mem/schemas/items.schema.json", ITEMS_SCHEMA);
+        store.insert("/mem/schemas/broken.schema.json", r#"{ "type": "not-a-type" }"#);
+        store.insert("/mem/data/items.yaml", "- id: a\n  title: Alpha\n");
+        let mut service = YamlService::new_with_store("/mem/schemas", "/mem/data", None, store)
+            .await
+            .expect("service should initialize");
+        service.lazy_schemas = true;
+        service.reload_schemas().await.unwrap();
+
+        let schemas = service.schema_set();
+        assert!(schemas.contains("items") && schemas.contains("broken"));
+        assert!(!schemas.is_compiled("items"));
+
+        service.get_yaml_data("items", None).await.unwrap();
+        assert!(schemas.is_compiled("items"));
+
+        // A compile error only shows up when the schema is first used.
+        assert!(!schemas.is_compiled("broken"));
+        assert!(matches!(
+            service.validate_value("broken", &serde_json::json!({})),
+            Err(ApiError::ValidationError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn save_yaml_data_enforces_if_match() {
+        let store = Arc::new(crate::services::file_store::InMemoryFileStore::new());
+        store.insert("/mem/schemas/items.schema.json", ITEMS_SCHEMA);
+        store.insert("/mem/data/items.yaml", "- id: a\n  title: Alpha\n");
+        let service = YamlService::new_with_store("/mem/schemas", "/mem/data", None, store.clone())
+            .await
+            .expect("service should initialize");
+
+        // The ETag served with the data is the hash of the bytes it was parsed from.
+        let etag = service.load_yaml_data("items", None, None, None, false).await.unwrap().etag;
+        assert_eq!(etag, content_etag(b"- id: a\n  title: Alpha\n"));
+        let edited = serde_json::json!([{ "id": "a", "title": "Renamed" }]);
+
+        // Missing ETag: rejected before touching the file.
+        assert!(matches!(
+            service.save_yaml_data("items", None, &edited, None).await,
+            Err(ApiError::PreconditionRequired(_))
+        ));
+
+        // Matching ETag: written, and the returned ETag is the new file's.
+        let new_etag = service.save_yaml_data("items", None, &edited, Some(&etag)).await.unwrap();
+        assert_ne!(new_etag, etag);
+        let reloaded = service.load_yaml_data("items", None, None, None, false).await.unwrap();
+        assert_eq!((reloaded.data, reloaded.etag), (edited.clone(), new_etag));
+        assert_eq!(service.get_yaml_data("items", None).await.unwrap(), edited);
+
+        // Stale ETag: a second editor still holding the old one gets a conflict.
+        let clobber = serde_json::json!([{ "id": "a", "title": "Clobbered" }]);
+        assert!(matches!(
+            service.save_yaml_data("items", None, &clobber, Some(&etag)).await,
+            Err(ApiError::Conflict(_))
+        ));
+        assert_eq!(service.get_yaml_data("items", None).await.unwrap(), edited);
+    }
+
+    #[tokio::test]
+    async fn service_reads_through_an_in_memory_store() {
+        let store = Arc::new(crate::services::file_store::InMemoryFileStore::new());
+        store.insert("/mem/schemas/items.schema.json", ITEMS_SCHEMA);
+        store.insert("/mem/data/items.yaml", "- id: a\n  title: Alpha\n- $include: parts/b.yaml\n");
+        store.insert("/mem/data/parts/b.yaml", "id: b\ntitle: Beta\n");
+
+        let service = YamlService::new_with_store("/mem/schemas", "/mem/data", None, store)
+            .await
+            .expect("service should initialize");
+
+        assert!(service.schema_set().contains("items"));
+        let data = service.get_yaml_data("items", None).await.unwrap();
+        assert_eq!(
+            data,
+            serde_json::json!([{ "id": "a", "title": "Alpha" }, { "id": "b", "title": "Beta" }])
+        );
+        assert_eq!(service.data_files().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn apply_defaults_fills_missing_properties_from_the_schema() {
+        let schema = r##"{
+            "type": "array",
+            "items": { "$ref": "#/definitions/item" },
+            "definitions": {
+                "item": {
+                    "type": "object",
+                    "required": ["id"],
+                    "properties": {
+                        "id": { "type": "string" },
+                        "visible": { "type": "boolean", "default": true },
+                        "meta": {
+                            "type": "object",
+                            "default": {},
+                            "properties": { "order": { "type": "integer", "default": 0 } }
+                        }
+                    }
+                }
+            }
+        }"##;
+        let fx = fixture(&[("menu.schema.json", schema)], &[]).await;
+
+        let data = serde_json::json!([{ "id": "a" }, { "id": "b", "visible": false, "meta": { "order": 3 } }]);
+        let repaired = fx.service.apply_defaults("menu", &data).unwrap();
+        assert_eq!(
+            repaired,
+            serde_json::json!([
+                { "id": "a", "visible": true, "meta": { "order": 0 } },
+                { "id": "b", "visible": false, "meta": { "order": 3 } }
+            ])
+        );
+        assert!(fx.service.validate_value("menu", &repaired).is_ok());
+        assert!(matches!(fx.service.apply_defaults("missing", &data), Err(ApiError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn oversized_files_are_rejected_before_reading() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let path = dir.path().join("big.yaml");
+        std::fs::write(&path, "x".repeat(2048)).expect("write file");
+
+        let err = ensure_within_size(&LocalFileStore, &path, 1024).await.unwrap_err();
+        assert!(
+            matches!(&err, ApiError::BadRequest(msg) if msg.contains("2048 bytes")),
+            "unexpected error: {err:?}"
+        );
+        assert!(ensure_within_size(&LocalFileStore, &path, 2048).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn built_in_job_event_schema_is_loaded() {
+        let fx = fixture(&[], &[]).await;
+
+        let event = serde_json::json!({
+            "job_id": "j1",
+            "event_type": "progress",
+            "status": "running",
+            "timestamp": "2024-01-01T00:00:00Z"
+        });
+        assert!(fx.service.validate_value(JOB_EVENT_SCHEMA_NAME, &event).is_ok());
+        let err = fx
+            .service
+            .validate_value(JOB_EVENT_SCHEMA_NAME, &serde_json::json!({ "job_id": "j1" }))
+            .unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError(_)), "got {:?}", err);
+    }
+
+    #[tokio::test]
+    async fn job_events_validate_against_their_job_type_schema() {
+        let backup_schema = r#"{
+            "type": "object",
+            "required": ["data"],
+            "properties": { "data": { "type": "object", "required": ["archive"] } }
+        }"#;
+        let fx = fixture(&[("event_backup.schema.json", backup_schema)], &[]).await;
+
+        let backup = serde_json::json!({ "job_type": "backup", "data": {} });
+        assert_eq!(fx.service.job_event_schema_name(&backup), "event_backup");
+        assert!(fx.service.validate_job_event(&backup).is_err());
+        let backup = serde_json::json!({ "job_type": "backup", "data": { "archive": "r1.tgz" } });
+        assert!(fx.service.validate_job_event(&backup).is_ok());
+
+        // No event_restore schema: the generic job_event schema applies.
+        let restore = serde_json::json!({ "job_type": "restore", "data": {} });
+        assert_eq!(fx.service.job_event_schema_name(&restore), JOB_EVENT_SCHEMA_NAME);
+        assert!(fx.service.validate_job_event(&restore).is_err());
+    }
+
+    #[tokio::test]
+    async fn duplicate_schema_names_prefer_schema_json_and_are_reported() {
+        let lenient = r#"{ "type": "array" }"#;
+        let fx = fixture(
+            &[("items.json", lenient), ("items.schema.json", ITEMS_SCHEMA)],
+            &[("items.yaml", "- id: a\n")],
+        )
+        .await;
+
+        let schemas = fx.service.schema_set();
+        let collisions = &schemas.health.collisions;
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(collisions[0].name, "items");
+        assert_eq!(collisions[0].files.len(), 2);
+        assert!(collisions[0].used.ends_with("items.schema.json"));
+
+        // The stricter items.schema.json is applied, so the missing title fails.
+        let err = fx.service.get_yaml_data("items", None).await.unwrap_err();
+        assert!(matches!(err, ApiError::ValidationError(_)), "got {:?}", err);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn validations_run_concurrently_with_repeated_reloads() {
+        let fx = fixture(&[("items.schema.json", ITEMS_SCHEMA)], &[]).await;
+        let service = Arc::new(fx.service);
+        let valid = serde_json::json!([{ "id": "a", "title": "Alpha" }]);
+        let invalid = serde_json::json!([{ "id": "a" }]);
+
+        let reloader = {
+            let service = service.clone();
+            tokio::spawn(async move {
+                for _ in 0..25 {
+                    service.reload_schemas().await.expect("reload should succeed");
+                }
+            })
+        };
+        let validators: Vec<_> = (0..4)
+            .map(|_| {
+                let (service, valid, invalid) = (service.clone(), valid.clone(), invalid.clone());
+                tokio::spawn(async move {
+                    for _ in 0..200 {
+                        assert!(service.validate_value("items", &valid).is_ok());
+                        assert!(service.validate_value("items", &invalid).is_err());
+                        tokio::task::yield_now().await;
+                    }
+                })
+            })
+            .collect();
+
+        reloader.await.unwrap();
+        for validator in validators {
+            validator.await.unwrap();
+        }
+        assert!(service.schema_set().contains("items"));
+    }
+
+    #[tokio::test]
+    async fn validate_yaml_data_unknown_schema_is_not_found() {
+        let fx = fixture(&[("items.schema.json", ITEMS_SCHEMA)], &[]).await;
+
+        let err = fx.service.validate_yaml_data("bogus", None).await.unwrap_err();
+        assert!(matches!(err, ApiError::NotFound(_)), "got {:?}", err);
     }
 }
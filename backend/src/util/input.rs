@@ -0,0 +1,79 @@
+// File Path: backend/src/util/input.rs
+
+//! # Request Input Validation
+//!
+//! Whitelist checks for names and paths taken from requests (`?schema=`, `?file=`,
+//! path segments, body fields) before they reach the services. Anything outside the
+//! whitelist, including control characters, is an `ApiError::BadRequest`.
+
+use crate::models::{ApiError, ApiResult};
+
+/// Longest accepted identifier (schema name).
+const MAX_IDENTIFIER_LEN: usize = 128;
+
+/// Longest accepted relative path.
+const MAX_PATH_LEN: usize = 255;
+
+/// Checks a schema-style identifier: ASCII letters, digits, `-` and `_`, with `/`
+/// between non-empty segments for nested names (`network/bgp`) and `:` for bundle
+/// entries (`bundle:navigation`). Returns the value for chaining.
+pub fn validate_identifier<'a>(field: &str, value: &'a str) -> ApiResult<&'a str> {
+    let valid = !value.is_empty()
+        && value.len() <= MAX_IDENTIFIER_LEN
+        && value.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'))
+        });
+    if !valid {
+        return Err(invalid(field, value));
+    }
+    Ok(value)
+}
+
+/// Checks a path relative to the data directory: identifier characters plus `.` in
+/// file names (`nav.admin.yaml`), `/` between segments. Absolute paths, empty, `.` or
+/// `..` segments and backslashes are rejected. Returns the value for chaining.
+pub fn validate_relative_path<'a>(field: &str, value: &'a str) -> ApiResult<&'a str> {
+    let valid = !value.is_empty()
+        && value.len() <= MAX_PATH_LEN
+        && value.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        });
+    if !valid {
+        return Err(invalid(field, value));
+    }
+    Ok(value)
+}
+
+fn invalid(field: &str, value: &str) -> ApiError {
+    // Debug formatting escapes control characters so they don't reach the logs raw.
+    ApiError::BadRequest(format!("Invalid {}: {:?}", field, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifiers_follow_the_whitelist() {
+        for ok in ["navigation", "settings_navigation", "network/bgp", "event-backup", "bundle:navigation"] {
+            assert_eq!(validate_identifier("schema", ok).unwrap(), ok);
+        }
+        for bad in ["", "/navigation", "network//bgp", "nav.yaml", "../etc", "nav\0", "nav\nigation", "a b"] {
+            assert!(matches!(validate_identifier("schema", bad), Err(ApiError::BadRequest(_))), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn relative_paths_cannot_escape_or_smuggle_characters() {
+        for ok in ["navigation.yaml", "inventories/basement.yaml", "navigation.admin.yaml", "lab-1/site_a.yml"] {
+            assert_eq!(validate_relative_path("file", ok).unwrap(), ok);
+        }
+        for bad in ["", "/etc/passwd", "../secret.yaml", "a/../../b.yaml", "./a.yaml", "a//b.yaml", "a\\b.yaml", "a\tb.yaml", "a%2e.yaml"] {
+            assert!(matches!(validate_relative_path("file", bad), Err(ApiError::BadRequest(_))), "{:?}", bad);
+        }
+    }
+}
@@ -0,0 +1,154 @@
+// File Path: backend/src/util/merge.rs
+
+//! # JSON Deep Merge
+//!
+//! Merges an overlay `serde_json::Value` into a base value: objects merge key by key
+//! (recursively), scalars and `null` in the overlay replace the base value, and arrays
+//! follow a configurable [`ArrayStrategy`].
+
+use serde_json::Value;
+
+/// How arrays present in both the base and the overlay are combined.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ArrayStrategy {
+    /// The overlay array replaces the base array (default).
+    #[default]
+    Replace,
+    /// Overlay items are appended after the base items.
+    Concat,
+    /// Object items sharing the same value for the key are merged recursively; new items
+    /// are appended. Falls back to `Replace` unless every item on both sides is an object
+    /// carrying the key.
+    MergeByKey(String),
+}
+
+/// Deep-merges `overlay` into `base`, combining arrays according to `arrays`.
+pub fn deep_merge(base: &mut Value, overlay: &Value, arrays: &ArrayStrategy) {
+    match (base, overlay) {
+        (Value::Object(base_map), Value::Object(overlay_map)) => {
+            for (key, overlay_value) in overlay_map {
+                match base_map.get_mut(key) {
+                    Some(base_value) => deep_merge(base_value, overlay_value, arrays),
+                    None => {
+                        base_map.insert(key.clone(), overlay_value.clone());
+                    }
+                }
+            }
+        }
+        (Value::Array(base_items), Value::Array(overlay_items)) => match arrays {
+            ArrayStrategy::Replace => *base_items = overlay_items.clone(),
+            ArrayStrategy::Concat => base_items.extend(overlay_items.iter().cloned()),
+            ArrayStrategy::MergeByKey(key) => {
+                if !is_keyed(base_items, key) || !is_keyed(overlay_items, key) {
+                    *base_items = overlay_items.clone();
+                    return;
+                }
+                for overlay_item in overlay_items {
+                    let existing = base_items
+                        .iter_mut()
+                        .find(|item| item.get(key) == overlay_item.get(key));
+                    match existing {
+                        Some(base_item) => deep_merge(base_item, overlay_item, arrays),
+                        None => base_items.push(overlay_item.clone()),
+                    }
+                }
+            }
+        },
+        (base, overlay) => *base = overlay.clone(),
+    }
+}
+
+/// True if every item is an object carrying `key`.
+fn is_keyed(items: &[Value], key: &str) -> bool {
+    items.iter().all(|item| item.get(key).is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn by_id() -> ArrayStrategy {
+        ArrayStrategy::MergeByKey("id".to_string())
+    }
+
+    #[test]
+    fn nested_objects_merge_recursively() {
+        let mut base = json!({ "a": { "b": 1, "c": { "d": 2 } }, "keep": true });
+        deep_merge(&mut base, &json!({ "a": { "c": { "e": 3 } }, "new": "x" }), &ArrayStrategy::Replace);
+
+        assert_eq!(base, json!({ "a": { "b": 1, "c": { "d": 2, "e": 3 } }, "keep": true, "new": "x" }));
+    }
+
+    #[test]
+    fn scalars_and_type_changes_replace() {
+        let mut base = json!({ "n": 1, "s": "old", "obj": { "x": 1 }, "list": [1] });
+        deep_merge(&mut base, &json!({ "n": 2, "s": "new", "obj": "flat", "list": { "k": 1 } }), &ArrayStrategy::Replace);
+
+        assert_eq!(base, json!({ "n": 2, "s": "new", "obj": "flat", "list": { "k": 1 } }));
+    }
+
+    #[test]
+    fn null_overlay_sets_null_and_null_base_is_replaced() {
+        let mut base = json!({ "a": 1, "b": null });
+        deep_merge(&mut base, &json!({ "a": null, "b": { "c": 1 } }), &ArrayStrategy::Replace);
+
+        assert_eq!(base, json!({ "a": null, "b": { "c": 1 } }));
+
+        let mut root = Value::Null;
+        deep_merge(&mut root, &json!({ "x": 1 }), &ArrayStrategy::Replace);
+        assert_eq!(root, json!({ "x": 1 }));
+    }
+
+    #[test]
+    fn replace_strategy_swaps_arrays() {
+        let mut base = json!({ "list": [1, 2, 3] });
+        deep_merge(&mut base, &json!({ "list": [4] }), &ArrayStrategy::Replace);
+
+        assert_eq!(base, json!({ "list": [4] }));
+    }
+
+    #[test]
+    fn concat_strategy_appends_items() {
+        let mut base = json!({ "list": [1, 2] });
+        deep_merge(&mut base, &json!({ "list": [2, 3] }), &ArrayStrategy::Concat);
+
+        assert_eq!(base, json!({ "list": [1, 2, 2, 3] }));
+    }
+
+    #[test]
+    fn merge_by_key_merges_matching_items_and_appends_new_ones() {
+        let mut base = json!([
+            { "id": "a", "title": "Alpha", "meta": { "x": 1 } },
+            { "id": "b", "title": "Beta" }
+        ]);
+        let overlay = json!([
+            { "id": "a", "meta": { "y": 2 } },
+            { "id": "c", "title": "Gamma" }
+        ]);
+        deep_merge(&mut base, &overlay, &by_id());
+
+        assert_eq!(base, json!([
+            { "id": "a", "title": "Alpha", "meta": { "x": 1, "y": 2 } },
+            { "id": "b", "title": "Beta" },
+            { "id": "c", "title": "Gamma" }
+        ]));
+    }
+
+    #[test]
+    fn merge_by_key_applies_to_nested_arrays() {
+        let mut base = json!({ "items": [{ "id": 1, "children": [{ "id": "x", "v": 1 }] }] });
+        let overlay = json!({ "items": [{ "id": 1, "children": [{ "id": "x", "v": 2 }, { "id": "y" }] }] });
+        deep_merge(&mut base, &overlay, &by_id());
+
+        assert_eq!(base, json!({ "items": [{ "id": 1, "children": [{ "id": "x", "v": 2 }, { "id": "y" }] }] }));
+    }
+
+    #[test]
+    fn merge_by_key_replaces_arrays_without_the_key() {
+        let mut base = json!({ "tags": ["a", "b"], "mixed": [{ "id": 1 }, { "name": "no id" }] });
+        deep_merge(&mut base, &json!({ "tags": ["c"], "mixed": [{ "id": 2 }] }), &by_id());
+
+        assert_eq!(base, json!({ "tags": ["c"], "mixed": [{ "id": 2 }] }));
+    }
+}
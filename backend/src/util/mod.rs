@@ -0,0 +1,11 @@
+// src/util/mod.rs
+
+//! # Utilities Module
+//!
+//! Small, dependency-free building blocks shared by services and handlers.
+
+
+// Deep-merging of JSON values (overlays, config composition)
+pub mod merge;
+// Whitelist validation of schema names and relative paths taken from requests
+pub mod input;